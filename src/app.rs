@@ -7,35 +7,112 @@ use gtk::gdk::prelude::FontMapExt;
 use gtk::gdk::ScrollDirection;
 use gtk::prelude::*;
 
-use adw::prelude::*;
 use once_cell::sync::{Lazy, OnceCell};
 use pango::FontDescription;
 use parking_lot::RwLock;
 use relm4::factory::FactoryVec;
 use relm4::*;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::background;
+use crate::bell;
 use crate::bridge;
 use crate::bridge::{
-    EditorMode, MouseButton, ParallelCommand, RedrawEvent, SerialCommand, UiCommand, WindowAnchor,
+    ClipboardReply, EditorMode, MessageKind, MouseButton, ParallelCommand, RedrawEvent, Register,
+    SerialCommand, UiCommand, WindowAnchor,
 };
-use crate::components::{VimCmdEvent, VimCmdPrompts};
-use crate::cursor::{CursorMode, VimCursor};
+use crate::components::{
+    VimCmdEvent, VimCmdPrompts, VimMessageHistory, VimMessageHistoryEvent, VimPopupMenu,
+    VimPopupMenuEvent, VimStatusStrip, VimStatusStripEvent, VimTabline, VimTablineEvent,
+};
+use crate::cursor::{self, CursorMode, CursorOverride, VimCursor};
 use crate::event_aggregator::EVENT_AGGREGATOR;
 use crate::grapheme::Coord;
-use crate::keys::ToInput;
-use crate::metrics::Metrics;
+use crate::guifont::{GuiFont, Zoom};
+use crate::guioptions::GuiOptions;
+use crate::keys::{self, ToInput};
+use crate::metrics::{CellSize, Metrics};
+use crate::preedit::Preedit;
+use crate::running_tracker::Shutdown;
+use crate::redraw::{RedrawScheduler, Target};
+use crate::session::WindowState;
 use crate::vimview::{self, VimGrid, VimMessage};
+use crate::wheel::{self, Wheel};
 use crate::Opts;
 
 #[allow(non_upper_case_globals)]
 pub static GridActived: Lazy<Arc<atomic::AtomicU64>> =
     Lazy::new(|| Arc::new(atomic::AtomicU64::new(0)));
 
+/// Cell under mouse pointer in grid of `GridActived`, as `(col, row)`.
+#[allow(non_upper_case_globals)]
+pub static PointerPosition: Lazy<Arc<RwLock<(u32, u32)>>> =
+    Lazy::new(|| Arc::new(RwLock::new((0, 0))));
+
+/// Opacity of main window in bits of f64, queried from neovim.
+#[allow(non_upper_case_globals)]
+pub static WindowOpacity: Lazy<Arc<atomic::AtomicU64>> =
+    Lazy::new(|| Arc::new(atomic::AtomicU64::new(1f64.to_bits())));
+
+/// `ambiwidth` is double, ambiguous width characters take two cells.
+#[allow(non_upper_case_globals)]
+pub static AmbiWidthDouble: Lazy<Arc<atomic::AtomicBool>> =
+    Lazy::new(|| Arc::new(atomic::AtomicBool::new(false)));
+
+/// `emoji` is set, emoji take two cells, on by default as neovim.
+#[allow(non_upper_case_globals)]
+pub static EmojiDoubleWidth: Lazy<Arc<atomic::AtomicBool>> =
+    Lazy::new(|| Arc::new(atomic::AtomicBool::new(true)));
+
+/// Cell size of current metrics, queried from neovim.
+#[allow(non_upper_case_globals)]
+pub static WindowCellSize: Lazy<Arc<RwLock<CellSize>>> =
+    Lazy::new(|| Arc::new(RwLock::new(CellSize::default())));
+
+/// Font used before `guifont` set, or it has no size.
+const DEFAULT_FONT: &str = "monospace 11";
+
+/// highlight group of error messages, kept on screen before quit.
+const ERROR_HLGROUP: &str = "ErrorMsg";
+/// highlight group of rule above scrolled messages.
+const MSGSEPARATOR_HLGROUP: &str = "MsgSeparator";
+/// messages shown at once, older ones collapsed into "+N more".
+const MAX_MESSAGES: usize = 5;
+
 #[derive(Clone, Debug)]
 pub enum AppMessage {
     Quit,
     ShowPointer,
+    // typing hides pointer by `--mousehide`.
+    HidePointer,
+    DismissOverlays,
+    // window of `:messages` closed.
+    MessageHistoryHidden,
+    DismissMessage(u64),
+    ExpandMessages,
+    BellFlashed,
+    SetOpacity(f64),
+    SetTitle(String),
+    GuiOptions(GuiOptions),
+    ToggleTerminalCursor,
+    ToggleTrailingWhitespace,
+    ToggleLigatures,
+    ToggleFullscreen,
+    Zoom(Zoom),
+    // scale factor of window changed, e.g. moved to another monitor.
+    ScaleFactor(f64),
+    Find(String),
+    FindClose,
+    // grid columns of `colorcolumn` of window, sent by neovim when it may change.
+    ColorColumns { window: u64, columns: Vec<usize> },
+    // window shows a terminal buffer or not, sent by neovim when it may change.
+    Terminal { window: u64, terminal: bool },
+    ReadClipboard(Register, ClipboardReply),
+    WriteClipboard(Register, String),
+    // paste text of clipboard by gui.
+    Paste(Register),
+    // files dropped onto grid, if any under pointer.
+    DropFiles(Vec<String>, Option<u64>),
     UiCommand(UiCommand),
     RedrawEvent(RedrawEvent),
 }
@@ -50,14 +127,30 @@ pub struct AppModel {
     pub opts: Opts,
 
     pub title: String,
+    // title sent by neovim, by `titlestring` or its default.
+    pub vim_title: String,
+    // title set by `reovim#set_title()`, over the one of neovim.
+    pub gui_title: Option<String>,
     pub size: Rc<Cell<(i32, i32)>>,
     pub default_width: i32,
     pub default_height: i32,
+    // size of last session, by `--remember-window-size`.
+    pub window_state: Option<WindowState>,
 
     pub guifont: Option<String>,
     pub guifontset: Option<String>,
     pub guifontwide: Option<String>,
     pub metrics: Rc<Cell<Metrics>>,
+    pub scale_factor: Cell<f64>,
+    pub opacity: f64,
+    // image painted beneath grids, by `--background-image`.
+    pub background_image: Option<cairo::ImageSurface>,
+    pub guioptions: GuiOptions,
+    // pattern of find on screen, `None` if closed.
+    pub find_pattern: Option<String>,
+    pub find_focus_changed: atomic::AtomicBool,
+    pub opacity_changed: atomic::AtomicBool,
+    pub fullscreen_toggled: atomic::AtomicBool,
     pub show_tab_line: Option<u64>,
 
     pub font_description: Rc<RefCell<pango::FontDescription>>,
@@ -66,8 +159,26 @@ pub struct AppModel {
     pub mode: EditorMode,
 
     pub mouse_on: Rc<atomic::AtomicBool>,
+    // pointer is over text of a grid, shown as I-beam.
+    pub pointer_over_grid: Rc<Cell<bool>>,
+    // fractional lines and columns of touchpad scrolling.
+    pub wheel: Rc<Cell<Wheel>>,
+    // message grid showing the more-prompt at its bottom.
+    pub more_prompt: Option<u64>,
+    // window flashed by visual bell.
+    pub bell_flash: bool,
+    // overlays opened, Escape dismisses them first by `--escape-dismiss-overlays`.
+    pub overlays: Rc<Cell<keys::Overlays>>,
+    pub message_history_open: bool,
+    pub popupmenu_open: bool,
     pub cursor: MicroComponent<VimCursor>,
     pub cursor_grid: u64,
+    // highlight trailing whitespace on screen.
+    pub trailing_whitespace: bool,
+    // shape ligatures of font in grids.
+    pub ligatures: bool,
+    // window grid owning focus, cmdline does not take it.
+    pub focused: u64,
     pub cursor_coord: Coord,
     pub cursor_coord_changed: atomic::AtomicBool,
     pub cursor_mode: usize,
@@ -76,17 +187,41 @@ pub struct AppModel {
     pub pctx: Rc<pango::Context>,
     pub gtksettings: OnceCell<gtk::Settings>,
     pub im_context: OnceCell<gtk::IMMulticontext>,
+    // composing text of input method and where the cursor is, in pixels.
+    pub preedit: Rc<RefCell<Preedit>>,
+    pub preedit_at: Rc<Cell<(f64, f64)>>,
+    // floats are moved into it by their grids, above all windows.
+    pub float_container: OnceCell<gtk::Fixed>,
+    // saved as window state of next session on quit.
+    pub main_window: OnceCell<gtk::ApplicationWindow>,
 
     pub hldefs: Rc<RwLock<vimview::HighlightDefinitions>>,
     pub hlgroups: Rc<RwLock<FxHashMap<String, u64>>>,
+    // highlight ids defined with the trailing whitespace match group.
+    pub whitespace_hlids: Rc<RwLock<FxHashSet<u64>>>,
+    // grid columns of `colorcolumn` by window, and color of `ColorColumn` for rules.
+    pub colorcolumns: FxHashMap<u64, Vec<usize>>,
+    pub colorcolumn_color: Option<gdk::RGBA>,
+    // windows showing a terminal buffer, `&buftype` is `terminal`.
+    pub terminals: FxHashSet<u64>,
 
-    pub background_changed: Rc<atomic::AtomicBool>,
+    // widgets drawn once at flush, not for every event.
+    pub redraw: RedrawScheduler,
 
     pub vgrids: crate::factory::FactoryMap<vimview::VimGrid>,
     pub messages: FactoryVec<vimview::VimMessage>,
+    // last serial given to a message.
+    pub message_serial: u64,
+    // older messages hidden behind newer ones, all shown once expanded.
+    pub collapsed_messages: usize,
+    pub messages_expanded: bool,
+    // grid of messages without ext_messages, drawn in message area.
+    pub message_grid: vimview::MessageGrid,
+    pub shutdown: Shutdown,
 
     pub dragging: Rc<Cell<Option<Dragging>>>,
     pub show_pointer: atomic::AtomicBool,
+    pub hide_pointer: atomic::AtomicBool,
 
     pub rt: tokio::runtime::Runtime,
 }
@@ -104,8 +239,12 @@ impl AppModel {
             .enable_io()
             .build()
             .unwrap();
-        let font_desc = FontDescription::from_string("monospace 11");
-        let size = Rc::new(Cell::new((opts.width, opts.height)));
+        let font_desc = FontDescription::from_string(DEFAULT_FONT);
+        let window_state = opts.remember_window_size.then(WindowState::load).flatten();
+        let (width, height) = window_state.map_or((opts.width, opts.height), |state| {
+            (state.width, state.height)
+        });
+        let size = Rc::new(Cell::new((width, height)));
         let pctx: Rc<pango::Context> = pangocairo::FontMap::default()
             .unwrap()
             .create_context()
@@ -125,26 +264,60 @@ impl AppModel {
             })
             .unwrap()
             .into();
-        let hldefs = Rc::new(RwLock::new(vimview::HighlightDefinitions::new()));
+        let hldefs = match opts.background.as_deref().map(gdk::RGBA::parse) {
+            Some(Ok(background)) => vimview::HighlightDefinitions::with_background(background),
+            Some(Err(_)) => {
+                log::warn!("invalid background color {:?}", opts.background);
+                vimview::HighlightDefinitions::new()
+            }
+            None => vimview::HighlightDefinitions::new(),
+        };
+        let hldefs = Rc::new(RwLock::new(hldefs));
         let metrics = Rc::new(Metrics::new().into());
         AppModel {
             size,
             title: opts.title.clone(),
-            default_width: opts.width,
-            default_height: opts.height,
+            vim_title: String::new(),
+            gui_title: None,
+            default_width: width,
+            default_height: height,
+            window_state,
             guifont: None,
             guifontset: None,
             guifontwide: None,
+            scale_factor: Cell::new(1.),
+            opacity: opts.opacity.clamp(0., 1.),
+            background_image: opts.background_image.as_deref().and_then(background::load),
+            guioptions: GuiOptions::default(),
+            find_pattern: None,
+            find_focus_changed: atomic::AtomicBool::new(false),
+            opacity_changed: atomic::AtomicBool::new(opts.opacity < 1.),
+            fullscreen_toggled: atomic::AtomicBool::new(false),
             show_tab_line: None,
 
             mode: EditorMode::Normal,
 
             mouse_on: Rc::new(false.into()),
+            pointer_over_grid: Rc::new(Cell::new(false)),
+            wheel: Rc::new(Cell::new(Wheel::default())),
+            more_prompt: None,
+            bell_flash: false,
+            overlays: Rc::new(Cell::new(keys::Overlays::default())),
+            message_history_open: false,
+            popupmenu_open: false,
             cursor: MicroComponent::new(
-                VimCursor::new(pctx.clone(), Rc::clone(&metrics), hldefs.clone()),
+                {
+                    let mut cursor =
+                        VimCursor::new(pctx.clone(), Rc::clone(&metrics), hldefs.clone());
+                    cursor.set_terminal_style(opts.terminal_cursor);
+                    cursor
+                },
                 (),
             ),
             cursor_grid: 0,
+            trailing_whitespace: opts.trailing_whitespace,
+            ligatures: !opts.no_ligatures,
+            focused: 0,
             cursor_mode: 0,
             cursor_modes: Vec::new(),
             cursor_coord: Coord::default(),
@@ -153,6 +326,10 @@ impl AppModel {
             pctx,
             gtksettings: OnceCell::new(),
             im_context: OnceCell::new(),
+            preedit: Rc::new(RefCell::new(Preedit::default())),
+            preedit_at: Rc::new(Cell::new((0., 0.))),
+            float_container: OnceCell::new(),
+            main_window: OnceCell::new(),
 
             metrics,
             font_description: Rc::new(RefCell::new(font_desc)),
@@ -160,14 +337,24 @@ impl AppModel {
 
             hldefs,
             hlgroups: Rc::new(RwLock::new(FxHashMap::default())),
+            whitespace_hlids: Rc::new(RwLock::new(FxHashSet::default())),
+            colorcolumns: FxHashMap::default(),
+            colorcolumn_color: None,
+            terminals: FxHashSet::default(),
 
-            background_changed: Rc::new(false.into()),
+            redraw: RedrawScheduler::default(),
 
             vgrids: crate::factory::FactoryMap::new(),
             messages: FactoryVec::new(),
+            message_serial: 0,
+            collapsed_messages: 0,
+            messages_expanded: false,
+            message_grid: vimview::MessageGrid::default(),
+            shutdown: Shutdown::default(),
 
             dragging: Rc::new(Cell::new(None)),
             show_pointer: true.into(),
+            hide_pointer: false.into(),
 
             opts,
 
@@ -187,7 +374,7 @@ impl AppModel {
             "{ | } ~ ",
             ""
         );
-        let desc = self.font_description.borrow_mut();
+        let mut desc = self.font_description.borrow_mut();
         log::debug!(
             "font desc {} {} {} {}",
             desc.family().unwrap(),
@@ -234,14 +421,319 @@ impl AppModel {
         {
             return;
         }
-        metrics.set_width(width.ceil());
-        metrics.set_ascent(ascent.ceil());
-        metrics.set_charwidth(charwidth.ceil());
-        metrics.set_charheight(charheight.ceil());
+        if self.opts.integer_cell_size {
+            // snapped from the unrounded advance, rounding it up first would
+            // drift the adjusted font size a whole logical pixel.
+            metrics.set_width(width);
+            metrics.set_ascent(ascent);
+            metrics.set_charwidth(charwidth);
+            metrics.set_charheight(charheight);
+            let scale = self.scale_factor.get();
+            metrics.snap_to_device_pixels(scale);
+            // trade exact font size for crispness.
+            if metrics.charwidth() != charwidth {
+                let size = desc.size() as f64 * metrics.charwidth() / charwidth;
+                desc.set_size(size.round() as i32);
+                self.pctx.set_font_description(&desc);
+                log::info!("font size adjusted to {} for scale {}", size / PANGO_SCALE, scale);
+            }
+        } else {
+            metrics.set_width(width.ceil());
+            metrics.set_ascent(ascent.ceil());
+            metrics.set_charwidth(charwidth.ceil());
+            metrics.set_charheight(charheight.ceil());
+        }
         log::info!("char-width {:?}", metrics.charwidth());
         log::info!("char-height {:?}", metrics.charheight());
         log::info!("char-ascent {:?}", metrics.ascent());
         self.metrics.replace(metrics);
+        self.publish_cell_size();
+    }
+
+    /// Share metrics with neovim side, answered by `reovim#cell_size()`.
+    /// Dots per inch of screen, from gtk settings or scale factor.
+    fn dpi(&self) -> f64 {
+        // gtk-xft-dpi is 1024 * dots per inch, -1 for default.
+        self.gtksettings
+            .get()
+            .map(|settings| settings.gtk_xft_dpi())
+            .filter(|dpi| *dpi > 0)
+            .map_or(96. * self.scale_factor.get(), |dpi| dpi as f64 / 1024.)
+    }
+
+    fn publish_cell_size(&self) {
+        let scale_factor = self.scale_factor.get();
+        let dpi = self.dpi();
+        let cell_size = self.metrics.get().cell_size(scale_factor, dpi);
+        log::debug!("cell size changed to {:?}", cell_size);
+        *WindowCellSize.write() = cell_size;
+    }
+
+    /// Command sent to neovim when the window is closed.
+    fn quit_command(&self) -> ParallelCommand {
+        if !self.opts.graceful_quit {
+            return ParallelCommand::Quit;
+        }
+        let timeout = match self.opts.quit_timeout {
+            0 => None,
+            ms => Some(std::time::Duration::from_millis(ms)),
+        };
+        ParallelCommand::QuitGracefully {
+            timeout,
+            confirm: self.opts.quit_confirm,
+        }
+    }
+
+    /// Clipboard of display backs `register`.
+    fn clipboard(&self, register: Register) -> gdk::Clipboard {
+        let display = gdk::Display::default().expect("no display to access clipboard");
+        match register {
+            Register::Primary => display.primary_clipboard(),
+            Register::Clipboard => display.clipboard(),
+        }
+    }
+
+    /// Rules of `colorcolumn` for grid, cached by its window.
+    fn sync_colorcolumns(&mut self, grid: u64) {
+        let color = self.colorcolumn_color;
+        if let Some(vgrid) = self.vgrids.get_mut(grid) {
+            let columns = self.colorcolumns.get(&vgrid.win()).cloned();
+            vgrid.set_colorcolumns(columns.unwrap_or_default(), color);
+        }
+    }
+
+    /// Message grid showing the more-prompt, always at its bottom row.
+    fn more_prompt(&self) -> Option<u64> {
+        let msggrid = &self.message_grid;
+        let grid = msggrid.grid().filter(|_| msggrid.scrolled())?;
+        let textbuf = self.vgrids.get(grid)?.textbuf().borrow();
+        let hlid = self
+            .hlgroups
+            .read()
+            .get(vimview::MORE_PROMPT_HLGROUP)
+            .copied();
+        let lines = textbuf.lines();
+        let row = textbuf.rows().checked_sub(1)?;
+        let more_prompt = lines
+            .get(row)
+            .map_or(false, |line| vimview::is_more_prompt(line, hlid));
+        Some(grid).filter(|_| more_prompt)
+    }
+
+    /// Any cell of message grid highlighted by `ErrorMsg`.
+    fn message_shows_error(&self, grid: u64) -> bool {
+        let errmsg = match self.hlgroups.read().get(ERROR_HLGROUP).copied() {
+            Some(errmsg) => errmsg,
+            None => return false,
+        };
+        let textbuf = match self.vgrids.get(grid) {
+            Some(vgrid) => vgrid.textbuf().borrow(),
+            None => return false,
+        };
+        let lines = textbuf.lines();
+        (0..textbuf.rows())
+            .filter_map(|row| lines.get(row))
+            .any(|line| line.iter().any(|cell| cell.hldef == Some(errmsg)))
+    }
+
+    /// Terminal buffer of grid, cached by its window.
+    fn sync_terminal(&mut self, grid: u64) {
+        if let Some(vgrid) = self.vgrids.get_mut(grid) {
+            let terminal = self.terminals.contains(&vgrid.win());
+            vgrid.set_is_terminal(terminal);
+        }
+    }
+
+    fn sync_all_colorcolumns(&mut self) {
+        let grids: Vec<u64> = self.vgrids.iter().map(|(grid, _)| *grid).collect();
+        for grid in grids {
+            self.sync_colorcolumns(grid);
+        }
+    }
+
+    /// Highlight matches of find pattern in visible content of all grids.
+    fn find_on_screen(&mut self) {
+        let pattern = self.find_pattern.as_deref().unwrap_or("");
+        let color = gdk::RGBA::parse(&self.opts.find_color).ok();
+        for (_, vgrid) in self.vgrids.iter_mut() {
+            let matches = {
+                let textbuf = vgrid.textbuf().borrow();
+                let lines = textbuf.lines();
+                let rows: Vec<&[vimview::TextCell]> = (0..textbuf.rows())
+                    .filter_map(|row| lines.get(row))
+                    .map(|line| line.as_ref())
+                    .collect();
+                vimview::find(&rows, pattern)
+            };
+            vgrid.set_matches(matches, color);
+        }
+        self.vgrids.update_all();
+    }
+
+    /// Use `desc` as font of grids, metrics and size of neovim follow it.
+    fn set_font(&mut self, desc: pango::FontDescription) {
+        self.pctx.set_font_description(&desc);
+        self.gtksettings.get().map(|settings| {
+            settings.set_gtk_font_name(Some(&desc.to_str()));
+        });
+
+        self.font_description.replace(desc);
+
+        self.calculate();
+
+        // wide font may follow size of guifont.
+        let wide_font = self.wide_font();
+        self.vgrids.iter_mut().for_each(|(_, vgrid)| {
+            vgrid.set_wide_font(wide_font.clone());
+            vgrid.reset_cache();
+        });
+        self.cursor
+            .model_mut()
+            .map(|mut m| m.reset_cache())
+            .unwrap();
+
+        self.font_changed.store(true, atomic::Ordering::Relaxed);
+        self.cursor_coord_changed
+            .store(true, atomic::Ordering::Relaxed);
+    }
+
+    /// Size of font by `guifont`, zoom resets to it.
+    fn guifont_size(&self) -> i32 {
+        self.guifont
+            .as_deref()
+            .map(|guifont| GuiFont::parse(guifont).font_description(self.dpi()).size())
+            .filter(|size| *size > 0)
+            .unwrap_or_else(|| FontDescription::from_string(DEFAULT_FONT).size())
+    }
+
+    /// Font of double width cells by `guifontwide`, size of `guifont` if not given.
+    fn wide_font(&self) -> Option<pango::FontDescription> {
+        let guifontwide = self
+            .guifontwide
+            .as_deref()
+            .filter(|f| !f.trim().is_empty())?;
+        let mut desc = GuiFont::parse(guifontwide).font_description(self.dpi());
+        if desc.size() == 0 {
+            desc.set_size(self.font_description.borrow().size());
+        }
+        Some(desc)
+    }
+
+    /// Alpha of default background of grids, translucent below 1.
+    fn background_alpha(&self) -> f64 {
+        self.opts.background_opacity.clamp(0., 1.)
+    }
+
+    /// Color of trailing whitespace overlay, none if it is off.
+    fn trailing_whitespace_color(&self) -> Option<gdk::RGBA> {
+        if !self.trailing_whitespace {
+            return None;
+        }
+        match gdk::RGBA::parse(&self.opts.trailing_whitespace_color) {
+            Ok(color) => Some(color),
+            Err(err) => {
+                log::warn!(
+                    "invalid trailing whitespace color {}: {}",
+                    self.opts.trailing_whitespace_color,
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    /// Darken window grids without focus, only grids changed are updated.
+    fn dim_inactive(&mut self) {
+        let amount = match self.opts.dim_inactive {
+            Some(amount) => amount.clamp(0., 1.),
+            None => return,
+        };
+        let focused = self.focused;
+        let changed: Vec<(u64, f64)> = self
+            .vgrids
+            .iter()
+            .filter_map(|(grid, vgrid)| {
+                let dim = if vimview::is_dimmed(*grid, focused, vgrid.is_float()) {
+                    amount
+                } else {
+                    0.
+                };
+                (vgrid.dim() != dim).then(|| (*grid, dim))
+            })
+            .collect();
+        for (grid, dim) in changed {
+            log::debug!("grid {} dimmed by {}", grid, dim);
+            if let Some(vgrid) = self.vgrids.get_mut(grid) {
+                vgrid.set_dim(dim);
+            }
+        }
+    }
+
+    /// Collapse older messages over `MAX_MESSAGES`, unless expanded by user.
+    fn collapse_messages(&mut self) {
+        let shown: Vec<usize> = (0..self.messages.len())
+            .filter(|index| !self.messages.get(*index).unwrap().is_dismissed())
+            .collect();
+        let collapsed = if self.messages_expanded {
+            0
+        } else {
+            shown.len().saturating_sub(MAX_MESSAGES)
+        };
+        for (n, index) in shown.into_iter().enumerate() {
+            let collapse = n < collapsed;
+            if self.messages.get(index).unwrap().is_collapsed() != collapse {
+                if let Some(message) = self.messages.get_mut(index) {
+                    message.set_collapsed(collapse);
+                }
+            }
+        }
+        self.collapsed_messages = collapsed;
+    }
+
+    /// Beep or flash window by `--bell`, `visual` for `visual_bell`.
+    fn ring_bell(&mut self, visual: bool, sender: &Sender<AppMessage>) {
+        if self.opts.bell.beeps(visual) {
+            if let Some(display) = gdk::Display::default() {
+                display.beep();
+            }
+        }
+        if self.opts.bell.flashes(visual) {
+            self.bell_flash = true;
+            glib::timeout_add_local_once(
+                bell::FLASH,
+                glib::clone!(@strong sender => move || {
+                    sender.send(AppMessage::BellFlashed).ok();
+                }),
+            );
+        }
+    }
+
+    /// Title of window as it is set, by gui first, then by neovim, name of program
+    /// when both are empty.
+    fn update_title(&mut self) {
+        self.title = self
+            .gui_title
+            .as_deref()
+            .or_else(|| Some(self.vim_title.as_str()).filter(|title| !title.trim().is_empty()))
+            .unwrap_or(&self.opts.title)
+            .to_string();
+    }
+
+    /// Drop all messages, collapsed ones too.
+    fn clear_messages(&mut self) {
+        self.messages.clear();
+        self.collapsed_messages = 0;
+        self.messages_expanded = false;
+    }
+
+    /// Gui overlays showing over grids.
+    pub fn overlays(&self) -> keys::Overlays {
+        keys::Overlays {
+            messages: !self.messages.is_empty(),
+            find: self.find_pattern.is_some(),
+            message_history: self.message_history_open,
+            popupmenu: self.popupmenu_open,
+        }
     }
 }
 
@@ -261,91 +753,372 @@ impl AppUpdate for AppModel {
         match message {
             AppMessage::UiCommand(ui_command) => {
                 log::trace!("ui-commad {:?}", ui_command);
+                // typing shows cursor at once, blinking starts over.
+                let typing = matches!(ui_command, UiCommand::Serial(SerialCommand::Keyboard(_)));
                 EVENT_AGGREGATOR.send(ui_command);
+                if typing {
+                    self.cursor.update_view().unwrap();
+                }
             }
             AppMessage::Quit => {
+                if let Some(wait) = self.shutdown.quit(std::time::Instant::now()) {
+                    log::info!("keep last error shown for {:?} before quit.", wait);
+                    glib::timeout_add_local_once(
+                        wait,
+                        glib::clone!(@strong sender => move || {
+                            sender.send(AppMessage::Quit).ok();
+                        }),
+                    );
+                    return true;
+                }
+                // neovim exited by itself, the window was never closed.
+                if self.opts.remember_window_size {
+                    if let Some(window) = self.main_window.get() {
+                        WindowState::of(window).save();
+                    }
+                }
                 return false;
             }
             AppMessage::ShowPointer => {
                 self.show_pointer.store(true, atomic::Ordering::Relaxed);
             }
+            AppMessage::HidePointer => {
+                self.hide_pointer.store(true, atomic::Ordering::Relaxed);
+            }
+            AppMessage::DismissOverlays => {
+                let overlays = self.overlays();
+                log::debug!("dismiss gui overlays {:?}.", overlays);
+                self.clear_messages();
+                if overlays.find {
+                    sender.send(AppMessage::FindClose).unwrap();
+                }
+                if overlays.message_history {
+                    components
+                        .message_history
+                        .send(VimMessageHistoryEvent::Hide)
+                        .unwrap();
+                }
+                if overlays.popupmenu {
+                    // completion is ended by neovim, typed text kept.
+                    let cancel = UiCommand::Serial(SerialCommand::Keyboard("<C-e>".into()));
+                    EVENT_AGGREGATOR.send(cancel);
+                }
+            }
+            AppMessage::MessageHistoryHidden => {
+                self.message_history_open = false;
+            }
+            AppMessage::DismissMessage(id) => {
+                // replaced or cleared before timed out.
+                let index = (0..self.messages.len())
+                    .find(|index| self.messages.get(*index).map_or(false, |m| m.id() == id));
+                if let Some(message) = index.and_then(|index| self.messages.get_mut(index)) {
+                    if message.is_fading() {
+                        message.dismiss();
+                    } else {
+                        message.fade();
+                        glib::timeout_add_local_once(
+                            vimview::MESSAGE_FADE,
+                            glib::clone!(@strong sender => move || {
+                                sender.send(AppMessage::DismissMessage(id)).ok();
+                            }),
+                        );
+                    }
+                }
+                // dismissed ones removed when no message left above them.
+                let mut len = self.messages.len();
+                while len > 0 && self.messages.get(len - 1).unwrap().is_dismissed() {
+                    self.messages.pop();
+                    len -= 1;
+                }
+                self.collapse_messages();
+            }
+            AppMessage::BellFlashed => {
+                self.bell_flash = false;
+            }
+            AppMessage::ExpandMessages => {
+                self.messages_expanded = true;
+                self.collapse_messages();
+            }
+            AppMessage::ColorColumns { window, columns } => {
+                log::debug!("window {} colorcolumns {:?}", window, columns);
+                self.colorcolumns.insert(window, columns);
+                let grids: Vec<u64> = self
+                    .vgrids
+                    .iter()
+                    .filter(|(_, vgrid)| vgrid.win() == window)
+                    .map(|(grid, _)| *grid)
+                    .collect();
+                for grid in grids {
+                    self.sync_colorcolumns(grid);
+                }
+            }
+            AppMessage::Terminal { window, terminal } => {
+                log::debug!("window {} terminal {}", window, terminal);
+                if terminal {
+                    self.terminals.insert(window);
+                } else {
+                    self.terminals.remove(&window);
+                }
+                let grids: Vec<u64> = self
+                    .vgrids
+                    .iter()
+                    .filter(|(_, vgrid)| vgrid.win() == window)
+                    .map(|(grid, _)| *grid)
+                    .collect();
+                for grid in grids {
+                    self.sync_terminal(grid);
+                }
+            }
+            AppMessage::ReadClipboard(register, reply) => {
+                self.clipboard(register)
+                    .read_text_async(gtk::gio::NONE_CANCELLABLE, move |text| {
+                        if let Err(ref err) = text {
+                            log::warn!("read clipboard {:?} failed: {}", register, err);
+                        }
+                        reply.send(text.ok().flatten().map(String::from));
+                    });
+            }
+            AppMessage::WriteClipboard(register, text) => {
+                self.clipboard(register).set_text(&text);
+            }
+            AppMessage::Paste(register) => {
+                self.clipboard(register)
+                    .read_text_async(gtk::gio::NONE_CANCELLABLE, move |text| match text {
+                        Ok(Some(text)) if !text.is_empty() => {
+                            let paste = SerialCommand::Paste(text.into());
+                            EVENT_AGGREGATOR.send(UiCommand::Serial(paste));
+                        }
+                        Ok(_) => log::info!("nothing to paste in {:?}", register),
+                        Err(err) => log::warn!("read clipboard {:?} failed: {}", register, err),
+                    });
+            }
+            AppMessage::DropFiles(paths, grid) => {
+                let window = grid
+                    .and_then(|grid| self.vgrids.get(grid))
+                    .map_or(0, |vgrid| vgrid.win());
+                log::info!("edit dropped files {:?} in window {}", paths, window);
+                let drop = ParallelCommand::FileDrop { paths, window };
+                EVENT_AGGREGATOR.send(UiCommand::Parallel(drop));
+            }
+            AppMessage::ToggleTerminalCursor => {
+                self.cursor
+                    .model_mut()
+                    .map(|mut m| {
+                        let terminal_style = !m.terminal_style;
+                        log::info!("cursor drawn in terminal style {}", terminal_style);
+                        m.set_terminal_style(terminal_style);
+                    })
+                    .unwrap();
+                self.cursor.update_view().unwrap();
+            }
+            AppMessage::ToggleTrailingWhitespace => {
+                self.trailing_whitespace = !self.trailing_whitespace;
+                log::info!("trailing whitespace shown {}", self.trailing_whitespace);
+                let matched = ParallelCommand::TrailingWhitespace(self.trailing_whitespace);
+                EVENT_AGGREGATOR.send(UiCommand::Parallel(matched));
+                let color = self.trailing_whitespace_color();
+                for (_, vgrid) in self.vgrids.iter_mut() {
+                    vgrid.set_trailing_whitespace(color, self.whitespace_hlids.clone());
+                }
+                self.vgrids.update_all();
+            }
+            AppMessage::ToggleLigatures => {
+                self.ligatures = !self.ligatures;
+                log::info!("ligatures shaped {}", self.ligatures);
+                for (_, vgrid) in self.vgrids.iter_mut() {
+                    vgrid.set_ligatures(self.ligatures);
+                }
+                self.vgrids.update_all();
+            }
+            AppMessage::ToggleFullscreen => {
+                self.fullscreen_toggled
+                    .store(true, atomic::Ordering::Relaxed);
+            }
+            AppMessage::GuiOptions(guioptions) => {
+                log::debug!("guioptions {:?}", guioptions);
+                self.guioptions = guioptions;
+                for (_, vgrid) in self.vgrids.iter_mut() {
+                    vgrid.set_scrollbars(guioptions.scrollbar_left, guioptions.scrollbar_right);
+                }
+                self.vgrids.update_all();
+            }
+            AppMessage::Find(pattern) => {
+                if self.find_pattern.is_none() {
+                    self.find_focus_changed.store(true, atomic::Ordering::Relaxed);
+                }
+                self.find_pattern.replace(pattern);
+                self.find_on_screen();
+                self.vgrids.flush();
+            }
+            AppMessage::FindClose => {
+                self.find_pattern = None;
+                self.find_focus_changed.store(true, atomic::Ordering::Relaxed);
+                self.find_on_screen();
+                self.vgrids.flush();
+            }
+            AppMessage::Zoom(zoom) => {
+                let original = self.guifont_size();
+                let mut desc = self.font_description.borrow().clone();
+                let size = Some(desc.size())
+                    .filter(|size| *size > 0)
+                    .unwrap_or(original);
+                desc.set_size(zoom.size(size, original));
+                log::info!("font zoomed to {}", desc.to_str());
+                self.set_font(desc);
+            }
+            AppMessage::ScaleFactor(scale_factor) => {
+                if self.scale_factor.get() != scale_factor {
+                    log::info!("scale factor changed to {}", scale_factor);
+                    self.scale_factor.set(scale_factor);
+                    if self.opts.integer_cell_size {
+                        // snapped to device pixels of the old scale.
+                        let desc = self.font_description.borrow().clone();
+                        self.set_font(desc);
+                    } else {
+                        self.publish_cell_size();
+                    }
+                }
+            }
+            AppMessage::SetTitle(title) => {
+                self.gui_title = Some(title).filter(|title| !title.is_empty());
+                self.update_title();
+            }
+            AppMessage::SetOpacity(opacity) => {
+                log::debug!("set window opacity to {}", opacity);
+                self.opacity = opacity;
+                self.opacity_changed.store(true, atomic::Ordering::Relaxed);
+            }
             AppMessage::RedrawEvent(event) => {
                 match event {
                     RedrawEvent::SetTitle { title } => {
-                        self.title = title
-                            .split("     ")
-                            .filter_map(|s| if s.is_empty() { None } else { Some(s.trim()) })
-                            .collect::<Vec<_>>()
-                            .join("  ")
+                        self.vim_title = title;
+                        self.update_title();
                     }
                     RedrawEvent::OptionSet { gui_option } => match gui_option {
                         bridge::GuiOption::AmbiWidth(ambi_width) => {
-                            log::debug!("unhandled ambi_width {}", ambi_width);
+                            // grids get trailing empty cells from neovim, only
+                            // messages measured by gui care.
+                            AmbiWidthDouble
+                                .store(ambi_width == "double", atomic::Ordering::Relaxed);
                         }
                         bridge::GuiOption::ArabicShape(arabic_shape) => {
                             log::debug!("unhandled arabic-shape: {}", arabic_shape);
                         }
                         bridge::GuiOption::Emoji(emoji) => {
                             log::debug!("emoji: {}", emoji);
+                            EmojiDoubleWidth.store(emoji, atomic::Ordering::Relaxed);
                         }
                         bridge::GuiOption::GuiFont(guifont) => {
                             if !guifont.trim().is_empty() {
                                 log::info!("gui font: {}", &guifont);
-                                let desc = pango::FontDescription::from_string(
-                                    &guifont.replace(":h", " "),
-                                );
-
-                                self.pctx.set_font_description(&desc);
-                                self.gtksettings.get().map(|settings| {
-                                    settings.set_gtk_font_name(Some(&desc.to_str()));
-                                });
-
+                                let desc = GuiFont::parse(&guifont).font_description(self.dpi());
                                 self.guifont.replace(guifont);
-                                self.font_description.replace(desc);
-
-                                self.calculate();
-
-                                self.vgrids
-                                    .iter_mut()
-                                    .for_each(|(_, vgrid)| vgrid.reset_cache());
-
-                                self.font_changed.store(true, atomic::Ordering::Relaxed);
-                                self.cursor_coord_changed
-                                    .store(true, atomic::Ordering::Relaxed);
+                                self.set_font(desc);
                             }
                         }
                         bridge::GuiOption::GuiFontSet(guifontset) => {
                             self.guifontset.replace(guifontset);
                         }
                         bridge::GuiOption::GuiFontWide(guifontwide) => {
+                            log::info!("gui font wide: {}", &guifontwide);
                             self.guifontwide.replace(guifontwide);
+                            let wide_font = self.wide_font();
+                            self.vgrids
+                                .iter_mut()
+                                .for_each(|(_, vgrid)| vgrid.set_wide_font(wide_font.clone()));
+                            self.vgrids.update_all();
                         }
                         bridge::GuiOption::LineSpace(linespace) => {
                             log::info!("line space: {}", linespace);
                             let mut metrics = self.metrics.get();
                             metrics.set_linespace(linespace as _);
+                            if self.opts.integer_cell_size {
+                                metrics.snap_to_device_pixels(self.scale_factor.get());
+                            }
                             self.metrics.replace(metrics);
+                            self.publish_cell_size();
+                            // rows of window change, same as font does.
+                            self.vgrids.update_all();
+                            self.font_changed.store(true, atomic::Ordering::Relaxed);
+                            self.cursor_coord_changed
+                                .store(true, atomic::Ordering::Relaxed);
                         }
                         bridge::GuiOption::ShowTabLine(show_tab_line) => {
                             self.show_tab_line.replace(show_tab_line);
+                            components
+                                .tabline
+                                .send(VimTablineEvent::ShowTabLine(show_tab_line))
+                                .unwrap();
                         }
                         bridge::GuiOption::TermGuiColors(term_gui_colors) => {
                             log::debug!("unhandled term gui colors: {}", term_gui_colors);
                         }
                         bridge::GuiOption::Pumblend(pumblend) => {
-                            log::debug!("unhandled pumblend: {}", pumblend)
+                            components
+                                .popup_menu
+                                .send(VimPopupMenuEvent::Blend(pumblend))
+                                .unwrap();
                         }
                         bridge::GuiOption::Unknown(name, value) => {
                             log::debug!("GuiOption({}: {:?}) not supported yet.", name, value)
                         }
                     },
                     RedrawEvent::DefaultColorsSet { colors } => {
-                        self.background_changed
-                            .store(true, atomic::Ordering::Relaxed);
+                        self.redraw.request(Target::Background);
+                        // only defaults are replaced, other highlights keep their ids
+                        // and are defined again by neovim if they changed.
                         self.hldefs.write().set_defaults(colors);
+                        self.vgrids
+                            .iter_mut()
+                            .for_each(|(_, vgrid)| vgrid.reset_cache());
+                        self.cursor
+                            .model_mut()
+                            .map(|mut m| m.reset_cache())
+                            .unwrap();
+                        self.vgrids.update_all();
+                        // messages and message area follow new colors.
+                        let colors = self.hldefs.read().colors();
+                        for index in 0..self.messages.len() {
+                            if let Some(message) = self.messages.get_mut(index) {
+                                message.set_colors(colors);
+                            }
+                        }
+                        self.redraw.request(Target::MessageGrid);
                     }
-                    RedrawEvent::HighlightAttributesDefine { id, style } => {
+                    RedrawEvent::HighlightAttributesDefine {
+                        id,
+                        mut style,
+                        names,
+                    } => {
+                        // cells painted by neovim are replaced by rules of the same color.
+                        let colorcolumn = names
+                            .iter()
+                            .any(|name| name == vimview::COLORCOLUMN_HLGROUP);
+                        if self.opts.colorcolumn_rule && colorcolumn {
+                            let color = style.colors.background;
+                            if names.len() == 1 && self.colorcolumn_color != color {
+                                self.colorcolumn_color = color;
+                                self.sync_all_colorcolumns();
+                            }
+                            style.colors.background = None;
+                        }
                         self.hldefs.write().set(id, style);
+                        // ids are reused after `:highlight clear`, kept only while matched.
+                        let whitespace =
+                            names.iter().any(|name| name == vimview::WHITESPACE_HLGROUP);
+                        if whitespace {
+                            self.whitespace_hlids.write().insert(id);
+                        } else {
+                            self.whitespace_hlids.write().remove(&id);
+                        }
+                        // not a ui highlight group, never sent by hl_group_set.
+                        if self.opts.image_placeholders
+                            && names.iter().any(|name| name == vimview::PLACEHOLDER_HLGROUP)
+                        {
+                            self.hlgroups
+                                .write()
+                                .insert(vimview::PLACEHOLDER_HLGROUP.to_string(), id);
+                        }
                     }
                     RedrawEvent::HighlightGroupSet { name, id } => {
                         self.hlgroups.write().insert(name, id);
@@ -379,6 +1152,14 @@ impl AppUpdate for AppModel {
                             .borrow()
                             .set_cells(row as _, column_start as _, &cells);
                         let row = row as usize;
+                        // errors are shown by the message grid only.
+                        if self.message_grid.is_message_grid(grid) {
+                            let errmsg = self.hlgroups.read().get(ERROR_HLGROUP).copied();
+                            let error = cells.iter().any(|cell| cell.hldef == errmsg);
+                            if errmsg.is_some() && error {
+                                self.shutdown.error_shown(std::time::Instant::now());
+                            }
+                        }
                         let coord = &self.cursor_coord;
                         let cursor_grid = self.cursor_grid;
                         if cursor_grid == grid && row as f64 == coord.row {
@@ -391,7 +1172,7 @@ impl AppUpdate for AppModel {
                                     .model_mut()
                                     .map(|mut m| m.set_cell(cell))
                                     .unwrap();
-                                self.cursor.update_view().unwrap();
+                                self.redraw.request(Target::Cursor);
                                 log::trace!("set cursor cell.");
                             } else {
                                 log::error!(
@@ -405,25 +1186,29 @@ impl AppUpdate for AppModel {
                     }
                     RedrawEvent::Scroll {
                         grid,
-                        top: _,
-                        bottom: _,
-                        left: _,
-                        right: _,
+                        top,
+                        bottom,
+                        left,
+                        right,
                         rows,
                         columns,
                     } => {
+                        let (top, bottom) = (top as usize, bottom as usize);
+                        let (left, right) = (left as usize, right as usize);
                         let vgrid = self.vgrids.get_mut(grid).unwrap();
+                        if rows == 0 && columns == 0 {
+                            log::warn!("grid {} scrolled by nothing.", grid);
+                        }
+                        // vertical first, then horizontal.
                         if rows.is_positive() {
-                            vgrid.up(rows.abs() as _);
+                            vgrid.up(top, bottom, left, right, rows.abs() as _);
                         } else if rows.is_negative() {
-                            vgrid.down(rows.abs() as _);
-                        } else if columns.is_positive() {
-                            unimplemented!("scroll left.");
+                            vgrid.down(top, bottom, left, right, rows.abs() as _);
+                        }
+                        if columns.is_positive() {
+                            vgrid.left(top, bottom, left, right, columns.abs() as _);
                         } else if columns.is_negative() {
-                            unimplemented!("scroll right.");
-                        } else {
-                            // rows and columns are both zero.
-                            unimplemented!("could not be there.");
+                            vgrid.right(top, bottom, left, right, columns.abs() as _);
                         }
                         let cursor_grid = self.cursor_grid;
                         log::debug!("scrolling grid {} cursor at {}", grid, cursor_grid);
@@ -439,7 +1224,7 @@ impl AppUpdate for AppModel {
                                 .model_mut()
                                 .map(|mut m| m.set_cell(cell))
                                 .unwrap();
-                            self.cursor.update_view().unwrap();
+                            self.redraw.request(Target::Cursor);
                         }
                     }
                     RedrawEvent::Resize {
@@ -457,7 +1242,7 @@ impl AppUpdate for AppModel {
                                 .resize(width as _, height as _);
                         } else {
                             log::debug!("Add grid {} to default window at left top.", grid);
-                            let vgrid = VimGrid::new(
+                            let mut vgrid = VimGrid::new(
                                 grid,
                                 0,
                                 (0., 0.).into(),
@@ -468,27 +1253,49 @@ impl AppUpdate for AppModel {
                                 self.font_description.clone(),
                             );
                             vgrid.set_pango_context(self.pctx.clone());
+                            vgrid.set_hlgroups(self.hlgroups.clone());
+                            vgrid.set_draw_separators(self.opts.split_separators);
+                            vgrid.set_fill_gutter(self.opts.fill_gutter);
+                            vgrid.set_seamless_background(self.opts.seamless_background);
+                            vgrid.set_background_alpha(self.background_alpha());
+                            vgrid.set_float_border(self.opts.float_borders);
+                            vgrid.set_float_container(self.float_container.get().cloned());
+                            vgrid.set_image_placeholders(self.opts.image_placeholders);
+                            vgrid.set_trailing_whitespace(
+                                self.trailing_whitespace_color(),
+                                self.whitespace_hlids.clone(),
+                            );
+                            vgrid.set_wide_font(self.wide_font());
+                            vgrid.set_ligatures(self.ligatures);
+                            vgrid.set_smooth_scroll(self.opts.smooth_scroll);
+                            vgrid.set_middle_paste(self.opts.middle_click_paste);
+                            vgrid.set_scrollbars(
+                                self.guioptions.scrollbar_left,
+                                self.guioptions.scrollbar_right,
+                            );
                             self.vgrids.insert(grid, vgrid);
                         };
                     }
 
                     RedrawEvent::WindowPosition {
                         grid,
-                        window: _,
+                        window,
                         start_row: row,
                         start_column: column,
                         width,
                         height,
                     } => {
+                        // kept by grid, never asked from neovim while drawing.
+                        let win = bridge::window_handle(window.get_value()).unwrap_or(0);
                         // let metrics = self.metrics.get();
                         // let x = start_column as f64 * metrics.width();
                         // let y = start_row as f64 * metrics.height(); //;
 
                         if self.vgrids.get(grid).is_none() {
                             // dose not exists, create
-                            let vgrid = VimGrid::new(
+                            let mut vgrid = VimGrid::new(
                                 grid,
-                                0,
+                                win,
                                 (column as usize, row as usize).into(),
                                 (width, height).into(),
                                 self.hldefs.clone(),
@@ -497,7 +1304,30 @@ impl AppUpdate for AppModel {
                                 self.font_description.clone(),
                             );
                             vgrid.set_pango_context(self.pctx.clone());
+                            vgrid.set_hlgroups(self.hlgroups.clone());
+                            vgrid.set_fill_gutter(self.opts.fill_gutter);
+                            vgrid.set_seamless_background(self.opts.seamless_background);
+                            vgrid.set_background_alpha(self.background_alpha());
+                            vgrid.set_float_border(self.opts.float_borders);
+                            vgrid.set_float_container(self.float_container.get().cloned());
+                            vgrid.set_image_placeholders(self.opts.image_placeholders);
+                            vgrid.set_trailing_whitespace(
+                                self.trailing_whitespace_color(),
+                                self.whitespace_hlids.clone(),
+                            );
+                            vgrid.set_wide_font(self.wide_font());
+                            vgrid.set_ligatures(self.ligatures);
+                            vgrid.set_smooth_scroll(self.opts.smooth_scroll);
+                            vgrid.set_middle_paste(self.opts.middle_click_paste);
+                            vgrid.set_scrollbars(
+                                self.guioptions.scrollbar_left,
+                                self.guioptions.scrollbar_right,
+                            );
                             self.vgrids.insert(grid, vgrid);
+                            self.sync_terminal(grid);
+                            if self.opts.colorcolumn_rule {
+                                self.sync_colorcolumns(grid);
+                            }
                             log::info!(
                                 "Add grid {} at {}x{} with {}x{}.",
                                 grid,
@@ -508,6 +1338,8 @@ impl AppUpdate for AppModel {
                             );
                         } else {
                             let vgrid = self.vgrids.get_mut(grid).unwrap();
+                            let moved = win != vgrid.win();
+                            vgrid.set_win(win);
                             vgrid.resize(width as _, height as _);
                             vgrid.set_coord(column as _, row as _);
                             log::debug!(
@@ -519,6 +1351,12 @@ impl AppUpdate for AppModel {
                                 width
                             );
                             vgrid.show();
+                            if moved {
+                                self.sync_terminal(grid);
+                            }
+                            if moved && self.opts.colorcolumn_rule {
+                                self.sync_colorcolumns(grid);
+                            }
                         }
 
                         log::info!(
@@ -528,7 +1366,7 @@ impl AppUpdate for AppModel {
                     }
                     RedrawEvent::WindowViewport {
                         grid,
-                        window: _,
+                        window,
                         top_line,
                         bottom_line,
                         current_line,
@@ -541,15 +1379,36 @@ impl AppUpdate for AppModel {
                         );
 
                         if self.vgrids.get(grid).is_none() {
-                            log::warn!("WindowViewport before create grid {}.", grid);
+                            // window closed, or positioned by a later event.
+                            log::debug!("WindowViewport before create grid {}.", grid);
                         } else {
                             let vgrid = self.vgrids.get_mut(grid).unwrap();
+                            let win = bridge::window_handle(window.get_value());
+                            let moved = win.map_or(false, |win| win != vgrid.win());
+                            if let Some(win) = win {
+                                vgrid.set_win(win);
+                            }
                             vgrid.show();
+                            if self.opts.scrollbars || self.guioptions.has_scrollbar() {
+                                vgrid.set_viewport(vimview::Viewport {
+                                    top_line,
+                                    bottom_line,
+                                    line_count,
+                                });
+                            }
+                            if moved {
+                                self.sync_terminal(grid);
+                            }
+                            if moved && self.opts.colorcolumn_rule {
+                                self.sync_colorcolumns(grid);
+                            }
                         }
                     }
                     RedrawEvent::WindowHide { grid } => {
                         log::info!("hide grid {}", grid);
-                        self.vgrids.get_mut(grid).unwrap().hide();
+                        if let Some(vgrid) = self.vgrids.get_mut(grid) {
+                            vgrid.hide();
+                        }
                     }
                     RedrawEvent::WindowClose { grid } => {
                         log::info!("grid {} closed", grid);
@@ -558,9 +1417,34 @@ impl AppUpdate for AppModel {
                     RedrawEvent::Destroy { grid } => {
                         log::info!("grid {} destroyed", grid);
                         self.vgrids.remove(grid);
+                        if self.more_prompt == Some(grid) {
+                            self.more_prompt = None;
+                        }
+                        if self.message_grid.destroy(grid) {
+                            self.redraw.request(Target::MessageGrid);
+                        }
                     }
                     RedrawEvent::Flush => {
+                        self.dim_inactive();
+                        if self.opts.more_prompt_indicator {
+                            // grid of messages is placed by `msg_set_pos` after its lines.
+                            self.more_prompt = self.more_prompt();
+                        }
+                        if self.message_grid.grid().is_some() {
+                            self.redraw.request(Target::MessageGrid);
+                        }
+                        // cursor, background and messages drawn once for all events.
+                        self.redraw.flush();
+                        if self.find_pattern.is_some() {
+                            self.find_on_screen();
+                        }
                         self.vgrids.flush();
+                        // highlights of tabline are defined again after colorscheme changed.
+                        components.tabline.send(VimTablineEvent::Redraw).unwrap();
+                    }
+                    // windows closed while neovim still sends events of them.
+                    RedrawEvent::CursorGoto { grid, .. } if self.vgrids.get(grid).is_none() => {
+                        log::debug!("cursor goto grid {} which is gone.", grid);
                     }
                     RedrawEvent::CursorGoto { grid, row, column } => {
                         let vgrid = self.vgrids.get(grid).unwrap();
@@ -589,7 +1473,7 @@ impl AppUpdate for AppModel {
                                     m.set_coord(coord);
                                 })
                                 .unwrap();
-                            self.cursor.update_view().unwrap();
+                            self.redraw.request(Target::Cursor);
                         } else {
                             log::warn!(
                                 "Cursor pos {}x{} of grid {} dose not exists",
@@ -601,42 +1485,58 @@ impl AppUpdate for AppModel {
                         self.cursor_coord_changed
                             .store(true, atomic::Ordering::Relaxed);
                         self.cursor_grid = grid;
+                        self.focused = vimview::focused_grid(self.focused, grid);
                     }
                     RedrawEvent::ModeInfoSet { cursor_modes } => {
                         self.cursor_modes = cursor_modes;
 
-                        let mode = self.cursor_modes.get(self.cursor_mode).unwrap().clone();
-                        self.cursor
-                            .model_mut()
-                            .map(|mut m| {
-                                m.set_mode(mode);
-                            })
-                            .unwrap();
-                        self.cursor.update_view().unwrap();
+                        if let Some(mode) = cursor::cursor_mode(&self.cursor_modes, self.cursor_mode) {
+                            let mut mode = mode.clone();
+                            CursorOverride::apply(&self.opts.cursor_shapes, &mut mode);
+                            self.cursor
+                                .model_mut()
+                                .map(|mut m| {
+                                    m.set_mode(mode);
+                                })
+                                .unwrap();
+                            self.redraw.request(Target::Cursor);
+                        }
                     }
                     RedrawEvent::ModeChange { mode, mode_index } => {
                         self.mode = mode;
                         self.cursor_mode = mode_index as _;
-                        let cursor_mode = self.cursor_modes.get(self.cursor_mode).unwrap().clone();
+                        let cursor_mode = cursor::cursor_mode(&self.cursor_modes, self.cursor_mode)
+                            .cloned()
+                            .map(|mut mode| {
+                                CursorOverride::apply(&self.opts.cursor_shapes, &mut mode);
+                                mode
+                            });
                         log::info!("Mode Change to {:?} {:?}", &self.mode, cursor_mode);
-                        self.cursor
-                            .model_mut()
-                            .map(|mut m| {
-                                m.set_mode(cursor_mode);
-                            })
-                            .unwrap();
-                        self.cursor.update_view().unwrap();
-                        if matches!(self.mode, EditorMode::Normal | EditorMode::Unknown(_)) {
-                            sender.send(AppMessage::ShowPointer).unwrap();
+                        if let Some(cursor_mode) = cursor_mode {
+                            self.cursor
+                                .model_mut()
+                                .map(|mut m| {
+                                    m.set_mode(cursor_mode);
+                                })
+                                .unwrap();
+                            self.redraw.request(Target::Cursor);
+                            // caret of cmdline blinks as cursor of the mode does.
+                            let blinking = self
+                                .cursor
+                                .model()
+                                .ok()
+                                .and_then(|m| m.maybe_blinking("cmdline-blinking"));
+                            components
+                                .cmd_prompt
+                                .send(VimCmdEvent::Blink(blinking))
+                                .unwrap();
                         }
                     }
                     RedrawEvent::BusyStart => {
                         log::debug!("Ignored BusyStart.");
-                        sender.send(AppMessage::ShowPointer).unwrap();
                     }
                     RedrawEvent::BusyStop => {
                         log::debug!("Ignored BusyStop.");
-                        sender.send(AppMessage::ShowPointer).unwrap();
                     }
                     RedrawEvent::MouseOn => {
                         self.mouse_on.store(true, atomic::Ordering::Relaxed);
@@ -644,6 +1544,8 @@ impl AppUpdate for AppModel {
                     RedrawEvent::MouseOff => {
                         self.mouse_on.store(false, atomic::Ordering::Relaxed);
                     }
+                    RedrawEvent::Bell => self.ring_bell(false, &sender),
+                    RedrawEvent::VisualBell => self.ring_bell(true, &sender),
 
                     RedrawEvent::MessageShow {
                         kind,
@@ -651,23 +1553,58 @@ impl AppUpdate for AppModel {
                         replace_last,
                     } => {
                         log::debug!("showing message {:?} {:?}", kind, content);
+                        if matches!(
+                            kind,
+                            MessageKind::Error
+                                | MessageKind::EchoError
+                                | MessageKind::LuaError
+                                | MessageKind::RpcError
+                        ) {
+                            self.shutdown.error_shown(std::time::Instant::now());
+                        }
                         if replace_last && !self.messages.is_empty() {
                             self.messages.pop();
                         }
 
-                        self.messages.push(VimMessage::new(
+                        let mut message = VimMessage::new(
                             kind,
                             content,
                             self.hldefs.clone(),
                             self.metrics.clone(),
                             self.pctx.clone(),
-                        ))
+                        );
+                        self.message_serial += 1;
+                        let id = self.message_serial;
+                        message.set_id(id);
+                        match self.opts.message_timeout {
+                            Some(timeout) if timeout > 0. && kind.is_transient() => {
+                                glib::timeout_add_local_once(
+                                    std::time::Duration::from_secs_f64(timeout),
+                                    glib::clone!(@strong sender => move || {
+                                        sender.send(AppMessage::DismissMessage(id)).ok();
+                                    }),
+                                );
+                            }
+                            _ => {}
+                        }
+                        // wrapped within window, margin, padding and border aside.
+                        let (width, _) = self.size.get();
+                        let cols = (width as f64 / self.metrics.get().width()) as usize;
+                        message.set_max_cols(cols.saturating_sub(4));
+                        self.messages.push(message);
+                        self.collapse_messages();
                     }
                     RedrawEvent::MessageShowMode { content } => {
-                        log::warn!("message show mode: {:?}", content);
+                        components
+                            .status_strip
+                            .send(VimStatusStripEvent::Mode(content))
+                            .unwrap();
                     }
                     RedrawEvent::MessageRuler { content } => {
-                        log::warn!("message ruler: {:?}", content);
+                        components
+                            .status_strip
+                            .send(VimStatusStripEvent::Ruler(content))
+                            .unwrap();
                     }
                     RedrawEvent::MessageSetPosition {
                         grid,
@@ -685,6 +1622,22 @@ impl AppUpdate for AppModel {
                         // let metrics = self.metrics.get();
                         // let y = row as f64 * metrics.height(); //;
                         let width = self.vgrids.get(1).map(|vgrid| vgrid.width()).unwrap();
+                        self.message_grid.set_position(
+                            grid,
+                            row as usize,
+                            scrolled,
+                            &separator_character,
+                        );
+                        // messages hidden back into the message area.
+                        if !scrolled {
+                            self.more_prompt = None;
+                        }
+                        // lines of messages may come before the grid is known as theirs.
+                        if self.message_shows_error(grid) {
+                            self.shutdown.error_shown(std::time::Instant::now());
+                        }
+                        self.redraw.request(Target::MessageGrid);
+                        // drawn by message area, keep the window grid hidden.
                         if let Some(vgrid) = self.vgrids.get_mut(grid) {
                             log::debug!(
                                 "moving message grid to 0x{} size {}x{}",
@@ -694,7 +1647,7 @@ impl AppUpdate for AppModel {
                             );
                             vgrid.set_coord(0., row as f64);
                             vgrid.resize(width, vgrid.height());
-                            vgrid.show();
+                            vgrid.hide();
                         } else {
                             log::debug!("creating message grid at 0x{} size {}x{}", row, width, 1);
                             let row = row as usize;
@@ -708,22 +1661,39 @@ impl AppUpdate for AppModel {
                                 self.metrics.clone(),
                                 self.font_description.clone(),
                             );
-                            vgrid.show();
+                            vgrid.hide();
                             vgrid.set_pango_context(self.pctx.clone());
+                            vgrid.set_wide_font(self.wide_font());
+                            vgrid.set_ligatures(self.ligatures);
                             self.vgrids.insert(grid, vgrid);
                         }
                     }
                     RedrawEvent::MessageShowCommand { content } => {
-                        log::warn!("message show command: {:?}", content);
+                        components
+                            .status_strip
+                            .send(VimStatusStripEvent::Command(content))
+                            .unwrap();
                     }
                     RedrawEvent::MessageHistoryShow { entries } => {
-                        log::warn!("message history: {:?}", entries);
+                        log::debug!("message history: {:?}", entries);
+                        self.message_history_open = true;
+                        components
+                            .message_history
+                            .send(VimMessageHistoryEvent::Show(entries))
+                            .unwrap();
                     }
                     RedrawEvent::MessageClear => {
                         log::warn!("message clear all");
-                        self.messages.clear();
+                        self.clear_messages();
                     }
 
+                    RedrawEvent::WindowFloatPosition {
+                        grid, anchor_grid, ..
+                    } if self.vgrids.get(grid).is_none()
+                        || self.vgrids.get(anchor_grid).is_none() =>
+                    {
+                        log::debug!("float grid {} or its anchor {} is gone.", grid, anchor_grid);
+                    }
                     RedrawEvent::WindowFloatPosition {
                         grid,
                         anchor,
@@ -731,7 +1701,7 @@ impl AppUpdate for AppModel {
                         anchor_row,
                         anchor_column,
                         focusable,
-                        sort_order: _,
+                        sort_order,
                     } => {
                         log::info!(
                             "grid {} is float window exists in vgrids {} anchor {} {:?} pos {}x{} focusable {}",
@@ -772,6 +1742,8 @@ impl AppUpdate for AppModel {
                         log::info!("moving float window {} to {}x{}", grid, col, row);
                         vgrid.set_coord(coord.col + col.max(0.), coord.row + row.max(0.));
                         vgrid.set_is_float(true);
+                        // sent since neovim 0.7, floats stack by creation before.
+                        vgrid.set_sort_order(sort_order.unwrap_or(0));
                         vgrid.set_focusable(focusable);
                     }
 
@@ -795,18 +1767,82 @@ impl AppUpdate for AppModel {
                             ))
                             .unwrap();
                     }
+                    RedrawEvent::CommandLinePosition { position, level } => {
+                        components
+                            .cmd_prompt
+                            .send(VimCmdEvent::Position(position, level))
+                            .unwrap();
+                    }
                     RedrawEvent::CommandLineHide => {
                         components.cmd_prompt.send(VimCmdEvent::Hide).unwrap();
                     }
+                    RedrawEvent::CommandLineBlockShow { lines } => {
+                        components
+                            .cmd_prompt
+                            .send(VimCmdEvent::BlockShow(lines))
+                            .unwrap();
+                    }
+                    RedrawEvent::CommandLineBlockAppend { line } => {
+                        components
+                            .cmd_prompt
+                            .send(VimCmdEvent::BlockAppend(line))
+                            .unwrap();
+                    }
                     RedrawEvent::CommandLineBlockHide => {
                         components.cmd_prompt.send(VimCmdEvent::BlockHide).unwrap();
                     }
+                    RedrawEvent::PopupMenuShow {
+                        items,
+                        selected,
+                        row,
+                        column,
+                        grid,
+                    } => {
+                        // cmdline completion has no grid, anchored at the last row.
+                        let leftop = match u64::try_from(grid).ok() {
+                            Some(grid) => self.vgrids.get(grid).map(|vgrid| vgrid.coord().clone()),
+                            None => self
+                                .vgrids
+                                .get(1)
+                                .map(|vgrid| (0, vgrid.height().saturating_sub(1)).into()),
+                        };
+                        let leftop = leftop.unwrap_or_default();
+                        self.popupmenu_open = true;
+                        components
+                            .popup_menu
+                            .send(VimPopupMenuEvent::Show(
+                                items,
+                                selected.map(|selected| selected as usize),
+                                (leftop.row + row as f64) as usize,
+                                (leftop.col + column as f64) as usize,
+                            ))
+                            .unwrap();
+                    }
+                    RedrawEvent::PopupMenuSelect { selected } => {
+                        components
+                            .popup_menu
+                            .send(VimPopupMenuEvent::Select(
+                                selected.map(|selected| selected as usize),
+                            ))
+                            .unwrap();
+                    }
+                    RedrawEvent::PopupMenuHide => {
+                        self.popupmenu_open = false;
+                        components.popup_menu.send(VimPopupMenuEvent::Hide).unwrap();
+                    }
+                    RedrawEvent::TablineUpdate { current, tabs } => {
+                        components
+                            .tabline
+                            .send(VimTablineEvent::Update(current, tabs))
+                            .unwrap();
+                    }
                     _ => {
                         log::error!("Unhandled RedrawEvent {:?}", event);
                     }
                 }
             }
         }
+        self.overlays.set(self.overlays());
         true
     }
 }
@@ -815,6 +1851,10 @@ impl AppUpdate for AppModel {
 pub struct AppComponents {
     _messager: relm4::RelmMsgHandler<crate::messager::VimMessager, AppModel>,
     cmd_prompt: RelmComponent<VimCmdPrompts, AppModel>,
+    popup_menu: RelmComponent<VimPopupMenu, AppModel>,
+    tabline: RelmComponent<VimTabline, AppModel>,
+    message_history: RelmComponent<VimMessageHistory, AppModel>,
+    status_strip: RelmComponent<VimStatusStrip, AppModel>,
 }
 
 #[relm_macros::widget(pub)]
@@ -836,7 +1876,7 @@ impl Widgets<AppModel, ()> for AppWidgets {
                 set_can_target: true,
                 set_focus_on_click: true,
 
-                // set_child: Add tabline
+                append: components.tabline.root_widget(),
 
                 append: overlay = &gtk::Overlay {
                     set_focusable: true,
@@ -853,7 +1893,7 @@ impl Widgets<AppModel, ()> for AppWidgets {
                             log::debug!("da resizing width: {}, height: {}", width, height);
                             size.set((width, height));
                             let metrics = metrics.get();
-                            let rows = da.height() as f64 / metrics.height(); //  + metrics.linespace
+                            let rows = da.height() as f64 / metrics.height();
                             let cols = da.width() as f64 / metrics.width();
                             log::debug!("da resizing rows: {} cols: {}", rows, cols);
                             sender
@@ -866,14 +1906,27 @@ impl Widgets<AppModel, ()> for AppWidgets {
                                 )
                                 .unwrap();
                         },
-                        set_draw_func[hldefs = model.hldefs.clone()] => move |_da, cr, w, h| {
+                        set_draw_func[hldefs = model.hldefs.clone(), alpha = model.background_alpha(), image = model.background_image.clone()] => move |_da, cr, w, h| {
                             let hldefs = hldefs.read();
                             let default_colors = hldefs.defaults().unwrap();
                             log::debug!("drawing default background {}x{}.", w, h);
                             if let Some(bg) = default_colors.background {
                                 cr.rectangle(0., 0., w.into(), h.into());
-                                cr.set_source_rgb(bg.red() as _, bg.green() as _, bg.blue() as _);
+                                // replaces, translucent background must not blend with itself.
+                                cr.set_operator(cairo::Operator::Source);
+                                cr.set_source_rgba(bg.red() as _, bg.green() as _, bg.blue() as _, alpha);
                                 cr.paint().unwrap();
+                                cr.set_operator(cairo::Operator::Over);
+                            }
+                            if let Some(image) = image.as_ref() {
+                                let size = (image.width() as f64, image.height() as f64);
+                                let (scale, x, y) = background::cover(size, (w.into(), h.into()));
+                                cr.save().unwrap();
+                                cr.translate(x, y);
+                                cr.scale(scale, scale);
+                                cr.set_source_surface(image, 0., 0.).unwrap();
+                                cr.paint().unwrap();
+                                cr.restore().unwrap();
                             }
                         }
                     },
@@ -883,13 +1936,98 @@ impl Widgets<AppModel, ()> for AppWidgets {
                         set_focus_on_click: true,
                         factory!(model.vgrids),
                     },
-                    add_overlay: float_win_container = &gtk::Fixed {
+                    add_overlay: float_win_container = &vimview::FloatContainer {
                         set_widget_name: "float-win-container",
                         set_visible: false,
                         set_hexpand: false,
                         set_vexpand: false,
                     },
+                    add_overlay: message_grid_view = &vimview::VimGridView {
+                        set_widget_name: "message-grid",
+                        set_visible: false,
+                        set_halign: gtk::Align::Start,
+                        set_valign: gtk::Align::Start,
+                        set_overflow: gtk::Overflow::Hidden,
+                        set_hldefs: model.hldefs.clone(),
+                        set_metrics: model.metrics.clone(),
+                        // opaque, it covers grids beneath like floats do.
+                        set_css_classes: &["vim-message-grid"],
+                    },
+                    add_overlay: message_separator = &gtk::Label {
+                        set_widget_name: "message-separator",
+                        set_visible: false,
+                        set_can_target: false,
+                        set_selectable: false,
+                        set_halign: gtk::Align::Start,
+                        set_valign: gtk::Align::Start,
+                        set_overflow: gtk::Overflow::Hidden,
+                    },
                     add_overlay: model.cursor.root_widget(),
+                    add_overlay: preedit_da = &gtk::DrawingArea {
+                        set_widget_name: "preedit",
+                        set_visible: false,
+                        set_can_target: false,
+                        set_draw_func[hldefs = model.hldefs.clone(), pctx = model.pctx.clone(), font_description = model.font_description.clone(), metrics = model.metrics.clone(), preedit = model.preedit.clone(), at = model.preedit_at.clone()] => move |_da, cr, _w, _h| {
+                            let hldefs = hldefs.read();
+                            let colors = match hldefs.defaults() {
+                                Some(colors) => colors,
+                                None => return,
+                            };
+                            if let (Some(fg), Some(bg)) = (colors.foreground, colors.background) {
+                                let (x, y) = at.get();
+                                let position = (x, y, metrics.get().height());
+                                preedit.borrow().draw(cr, &pctx, &font_description.borrow(), position, &fg, &bg);
+                            }
+                        },
+                    },
+                    add_overlay: components.popup_menu.root_widget(),
+                    add_overlay: find_entry = &gtk::SearchEntry {
+                        set_widget_name: "find-on-screen",
+                        set_visible: watch!(model.find_pattern.is_some()),
+                        set_halign: gtk::Align::End,
+                        set_valign: gtk::Align::Start,
+                        set_placeholder_text: Some("Find on screen"),
+                        connect_search_changed[sender = sender.clone()] => move |entry| {
+                            sender.send(AppMessage::Find(entry.text().to_string())).unwrap();
+                        },
+                        connect_stop_search[sender = sender.clone()] => move |_| {
+                            sender.send(AppMessage::FindClose).unwrap();
+                        },
+                    },
+                    add_overlay: more_prompt = &gtk::Box {
+                        set_widget_name: "more-prompt",
+                        set_visible: watch!(model.more_prompt.is_some()),
+                        set_spacing: 5,
+                        set_focus_on_click: false,
+                        set_halign: gtk::Align::End,
+                        set_valign: gtk::Align::End,
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_css_classes: &["more-prompt"],
+                        append = &gtk::Button {
+                            set_label: "More",
+                            set_tooltip_text: Some("Next page <Space>"),
+                            set_focus_on_click: false,
+                            connect_clicked[sender = sender.clone()] => move |_| {
+                                sender.send(UiCommand::Serial(SerialCommand::Keyboard("<Space>".into())).into()).unwrap();
+                            },
+                        },
+                        append = &gtk::Button {
+                            set_label: "Line",
+                            set_tooltip_text: Some("Next line <CR>"),
+                            set_focus_on_click: false,
+                            connect_clicked[sender = sender.clone()] => move |_| {
+                                sender.send(UiCommand::Serial(SerialCommand::Keyboard("<CR>".into())).into()).unwrap();
+                            },
+                        },
+                        append = &gtk::Button {
+                            set_label: "Quit",
+                            set_tooltip_text: Some("Abort <q>"),
+                            set_focus_on_click: false,
+                            connect_clicked[sender = sender.clone()] => move |_| {
+                                sender.send(UiCommand::Serial(SerialCommand::Keyboard("q".into())).into()).unwrap();
+                            },
+                        },
+                    },
                     add_overlay: messages_container = &gtk::Box {
                         set_widget_name: "messages-container",
                         set_opacity: 0.95,
@@ -904,28 +2042,117 @@ impl Widgets<AppModel, ()> for AppWidgets {
                         set_valign: gtk::Align::Start,
                         set_overflow: gtk::Overflow::Visible,
                         set_orientation: gtk::Orientation::Vertical,
+                        append: more_messages = &gtk::Button {
+                            set_label: watch!(&format!("+{} more", model.collapsed_messages)),
+                            set_visible: watch!(model.collapsed_messages > 0),
+                            set_tooltip_text: Some("Show all messages"),
+                            set_halign: gtk::Align::End,
+                            set_focus_on_click: false,
+                            connect_clicked[sender = sender.clone()] => move |_| {
+                                sender.send(AppMessage::ExpandMessages).unwrap();
+                            },
+                        },
                         factory!(model.messages),
                     },
-                    // add_overlay: components.cmd_prompt.root_widget() ,
-                }
+                    add_overlay: bell_flash = &gtk::DrawingArea {
+                        set_widget_name: "bell-flash",
+                        set_visible: watch!(model.bell_flash),
+                        set_can_target: false,
+                        set_draw_func[hldefs = model.hldefs.clone()] => move |_da, cr, w, h| {
+                            let hldefs = hldefs.read();
+                            if let Some(fg) = hldefs.defaults().and_then(|colors| colors.foreground) {
+                                cr.rectangle(0., 0., w.into(), h.into());
+                                cr.set_source_rgba(fg.red() as _, fg.green() as _, fg.blue() as _, 0.2);
+                                cr.fill().unwrap();
+                            }
+                        },
+                    },
+                    add_overlay: components.cmd_prompt.root_widget(),
+                },
+                append: components.status_strip.root_widget(),
             },
-            connect_close_request[sender = sender.clone()] => move |_| {
-                sender.send(AppMessage::UiCommand(UiCommand::Parallel(ParallelCommand::Quit))).ok();
+            connect_close_request[sender = sender.clone(), quit = model.quit_command(), remember = model.opts.remember_window_size] => move |window| {
+                if remember {
+                    WindowState::of(window).save();
+                }
+                sender.send(AppMessage::UiCommand(UiCommand::Parallel(quit.clone()))).ok();
                 gtk::Inhibit(true)
             },
         }
     }
 
-    additional_fields! {
-        pointer_animation: adw::TimedAnimation,
-    }
-
     fn post_init() {
+        model.scale_factor.set(main_window.scale_factor() as f64);
+        main_window.connect_scale_factor_notify(glib::clone!(@strong sender => move |window| {
+            sender
+                .send(AppMessage::ScaleFactor(window.scale_factor() as f64))
+                .unwrap();
+        }));
         model.calculate();
         model.gtksettings.set(overlay.settings()).ok();
+        // measured before, by the fallback dpi without settings.
+        model.publish_cell_size();
+        if model.trailing_whitespace {
+            let matched = ParallelCommand::TrailingWhitespace(true);
+            EVENT_AGGREGATOR.send(UiCommand::Parallel(matched));
+        }
+        if model.opts.colorcolumn_rule {
+            EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::WatchColorColumn));
+        }
+        model
+            .float_container
+            .set(float_win_container.clone().upcast())
+            .ok();
+        model.main_window.set(main_window.clone()).ok();
+        vimview::connect_input(
+            &message_grid_view,
+            sender.clone(),
+            &model.dragging,
+            &model.metrics,
+            model.opts.middle_click_paste,
+        );
         let metrics = model.metrics.get();
-        let rows = (model.opts.height as f64 / metrics.height()).ceil() as i64;
-        let cols = (model.opts.width as f64 / metrics.width()).ceil() as i64;
+        let (rows, cols) = if let Some(geometry) = model.opts.geometry {
+            // size in cells known only after font measured.
+            let (width, height) = geometry.pixels(&metrics);
+            // tabline and status strip are above and below the grids.
+            let bars: i32 = [
+                components.tabline.root_widget().upcast_ref::<gtk::Widget>(),
+                components.status_strip.root_widget().upcast_ref(),
+            ]
+            .into_iter()
+            .filter(|bar| bar.is_visible())
+            .map(|bar| bar.measure(gtk::Orientation::Vertical, width).1)
+            .sum();
+            main_window.set_default_size(width, height + bars);
+            model.size.set((width, height));
+            (geometry.rows as i64, geometry.cols as i64)
+        } else {
+            (
+                (model.default_height as f64 / metrics.height()).ceil() as i64,
+                (model.default_width as f64 / metrics.width()).ceil() as i64,
+            )
+        };
+        let maximized = model.window_state.map_or(false, |state| state.maximized);
+        if model.opts.fullscreen {
+            main_window.fullscreen();
+        } else if model.opts.maximized || maximized {
+            main_window.maximize();
+        }
+        if model.background_alpha() < 1. {
+            if !main_window.display().is_composited() {
+                log::warn!("background opacity dose not supported without compositor.");
+            }
+            // backgrounds of grids drawn translucent, not the window behind them.
+            let provider = gtk::CssProvider::new();
+            provider.load_from_data(b"window.translucent { background: transparent; }");
+            gtk::StyleContext::add_provider_for_display(
+                &main_window.display(),
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+            main_window.add_css_class("translucent");
+        }
         let mut opts = model.opts.clone();
         opts.size.replace((cols, rows));
         model.rt.spawn(bridge::open(opts));
@@ -933,40 +2160,43 @@ impl Widgets<AppModel, ()> for AppWidgets {
         da.queue_resize();
         da.queue_draw();
 
-        let target = adw::CallbackAnimationTarget::new(Some(Box::new(
-            glib::clone!(@weak main_window => move |_| {
-                main_window.set_cursor_from_name(Some("text"));
-            }),
-        )));
-        let pointer_animation = adw::TimedAnimation::new(&main_window, 0., 1., 1000, &target);
-        pointer_animation.set_easing(adw::Easing::Linear);
-        pointer_animation.set_repeat_count(1);
-        pointer_animation.connect_done(move |this| {
-            this.widget().set_cursor_from_name(Some("none"));
-        });
-
         let im_context = gtk::IMMulticontext::new();
-        im_context.set_use_preedit(false);
+        im_context.set_use_preedit(true);
         im_context.set_client_widget(Some(&overlay));
 
         im_context.set_input_purpose(gtk::InputPurpose::Terminal);
 
         im_context.set_cursor_location(&gdk::Rectangle::new(0, 0, 5, 10));
-        // im_context.connect_preedit_start(|_| {
-        //     log::debug!("preedit started.");
-        // });
-        // im_context.connect_preedit_end(|im_context| {
-        //     log::debug!("preedit done, '{}'", im_context.preedit_string().0);
-        // });
-        // im_context.connect_preedit_changed(|im_context| {
-        //     log::debug!("preedit changed, '{}'", im_context.preedit_string().0);
-        // });
+        // composing text shown at cursor, neovim gets it only once committed.
+        im_context.connect_preedit_changed(
+            glib::clone!(@strong model.preedit as preedit, @weak preedit_da => move |im_context| {
+                let (text, attrs, cursor) = im_context.preedit_string();
+                log::debug!("preedit changed, '{}' cursor at {}", text, cursor);
+                preedit.replace(Preedit::new(&text, attrs, cursor));
+                preedit_da.set_visible(!text.is_empty());
+                preedit_da.queue_draw();
+            }),
+        );
+        im_context.connect_preedit_end(
+            glib::clone!(@strong model.preedit as preedit, @weak preedit_da => move |_| {
+                log::debug!("preedit done.");
+                preedit.replace(Preedit::default());
+                preedit_da.set_visible(false);
+            }),
+        );
 
+        let paste_threshold = model.opts.paste_threshold;
         im_context.connect_commit(glib::clone!(@strong sender => move |ctx, text| {
             log::debug!("im-context({}) commit '{}'", ctx.context_id(), text);
-            sender
-                .send(UiCommand::Serial(SerialCommand::Keyboard(text.replace("<", "<lt>").into())).into())
-                .unwrap();
+            if keys::commit_as_paste(text, paste_threshold) {
+                sender
+                    .send(UiCommand::Serial(SerialCommand::Paste(text.into())).into())
+                    .unwrap();
+            } else if let Some(input) = text.to_input() {
+                sender
+                    .send(UiCommand::Serial(SerialCommand::Keyboard(input)).into())
+                    .unwrap();
+            }
         }));
 
         main_window.set_focus_widget(Some(&overlay));
@@ -976,33 +2206,51 @@ impl Widgets<AppModel, ()> for AppWidgets {
             .flags(gtk::EventControllerScrollFlags::all())
             .name("vimview-scrolling-listener")
             .build();
-        listener.connect_scroll(glib::clone!(@strong sender, @strong model.mouse_on as mouse_on, @strong grids_container => move |c, x, y| {
+        let scroll_multiplier = model.opts.scroll_multiplier;
+        listener.connect_scroll(glib::clone!(@strong sender, @strong model.mouse_on as mouse_on, @strong model.wheel as wheel, @strong grids_container => move |c, x, y| {
             if !mouse_on.load(atomic::Ordering::Relaxed) {
                 return gtk::Inhibit(false)
             }
             let event = c.current_event().unwrap().downcast::<gdk::ScrollEvent>().unwrap();
-            let modifier = event.modifier_state();
+            let mut modifier = event.modifier_state();
             let id = GridActived.load(atomic::Ordering::Relaxed);
-            let direction = match event.direction() {
-                ScrollDirection::Up => {
-                    "up"
-                },
-                    ScrollDirection::Down => {
-                    "down"
-                }
-                ScrollDirection::Left => {
-                    "left"
-                }
-                ScrollDirection::Right => {
-                    "right"
-                }
+            let position = *PointerPosition.read();
+            let (mut x, mut y) = match event.direction() {
+                ScrollDirection::Up | ScrollDirection::Down => (0., y),
+                ScrollDirection::Left | ScrollDirection::Right => (x, 0.),
+                ScrollDirection::Smooth => (x, y),
                 _ => {
                     return gtk::Inhibit(false)
                 }
             };
-            log::debug!("scrolling grid {} x: {}, y: {} {}", id, x, y, &direction);
-            let command = UiCommand::Serial(SerialCommand::Scroll { direction: direction.into(), grid_id: id, position: (0, 1), modifier });
-            sender.send(AppMessage::UiCommand(command)).unwrap();
+            // ctrl+wheel zooms font by gui.
+            if modifier.contains(gdk::ModifierType::CONTROL_MASK) {
+                let mut accumulator = wheel.get();
+                let steps = accumulator.steps(0., y, 1.);
+                wheel.set(accumulator);
+                for (direction, count) in steps {
+                    let zoom = if direction == "up" { Zoom::In } else { Zoom::Out };
+                    for _ in 0..count {
+                        sender.send(AppMessage::Zoom(zoom)).unwrap();
+                    }
+                }
+                return gtk::Inhibit(true)
+            }
+            if modifier.contains(gdk::ModifierType::SHIFT_MASK) {
+                (x, y) = wheel::shift_horizontal(x, y);
+                modifier.remove(gdk::ModifierType::SHIFT_MASK);
+            }
+            // touchpads scroll by fractions, one step per whole line or column.
+            let mut accumulator = wheel.get();
+            let steps = accumulator.steps(x, y, scroll_multiplier);
+            wheel.set(accumulator);
+            for (direction, count) in steps {
+                log::debug!("scrolling grid {} {} by {} at {:?}", id, direction, count, position);
+                for _ in 0..count {
+                    let command = UiCommand::Serial(SerialCommand::Scroll { direction: direction.into(), grid_id: id, position, modifier });
+                    sender.send(AppMessage::UiCommand(command)).unwrap();
+                }
+            }
             gtk::Inhibit(false)
         }));
 
@@ -1027,18 +2275,88 @@ impl Widgets<AppModel, ()> for AppWidgets {
         );
         main_window.add_controller(&focus_controller);
 
+        // I-beam over text of grids, arrow elsewhere or while neovim takes no mouse.
+        let pointer_listener = gtk::EventControllerMotion::builder()
+            .name("vimview-pointer-listener")
+            .build();
+        pointer_listener.connect_motion(glib::clone!(@strong sender, @strong model.pointer_over_grid as over_grid, @weak main_window => move |_, x, y| {
+            let grid = main_window
+                .pick(x, y, gtk::PickFlags::DEFAULT)
+                .and_then(|widget| widget.ancestor(vimview::VimGridView::static_type()));
+            over_grid.set(grid.is_some());
+            sender.send(AppMessage::ShowPointer).unwrap();
+        }));
+        main_window.add_controller(&pointer_listener);
+
+        // files dragged from file managers, opened in window under pointer.
+        let drop_target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+        drop_target.connect_drop(glib::clone!(@strong sender, @weak main_window => @default-return false, move |_, value, x, y| {
+            let files = match value.get::<gdk::FileList>() {
+                Ok(files) => files.files(),
+                Err(_) => return false,
+            };
+            let paths: Vec<String> = files
+                .iter()
+                .filter_map(|file| file.path())
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            if paths.is_empty() {
+                return false;
+            }
+            let grid = main_window
+                .pick(x, y, gtk::PickFlags::DEFAULT)
+                .and_then(|widget| widget.ancestor(vimview::VimGridView::static_type()))
+                .and_then(|widget| widget.downcast::<vimview::VimGridView>().ok())
+                .map(|view| view.id());
+            sender.send(AppMessage::DropFiles(paths, grid)).unwrap();
+            true
+        }));
+        main_window.add_controller(&drop_target);
+
         let key_controller = gtk::EventControllerKey::builder()
             .name("vimview-key-controller")
             .build();
-        key_controller.set_im_context(&im_context);
+        let escape_dismiss_overlays = model.opts.escape_dismiss_overlays;
+        let alt_as_meta = model.opts.alt_as_meta;
+        let find_on_screen = model.opts.find_on_screen;
+        let mousehide = model.opts.mousehide;
+        // input method filters each key once here, not by the controller too, so dead keys
+        // and compose sequences are committed exactly once.
         key_controller.connect_key_pressed(
-            glib::clone!(@strong sender => move |c, keyval, _keycode, modifier| {
+            glib::clone!(@strong sender, @strong model.overlays as overlays, @strong im_context => move |c, keyval, _keycode, modifier| {
                 let event = c.current_event().unwrap();
+                let consumed = event
+                    .downcast_ref::<gdk::KeyEvent>()
+                    .map_or(gdk::ModifierType::empty(), |event| event.consumed_modifiers());
+                let modifier = keys::effective_modifier(modifier, consumed);
+                // pressing Ctrl alone e.g. for a link is no typing.
+                let typing = event
+                    .downcast_ref::<gdk::KeyEvent>()
+                    .map_or(true, |event| !event.is_modifier());
+                if mousehide && typing {
+                    sender.send(AppMessage::HidePointer).unwrap();
+                }
 
-                if c.im_context().filter_keypress(&event) {
+                if !keys::bypass_im_context(alt_as_meta, modifier) && im_context.filter_keypress(&event) {
                     log::debug!("keypress handled by im-context.");
                     return gtk::Inhibit(true)
                 }
+                if find_on_screen && keyval == gdk::Key::f && modifier == gdk::ModifierType::CONTROL_MASK {
+                    sender.send(AppMessage::Find(String::new())).unwrap();
+                    return gtk::Inhibit(true)
+                }
+                if let Some(zoom) = keys::zoom(keyval, modifier) {
+                    sender.send(AppMessage::Zoom(zoom)).unwrap();
+                    return gtk::Inhibit(true)
+                }
+                if keys::paste(keyval, modifier) {
+                    sender.send(AppMessage::Paste(Register::Clipboard)).unwrap();
+                    return gtk::Inhibit(true)
+                }
+                if keys::escape_dismisses_overlay(keyval, modifier, escape_dismiss_overlays, overlays.get()) {
+                    sender.send(AppMessage::DismissOverlays).unwrap();
+                    return gtk::Inhibit(true)
+                }
                 let keypress = (keyval, modifier);
                 log::debug!("keypress : {:?}", keypress);
                 if let Some(keypress) = keypress.to_input() {
@@ -1051,27 +2369,132 @@ impl Widgets<AppModel, ()> for AppWidgets {
                 }
             }),
         );
+        key_controller.connect_key_released(
+            glib::clone!(@strong im_context => move |c, _keyval, _keycode, _modifier| {
+                if let Some(event) = c.current_event() {
+                    im_context.filter_keypress(&event);
+                }
+            }),
+        );
         overlay.add_controller(&key_controller);
         model.im_context.set(im_context).unwrap();
     }
 
     fn pre_view() {
-        if let Ok(true) = model.show_pointer.compare_exchange(
+        self.float_win_container
+            .set_visible(model.vgrids.iter().any(|(_, vgrid)| vgrid.is_float()));
+        if let Ok(true) = model.find_focus_changed.compare_exchange(
+            true,
+            false,
+            atomic::Ordering::Acquire,
+            atomic::Ordering::Relaxed,
+        ) {
+            if model.find_pattern.is_some() {
+                self.find_entry.set_text("");
+                self.find_entry.grab_focus();
+            } else {
+                self.overlay.grab_focus();
+            }
+        }
+        if let Ok(true) = model.opacity_changed.compare_exchange(
+            true,
+            false,
+            atomic::Ordering::Acquire,
+            atomic::Ordering::Relaxed,
+        ) {
+            if self.main_window.display().is_composited() {
+                self.main_window.set_opacity(model.opacity);
+            } else {
+                log::warn!("window opacity dose not supported without compositor.");
+            }
+            WindowOpacity.store(self.main_window.opacity().to_bits(), atomic::Ordering::Relaxed);
+        }
+        if let Ok(true) = model.fullscreen_toggled.compare_exchange(
+            true,
+            false,
+            atomic::Ordering::Acquire,
+            atomic::Ordering::Relaxed,
+        ) {
+            // window returns to maximized or normal size as before.
+            if self.main_window.is_fullscreen() {
+                self.main_window.unfullscreen();
+            } else {
+                self.main_window.fullscreen();
+            }
+        }
+        if model.redraw.take(Target::MessageGrid) {
+            let view = &self.message_grid_view;
+            let grid = model.message_grid.grid();
+            let vgrid = grid.and_then(|grid| model.vgrids.get(grid));
+            if let (Some(grid), Some(vgrid)) = (grid, vgrid) {
+                let top = model.message_grid.top(model.metrics.get().height());
+                // input over messages goes to the grid it draws.
+                view.set_property("id", grid);
+                view.set_textbuf(vgrid.textbuf().clone());
+                view.set_margin_top(top as i32);
+                view.set_visible(true);
+                view.queue_resize();
+                view.queue_draw();
+            } else {
+                view.set_visible(false);
+            }
+            // rule above scrolled messages, neovim leaves it to ui with multigrid.
+            let separator = &self.message_separator;
+            match (model.message_grid.separator(), vgrid) {
+                (Some(c), Some(vgrid)) => {
+                    let height = model.metrics.get().height();
+                    let top = model.message_grid.top(height) - height;
+                    let hlid = model
+                        .hlgroups
+                        .read()
+                        .get(MSGSEPARATOR_HLGROUP)
+                        .copied()
+                        .unwrap_or(vimview::HighlightDefinitions::DEFAULT);
+                    let mut text = String::new();
+                    let attrs = pango::AttrList::new();
+                    if let Some(desc) = model.pctx.font_description() {
+                        attrs.insert(pango::AttrFontDesc::new(&desc));
+                    }
+                    crate::components::push_styled_content(
+                        &mut text,
+                        &attrs,
+                        &model.hldefs.read(),
+                        vec![(hlid, c.to_string().repeat(vgrid.width()))],
+                    );
+                    separator.set_text(&text);
+                    separator.set_attributes(Some(&attrs));
+                    separator.set_margin_top(top as i32);
+                    separator.set_visible(true);
+                }
+                _ => separator.set_visible(false),
+            }
+        }
+        if let Ok(true) = model.hide_pointer.compare_exchange(
             true,
             false,
             atomic::Ordering::Acquire,
             atomic::Ordering::Relaxed,
         ) {
-            self.pointer_animation.play();
+            self.main_window.set_cursor_from_name(Some("none"));
         }
-        if let Ok(true) = model.background_changed.compare_exchange(
+        // moving pointer shows it again, after typing hid it.
+        if let Ok(true) = model.show_pointer.compare_exchange(
             true,
             false,
             atomic::Ordering::Acquire,
             atomic::Ordering::Relaxed,
         ) {
+            let over_text =
+                model.pointer_over_grid.get() && model.mouse_on.load(atomic::Ordering::Relaxed);
+            let name = if over_text { "text" } else { "default" };
+            self.main_window.set_cursor_from_name(Some(name));
+        }
+        if model.redraw.take(Target::Background) {
             self.da.queue_draw();
         }
+        if model.redraw.take(Target::Cursor) {
+            model.cursor.update_view().unwrap();
+        }
         if let Ok(true) = model.cursor_coord_changed.compare_exchange(
             true,
             false,
@@ -1090,6 +2513,11 @@ impl Widgets<AppModel, ()> for AppWidgets {
                     metrics.height() as i32,
                 );
                 unsafe { model.im_context.get_unchecked() }.set_cursor_location(&rect);
+                // composing text follows cursor.
+                model.preedit_at.set((x, y));
+                if self.preedit_da.is_visible() {
+                    self.preedit_da.queue_draw();
+                }
             }
         }
         if let Ok(true) = model.font_changed.compare_exchange(
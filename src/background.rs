@@ -0,0 +1,43 @@
+use std::path::Path;
+
+/// Background image of window, logs and drops it if unreadable.
+pub fn load(path: &Path) -> Option<cairo::ImageSurface> {
+    let image = std::fs::File::open(path)
+        .map_err(cairo::IoError::Io)
+        .and_then(|mut file| cairo::ImageSurface::create_from_png(&mut file));
+    match image {
+        Ok(image) => Some(image),
+        Err(err) => {
+            log::warn!("invalid background image {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Scale and offset of an image of `image` size covering `area`, centered,
+/// as `(scale, x, y)`. The overflowing side is cropped evenly.
+pub fn cover(image: (f64, f64), area: (f64, f64)) -> (f64, f64, f64) {
+    let (width, height) = image;
+    if width <= 0. || height <= 0. {
+        return (1., 0., 0.);
+    }
+    let scale = (area.0 / width).max(area.1 / height);
+    let x = (area.0 - width * scale) / 2.;
+    let y = (area.1 - height * scale) / 2.;
+    (scale, x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cover() {
+        assert_eq!(cover((100., 100.), (200., 200.)), (2., 0., 0.));
+        // wider area, image cropped at top and bottom.
+        assert_eq!(cover((100., 100.), (200., 100.)), (2., 0., -50.));
+        // taller area, image cropped at left and right.
+        assert_eq!(cover((200., 100.), (100., 100.)), (1., -50., 0.));
+        assert_eq!(cover((0., 0.), (100., 100.)), (1., 0., 0.));
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+/// Time the window stays flashed by a visual bell.
+pub const FLASH: Duration = Duration::from_millis(100);
+
+/// What rings a bell of neovim, selected by `--bell`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bell {
+    /// Beep for `bell`, flash for `visual_bell` as `visualbell` asks.
+    Audible,
+    /// Flash for both.
+    Visual,
+    None,
+}
+
+impl Bell {
+    /// Beep the display for an event of neovim, `visual` for `visual_bell`.
+    pub fn beeps(&self, visual: bool) -> bool {
+        matches!(self, Bell::Audible) && !visual
+    }
+
+    /// Flash the window for an event of neovim, `visual` for `visual_bell`.
+    pub fn flashes(&self, visual: bool) -> bool {
+        match self {
+            Bell::Audible => visual,
+            Bell::Visual => true,
+            Bell::None => false,
+        }
+    }
+}
+
+impl Default for Bell {
+    fn default() -> Self {
+        Bell::Audible
+    }
+}
+
+impl std::str::FromStr for Bell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "audible" => Ok(Bell::Audible),
+            "visual" => Ok(Bell::Visual),
+            "none" => Ok(Bell::None),
+            _ => Err(format!(
+                "unknown bell '{}', expect audible, visual or none",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("audible".parse(), Ok(Bell::Audible));
+        assert_eq!("visual".parse(), Ok(Bell::Visual));
+        assert_eq!("none".parse(), Ok(Bell::None));
+        assert!("loud".parse::<Bell>().is_err());
+    }
+
+    #[test]
+    fn test_ring() {
+        // `:set visualbell` makes neovim send visual_bell instead.
+        assert!(Bell::Audible.beeps(false));
+        assert!(!Bell::Audible.flashes(false));
+        assert!(Bell::Audible.flashes(true));
+        assert!(!Bell::Audible.beeps(true));
+
+        assert!(Bell::Visual.flashes(false));
+        assert!(!Bell::Visual.beeps(false));
+
+        assert!(!Bell::None.beeps(false));
+        assert!(!Bell::None.flashes(true));
+    }
+}
@@ -1,56 +1,149 @@
-use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
 
-use nvim::Value;
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use tokio::sync::oneshot;
 
-use clipboard::ClipboardContext;
-use clipboard::ClipboardProvider;
+/// Registers of neovim backed by clipboards of display, `*` by PRIMARY and `+` by CLIPBOARD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Register {
+    Primary,
+    Clipboard,
+}
+
+impl Register {
+    pub fn parse(name: &str) -> Option<Register> {
+        match name {
+            "*" => Some(Register::Primary),
+            "+" => Some(Register::Clipboard),
+            _ => None,
+        }
+    }
+}
 
-pub fn get_remote_clipboard(format: Option<&str>) -> Result<Value, Box<dyn Error>> {
-    let mut ctx: ClipboardContext = ClipboardProvider::new()?;
-    let clipboard_raw = ctx.get_contents()?.replace('\r', "");
+/// Type of register, `v` charwise, `V` linewise and `b` blockwise.
+const CHARWISE: &str = "v";
+const LINEWISE: &str = "V";
 
-    let lines = if let Some("dos") = format {
-        // add \r to lines of current file format is dos
-        clipboard_raw.replace('\n', "\r\n")
-    } else {
-        // else, \r is stripped, leaving only \n
-        clipboard_raw
+/// Contents copied to clipboards, type of register is kept while clipboard still holds them.
+#[derive(Debug, Default)]
+pub struct Registers {
+    copied: FxHashMap<Register, (String, String)>,
+}
+
+impl Registers {
+    /// Text of `lines` put in clipboard, linewise ends with newline.
+    pub fn copy(&mut self, register: Register, lines: &[String], regtype: &str) -> String {
+        let mut text = lines.join("\n");
+        if regtype == LINEWISE {
+            text.push('\n');
+        }
+        self.copied
+            .insert(register, (text.clone(), regtype.to_string()));
+        text
     }
-    .split('\n')
-    .map(Value::from)
-    .collect::<Vec<Value>>();
 
-    let lines = Value::from(lines);
-    // v paste is normal paste (everything in lines is pasted)
-    // V paste is paste with extra endline (line paste)
-    // If you want V paste, copy text with extra endline
-    let paste_mode = Value::from("v");
+    /// Lines and type of register from `text` of clipboard, linewise if it ends with newline.
+    pub fn paste(&self, register: Register, text: &str) -> (Vec<String>, String) {
+        let text = text.replace('\r', "");
+        let regtype = match self.copied.get(&register) {
+            Some((copied, regtype)) if *copied == text => regtype.as_str(),
+            _ if text.ends_with('\n') => LINEWISE,
+            _ => CHARWISE,
+        };
+        let text = match regtype {
+            LINEWISE => text.strip_suffix('\n').unwrap_or(&text),
+            _ => &text,
+        };
+        let lines = text.split('\n').map(String::from).collect();
+        (lines, regtype.to_string())
+    }
+}
+
+/// Reply of text read from clipboard, sent once.
+#[derive(Clone)]
+pub struct ClipboardReply(Arc<Mutex<Option<oneshot::Sender<Option<String>>>>>);
+
+impl ClipboardReply {
+    pub fn new() -> (ClipboardReply, oneshot::Receiver<Option<String>>) {
+        let (tx, rx) = oneshot::channel();
+        (ClipboardReply(Arc::new(Mutex::new(Some(tx)))), rx)
+    }
+
+    pub fn send(&self, text: Option<String>) {
+        if let Some(tx) = self.0.lock().take() {
+            tx.send(text).ok();
+        }
+    }
+}
 
-    // returns [content: [String], paste_mode: v or V]
-    Ok(Value::from(vec![lines, paste_mode]))
+impl fmt::Debug for ClipboardReply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ClipboardReply")
+    }
 }
 
-pub fn set_remote_clipboard(arguments: Vec<Value>) -> Result<(), Box<dyn Error>> {
-    if arguments.len() != 3 {
-        return Err("expected exactly 3 arguments to set_remote_clipboard".into());
-    }
-
-    #[cfg(not(windows))]
-    let endline = "\n";
-    #[cfg(windows)]
-    let endline = "\r\n";
-
-    let lines = arguments[0]
-        .as_array()
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|x| x.as_str().map(String::from))
-                .map(|s| s.replace('\r', "")) // strip \r
-                .collect::<Vec<String>>()
-                .join(endline)
-        })
-        .ok_or("can't build string from provided text")?;
-
-    let mut ctx: ClipboardContext = ClipboardProvider::new()?;
-    ctx.set_contents(lines)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_register() {
+        assert_eq!(Register::parse("*"), Some(Register::Primary));
+        assert_eq!(Register::parse("+"), Some(Register::Clipboard));
+        assert_eq!(Register::parse("a"), None);
+    }
+
+    #[test]
+    fn test_copy_paste() {
+        let mut registers = Registers::default();
+        let yanked = lines(&["fn main() {", "}"]);
+
+        let text = registers.copy(Register::Clipboard, &yanked, "V");
+        assert_eq!(text, "fn main() {\n}\n");
+        assert_eq!(
+            registers.paste(Register::Clipboard, &text),
+            (yanked.clone(), "V".to_string())
+        );
+
+        let text = registers.copy(Register::Clipboard, &yanked, "v");
+        assert_eq!(text, "fn main() {\n}");
+        assert_eq!(
+            registers.paste(Register::Clipboard, &text),
+            (yanked.clone(), "v".to_string())
+        );
+
+        // blockwise kept while clipboard holds the copy.
+        let text = registers.copy(Register::Primary, &lines(&["ab", "cd"]), "\u{16}2");
+        assert_eq!(
+            registers.paste(Register::Primary, &text),
+            (lines(&["ab", "cd"]), "\u{16}2".to_string())
+        );
+        assert_eq!(
+            registers.paste(Register::Clipboard, &text),
+            (lines(&["ab", "cd"]), "v".to_string())
+        );
+    }
+
+    #[test]
+    fn test_paste_from_others() {
+        let registers = Registers::default();
+        assert_eq!(
+            registers.paste(Register::Clipboard, "one\r\ntwo\r\n"),
+            (lines(&["one", "two"]), "V".to_string())
+        );
+        assert_eq!(
+            registers.paste(Register::Clipboard, "word"),
+            (lines(&["word"]), "v".to_string())
+        );
+        assert_eq!(
+            registers.paste(Register::Primary, ""),
+            (lines(&[""]), "v".to_string())
+        );
+    }
 }
@@ -3,6 +3,7 @@
 
 use std::{
     io::{self, Error, ErrorKind},
+    path::Path,
     process::Stdio,
 };
 
@@ -35,6 +36,50 @@ where
     Ok((neovim, io_handle))
 }
 
+/// Connect to a neovim instance via unix socket, e.g. `$NVIM` of its terminals
+#[cfg(unix)]
+pub async fn new_socket<H>(
+    path: &Path,
+    handler: H,
+) -> io::Result<(Neovim<TxWrapper>, JoinHandle<Result<(), Box<LoopError>>>)>
+where
+    H: Handler<Writer = TxWrapper>,
+{
+    let stream = tokio::net::UnixStream::connect(path).await?;
+    let (reader, writer) = split(stream);
+    let (neovim, io) = Neovim::<TxWrapper>::new(reader.compat(), writer.wrap_tx(), handler);
+    let io_handle = spawn(io);
+
+    Ok((neovim, io_handle))
+}
+
+/// Connect to a neovim instance via named pipe, e.g. `\\.\pipe\nvim`
+#[cfg(windows)]
+pub async fn new_socket<H>(
+    path: &Path,
+    handler: H,
+) -> io::Result<(Neovim<TxWrapper>, JoinHandle<Result<(), Box<LoopError>>>)>
+where
+    H: Handler<Writer = TxWrapper>,
+{
+    let pipe = tokio::net::windows::named_pipe::ClientOptions::new().open(path)?;
+    let (reader, writer) = split(pipe);
+    let (neovim, io) = Neovim::<TxWrapper>::new(reader.compat(), writer.wrap_tx(), handler);
+    let io_handle = spawn(io);
+
+    Ok((neovim, io_handle))
+}
+
+/// `HOST:PORT` of tcp, anything else taken as path of socket or named pipe.
+pub fn is_tcp_address(address: &str) -> bool {
+    match address.rsplit_once(':') {
+        Some((host, port)) => {
+            !host.is_empty() && !host.contains(&['/', '\\'][..]) && port.parse::<u16>().is_ok()
+        }
+        None => false,
+    }
+}
+
 /// Connect to a neovim instance by spawning a new one
 ///
 /// stdin/stdout will be rewritten to `Stdio::piped()`
@@ -62,3 +107,20 @@ where
 
     Ok((neovim, io_handle))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_tcp_address() {
+        assert!(is_tcp_address("127.0.0.1:6666"));
+        assert!(is_tcp_address("localhost:6666"));
+        assert!(is_tcp_address("[::1]:6666"));
+        assert!(!is_tcp_address("/run/user/1000/nvim.1234.0"));
+        assert!(!is_tcp_address("./nvim.sock"));
+        assert!(!is_tcp_address(r"\\.\pipe\nvim-1234"));
+        assert!(!is_tcp_address(":6666"));
+        assert!(!is_tcp_address("localhost:nvim"));
+    }
+}
@@ -64,6 +64,22 @@ pub struct GridLineCell {
 
 pub type StyledContent = Vec<(u64, String)>;
 
+/// Tab page of tabline, `tab` is the handle to switch to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tab {
+    pub tab: Value,
+    pub name: String,
+}
+
+/// Candidate of completion popup menu.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PopupMenuItem {
+    pub word: String,
+    pub kind: String,
+    pub menu: String,
+    pub info: String,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum MessageKind {
     Unknown,
@@ -99,6 +115,14 @@ impl MessageKind {
             _ => MessageKind::Unknown,
         }
     }
+
+    /// Notifications worth a glance only, e.g. `:echo` or written file.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            MessageKind::Unknown | MessageKind::Echo | MessageKind::EchoMessage
+        )
+    }
 }
 impl std::fmt::Display for MessageKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
@@ -155,6 +179,7 @@ pub enum EditorMode {
     Visual,
     Replace,
     CmdLine,
+    Terminal,
     Unknown(String),
 }
 
@@ -176,6 +201,8 @@ pub enum RedrawEvent {
     },
     MouseOn,
     MouseOff,
+    Bell,
+    VisualBell,
     BusyStart,
     BusyStop,
     Flush,
@@ -190,6 +217,8 @@ pub enum RedrawEvent {
     HighlightAttributesDefine {
         id: u64,
         style: Style,
+        // highlight groups of `hi_name` from ext_hlstate info.
+        names: Vec<String>,
     },
     HighlightGroupSet {
         id: u64,
@@ -307,6 +336,22 @@ pub enum RedrawEvent {
     MessageHistoryShow {
         entries: Vec<(MessageKind, StyledContent)>,
     },
+    PopupMenuShow {
+        items: Vec<PopupMenuItem>,
+        selected: Option<u64>,
+        row: u64,
+        column: u64,
+        // -1 for cmdline completion.
+        grid: i64,
+    },
+    PopupMenuSelect {
+        selected: Option<u64>,
+    },
+    PopupMenuHide,
+    TablineUpdate {
+        current: Value,
+        tabs: Vec<Tab>,
+    },
 }
 
 #[derive(Debug)]
@@ -447,6 +492,9 @@ fn parse_mode_info_set(mode_info_set_arguments: Vec<Value>) -> Result<RedrawEven
 
         for (name, value) in info_map {
             match parse_string(name)?.as_str() {
+                "name" => {
+                    mode_info.name = Some(parse_string(value)?);
+                }
                 "cursor_shape" => {
                     mode_info.shape = CursorShape::from_type_name(&parse_string(value)?);
                 }
@@ -508,6 +556,7 @@ fn parse_mode_change(mode_change_arguments: Vec<Value>) -> Result<RedrawEvent> {
             "visual" => EditorMode::Visual,
             "replace" => EditorMode::Replace,
             "cmdline_normal" => EditorMode::CmdLine,
+            "terminal" => EditorMode::Terminal,
             _ => EditorMode::Unknown(mode_name),
         },
         mode_index: parse_u64(mode_index)?,
@@ -562,6 +611,12 @@ fn parse_style(style_map: Value) -> Result<Style> {
                 }
                 ("underline", Value::Boolean(underline)) => style.underline = underline,
                 ("undercurl", Value::Boolean(undercurl)) => style.undercurl = undercurl,
+                // `underlineline` before neovim 0.8.
+                ("underdouble" | "underlineline", Value::Boolean(underdouble)) => {
+                    style.underdouble = underdouble
+                }
+                ("underdotted", Value::Boolean(underdotted)) => style.underdotted = underdotted,
+                ("underdashed", Value::Boolean(underdashed)) => style.underdashed = underdashed,
                 ("blend", Value::Integer(blend)) => style.blend = blend.as_u64().unwrap() as u8,
                 _ => debug!("Ignored style attribute: {}", name),
             }
@@ -574,12 +629,28 @@ fn parse_style(style_map: Value) -> Result<Style> {
 }
 
 fn parse_hl_attr_define(hl_attr_define_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let [id, attributes, _terminal_attributes, _info] = extract_values(hl_attr_define_arguments)?;
+    let [id, attributes, _terminal_attributes, info] = extract_values(hl_attr_define_arguments)?;
 
     let style = parse_style(attributes)?;
+    let names = info
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_map)
+                .filter_map(|item| {
+                    item.iter()
+                        .find(|(k, _)| k.as_str() == Some("hi_name"))
+                        .and_then(|(_, v)| v.as_str())
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
     Ok(RedrawEvent::HighlightAttributesDefine {
         id: parse_u64(id)?,
         style,
+        names,
     })
 }
 
@@ -913,6 +984,75 @@ fn parse_msg_history_show(msg_history_show_arguments: Vec<Value>) -> Result<Redr
     })
 }
 
+fn parse_tab(tab: Value) -> Result<Tab> {
+    let mut handle = None;
+    let mut name = None;
+    for (key, value) in parse_map(tab)? {
+        match parse_string(key)?.as_str() {
+            "tab" => handle = Some(value),
+            "name" => name = Some(parse_string(value)?),
+            _ => {}
+        }
+    }
+    match (handle, name) {
+        (Some(tab), Some(name)) => Ok(Tab { tab, name }),
+        (tab, name) => Err(ParseError::Format(format!("tab {:?} {:?}", tab, name))),
+    }
+}
+
+fn parse_tabline_update(tabline_update_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    // buffers of nvim 0.6+ are ignored, tab pages only.
+    let ([current, tabs], _) = extract_values_with_optional::<2, 2>(tabline_update_arguments)?;
+
+    Ok(RedrawEvent::TablineUpdate {
+        current,
+        tabs: parse_array(tabs)?
+            .into_iter()
+            .map(parse_tab)
+            .collect::<Result<_>>()?,
+    })
+}
+
+fn parse_popupmenu_item(item: Value) -> Result<PopupMenuItem> {
+    let [word, kind, menu, info] = extract_values(parse_array(item)?)?;
+
+    Ok(PopupMenuItem {
+        word: parse_string(word)?,
+        kind: parse_string(kind)?,
+        menu: parse_string(menu)?,
+        info: parse_string(info)?,
+    })
+}
+
+// -1 for no item selected.
+fn parse_popupmenu_selected(selected: Value) -> Result<Option<u64>> {
+    let selected = parse_i64(selected)?;
+    Ok(u64::try_from(selected).ok())
+}
+
+fn parse_popupmenu_show(popupmenu_show_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [items, selected, row, column, grid] = extract_values(popupmenu_show_arguments)?;
+
+    Ok(RedrawEvent::PopupMenuShow {
+        items: parse_array(items)?
+            .into_iter()
+            .map(parse_popupmenu_item)
+            .collect::<Result<_>>()?,
+        selected: parse_popupmenu_selected(selected)?,
+        row: parse_u64(row)?,
+        column: parse_u64(column)?,
+        grid: parse_i64(grid)?,
+    })
+}
+
+fn parse_popupmenu_select(popupmenu_select_arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [selected] = extract_values(popupmenu_select_arguments)?;
+
+    Ok(RedrawEvent::PopupMenuSelect {
+        selected: parse_popupmenu_selected(selected)?,
+    })
+}
+
 pub fn parse_redraw_event(
     event_value: Value,
     neovim: nvim::Neovim<TxWrapper>,
@@ -936,6 +1076,8 @@ pub fn parse_redraw_event(
             "mode_change" => Some(parse_mode_change(event_parameters)?),
             "mouse_on" => Some(RedrawEvent::MouseOn),
             "mouse_off" => Some(RedrawEvent::MouseOff),
+            "bell" => Some(RedrawEvent::Bell),
+            "visual_bell" => Some(RedrawEvent::VisualBell),
             "busy_start" => Some(RedrawEvent::BusyStart),
             "busy_stop" => Some(RedrawEvent::BusyStop),
             "flush" => Some(RedrawEvent::Flush),
@@ -968,6 +1110,10 @@ pub fn parse_redraw_event(
             "msg_showcmd" => Some(parse_msg_showcmd(event_parameters)?),
             "msg_ruler" => Some(parse_msg_ruler(event_parameters)?),
             "msg_history_show" => Some(parse_msg_history_show(event_parameters)?),
+            "popupmenu_show" => Some(parse_popupmenu_show(event_parameters)?),
+            "popupmenu_select" => Some(parse_popupmenu_select(event_parameters)?),
+            "popupmenu_hide" => Some(RedrawEvent::PopupMenuHide),
+            "tabline_update" => Some(parse_tabline_update(event_parameters)?),
             _ => None,
         };
 
@@ -1049,3 +1195,186 @@ pub fn parse_channel_list(channel_infos: Vec<Value>) -> Result<Vec<ChannelInfo>>
         .map(parse_channel_info)
         .collect::<Result<Vec<ChannelInfo>>>()
 }
+
+/// Handle of window sent as msgpack extension, `None` if it is not one.
+pub fn window_handle(window: &Value) -> Option<u64> {
+    let data = match window {
+        Value::Ext(_, data) => data.as_slice(),
+        _ => return None,
+    };
+    // handle is a msgpack encoded unsigned integer.
+    let (marker, bytes) = data.split_first()?;
+    let width = match marker {
+        0x00..=0x7f => return Some(*marker as u64).filter(|_| bytes.is_empty()),
+        0xcc => 1,
+        0xcd => 2,
+        0xce => 4,
+        0xcf => 8,
+        _ => return None,
+    };
+    if bytes.len() != width {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .fold(0, |handle, byte| handle << 8 | *byte as u64),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_message_kind() {
+        assert!(MessageKind::parse("echo").is_transient());
+        assert!(MessageKind::parse("echomsg").is_transient());
+        assert!(MessageKind::parse("").is_transient());
+        assert!(!MessageKind::parse("emsg").is_transient());
+        assert!(!MessageKind::parse("echoerr").is_transient());
+        assert!(!MessageKind::parse("confirm").is_transient());
+    }
+
+    #[test]
+    fn test_grid_line_trailing_cells() {
+        // `set cursorline`, cells past end of line are sent repeated by the same highlight.
+        let cell = |values: Vec<Value>| Value::Array(values);
+        let arguments = vec![
+            Value::from(2),
+            Value::from(5),
+            Value::from(0),
+            Value::Array(vec![
+                cell(vec![Value::from("f"), Value::from(7)]),
+                cell(vec![Value::from("n")]),
+                cell(vec![Value::from(" "), Value::from(7), Value::from(78)]),
+            ]),
+        ];
+        let cells = match parse_grid_line(arguments).unwrap() {
+            RedrawEvent::GridLine { cells, .. } => cells,
+            _ => unreachable!(),
+        };
+        assert!(cells.iter().all(|cell| cell.hldef == Some(7)));
+        assert_eq!(cells[2].repeat, Some(78));
+    }
+
+    #[test]
+    fn test_ext_messages_events() {
+        // as sent by neovim attached with ext_messages.
+        let chunk = |hl: u64, text: &str| Value::Array(vec![Value::from(hl), Value::from(text)]);
+        // `:echoerr "oops"`
+        let arguments = vec![
+            Value::from("echoerr"),
+            Value::Array(vec![chunk(9, "oops")]),
+            Value::from(false),
+        ];
+        match parse_msg_show(arguments).unwrap() {
+            RedrawEvent::MessageShow {
+                kind,
+                content,
+                replace_last,
+            } => {
+                assert!(matches!(kind, MessageKind::EchoError));
+                assert_eq!(content, vec![(9, "oops".to_string())]);
+                assert!(!replace_last);
+            }
+            _ => unreachable!(),
+        }
+        // `:messages` after `:echomsg "first"`
+        let entry = Value::Array(vec![
+            Value::from("echomsg"),
+            Value::Array(vec![chunk(0, "first")]),
+        ]);
+        let arguments = vec![Value::Array(vec![entry])];
+        match parse_msg_history_show(arguments).unwrap() {
+            RedrawEvent::MessageHistoryShow { entries } => {
+                assert_eq!(entries.len(), 1);
+                assert!(matches!(entries[0].0, MessageKind::EchoMessage));
+                assert_eq!(entries[0].1, vec![(0, "first".to_string())]);
+            }
+            _ => unreachable!(),
+        }
+        // entering insert mode with `showmode`, ruler of `ruler`.
+        let arguments = vec![Value::Array(vec![chunk(5, "-- INSERT --")])];
+        match parse_msg_showmode(arguments).unwrap() {
+            RedrawEvent::MessageShowMode { content } => {
+                assert_eq!(content, vec![(5, "-- INSERT --".to_string())]);
+            }
+            _ => unreachable!(),
+        }
+        let arguments = vec![Value::Array(vec![chunk(0, "1,1           All")])];
+        match parse_msg_ruler(arguments).unwrap() {
+            RedrawEvent::MessageRuler { content } => assert_eq!(content.len(), 1),
+            _ => unreachable!(),
+        }
+        // typing `2d` with `showcmd`.
+        let arguments = vec![Value::Array(vec![chunk(0, "2d")])];
+        match parse_msg_showcmd(arguments).unwrap() {
+            RedrawEvent::MessageShowCommand { content } => {
+                assert_eq!(content, vec![(0, "2d".to_string())]);
+            }
+            _ => unreachable!(),
+        }
+        // partial command done with, cleared by neovim itself.
+        let arguments = vec![Value::Array(vec![])];
+        match parse_msg_showcmd(arguments).unwrap() {
+            RedrawEvent::MessageShowCommand { content } => assert!(content.is_empty()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_ext_cmdline_events() {
+        // as sent by neovim attached with ext_cmdline, typing `:ec`.
+        let chunk = Value::Array(vec![Value::from(0), Value::from("ec")]);
+        let arguments = vec![
+            Value::Array(vec![chunk]),
+            Value::from(2),
+            Value::from(":"),
+            Value::from(""),
+            Value::from(0),
+            Value::from(1),
+        ];
+        match parse_cmdline_show(arguments).unwrap() {
+            RedrawEvent::CommandLineShow {
+                content,
+                position,
+                first_character,
+                prompt,
+                indent,
+                level,
+            } => {
+                assert_eq!(content, vec![(0, "ec".to_string())]);
+                assert_eq!(position, 2);
+                assert_eq!(first_character, ":");
+                assert!(prompt.is_empty());
+                assert_eq!((indent, level), (0, 1));
+            }
+            _ => unreachable!(),
+        }
+        // caret moved by <Left>.
+        let arguments = vec![Value::from(1), Value::from(1)];
+        match parse_cmdline_pos(arguments).unwrap() {
+            RedrawEvent::CommandLinePosition { position, level } => {
+                assert_eq!((position, level), (1, 1));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_window_handle() {
+        assert_eq!(window_handle(&Value::Ext(1, vec![0x03])), Some(3));
+        assert_eq!(
+            window_handle(&Value::Ext(1, vec![0xcd, 0x03, 0xe8])),
+            Some(1000)
+        );
+        assert_eq!(
+            window_handle(&Value::Ext(1, vec![0xce, 0x00, 0x00, 0x03, 0xe9])),
+            Some(1001)
+        );
+        assert_eq!(window_handle(&Value::Ext(1, vec![0xcd, 0x03])), None);
+        assert_eq!(window_handle(&Value::Ext(1, vec![])), None);
+        assert_eq!(window_handle(&Value::from(1000)), None);
+    }
+}
@@ -1,23 +1,38 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use log::trace;
 use nvim::{Handler, Neovim, Value};
+use parking_lot::Mutex;
 
-//use crate::bridge::clipboard::{get_remote_clipboard, set_remote_clipboard};
 #[cfg(windows)]
 use crate::bridge::ui_commands::{ParallelCommand, UiCommand};
 use crate::{
-    bridge::{events::parse_redraw_event, TxWrapper},
+    app::{AppMessage, WindowCellSize, WindowOpacity},
+    bridge::{
+        clipboard::{ClipboardReply, Register, Registers},
+        events::parse_redraw_event,
+        TxWrapper,
+    },
     event_aggregator::EVENT_AGGREGATOR,
+    guioptions::GuiOptions,
+    metrics::CellSize,
     running_tracker::*,
     settings::SETTINGS,
+    vimview,
 };
 
 #[derive(Clone)]
-pub struct NeovimHandler {}
+pub struct NeovimHandler {
+    registers: Arc<Mutex<Registers>>,
+}
 
 impl NeovimHandler {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            registers: Arc::new(Mutex::new(Registers::default())),
+        }
     }
 }
 
@@ -28,26 +43,33 @@ impl Handler for NeovimHandler {
     async fn handle_request(
         &self,
         event_name: String,
-        _arguments: Vec<Value>,
+        arguments: Vec<Value>,
         _neovim: Neovim<TxWrapper>,
     ) -> Result<Value, Value> {
         trace!("Neovim request: {:?}", &event_name);
 
         match event_name.as_ref() {
-            "neovide.get_clipboard" => {
-                // let endline_type = neovim
-                //     .command_output("set ff")
-                //     .await
-                //     .ok()
-                //     .and_then(|format| {
-                //         let mut s = format.split('=');
-                //         s.next();
-                //         s.next().map(String::from)
-                //     });
-
-                // get_remote_clipboard(endline_type.as_deref())
-                //     .map_err(|_| Value::from("cannot get remote clipboard content"))
-                Err(Value::from("get remote clipboard ignored."))
+            "reovim.get_clipboard" => {
+                let register = arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .and_then(Register::parse)
+                    .ok_or_else(|| Value::from("invalid clipboard register"))?;
+                // clipboards are read by gtk in main thread.
+                let (reply, rx) = ClipboardReply::new();
+                EVENT_AGGREGATOR.send(AppMessage::ReadClipboard(register, reply));
+                let text = rx.await.ok().flatten().unwrap_or_default();
+                let (lines, regtype) = self.registers.lock().paste(register, &text);
+                let lines = lines.into_iter().map(Value::from).collect::<Vec<_>>();
+                Ok(Value::from(vec![Value::from(lines), Value::from(regtype)]))
+            }
+            "reovim.get_opacity" => {
+                let opacity = f64::from_bits(WindowOpacity.load(Ordering::Relaxed));
+                Ok(Value::from(opacity))
+            }
+            "reovim.get_cell_size" => {
+                let cell_size = *WindowCellSize.read();
+                Ok(cell_size_value(&cell_size))
             }
             _ => Ok(Value::from("rpcrequest not handled")),
         }
@@ -90,11 +112,192 @@ impl Handler for NeovimHandler {
             "neovide.unregister_right_click" => {
                 EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::UnregisterRightClick));
             }
-            "neovide.set_clipboard" => {
-                // set_remote_clipboard(arguments).ok();
-                log::error!("set remote clipboard ignored.")
+            "reovim.set_opacity" => {
+                match arguments.first().and_then(parse_opacity) {
+                    Some(opacity) => EVENT_AGGREGATOR.send(AppMessage::SetOpacity(opacity)),
+                    None => log::warn!("invalid opacity {:?}", arguments),
+                }
+            }
+            "reovim.toggle_terminal_cursor" => {
+                EVENT_AGGREGATOR.send(AppMessage::ToggleTerminalCursor);
+            }
+            "reovim.toggle_trailing_whitespace" => {
+                EVENT_AGGREGATOR.send(AppMessage::ToggleTrailingWhitespace);
+            }
+            "reovim.toggle_ligatures" => {
+                EVENT_AGGREGATOR.send(AppMessage::ToggleLigatures);
+            }
+            "reovim.set_title" => {
+                // empty one gives title back to neovim.
+                let title = arguments.first().and_then(Value::as_str).unwrap_or("");
+                EVENT_AGGREGATOR.send(AppMessage::SetTitle(title.to_string()));
             }
+            "reovim.toggle_fullscreen" => {
+                EVENT_AGGREGATOR.send(AppMessage::ToggleFullscreen);
+            }
+            "reovim.guioptions" => {
+                let guioptions = arguments.first().and_then(Value::as_str).unwrap_or("");
+                EVENT_AGGREGATOR.send(AppMessage::GuiOptions(GuiOptions::parse(guioptions)));
+            }
+            "reovim.colorcolumn" => match parse_colorcolumn(&arguments) {
+                Some((window, columns)) => {
+                    EVENT_AGGREGATOR.send(AppMessage::ColorColumns { window, columns });
+                }
+                None => log::warn!("invalid colorcolumn {:?}", arguments),
+            },
+            "reovim.terminal" => match parse_terminal(&arguments) {
+                Some((window, terminal)) => {
+                    EVENT_AGGREGATOR.send(AppMessage::Terminal { window, terminal });
+                }
+                None => log::warn!("invalid terminal {:?}", arguments),
+            },
+            "reovim.set_clipboard" => match parse_clipboard_copy(&arguments) {
+                Some((lines, regtype, register)) => {
+                    let text = self.registers.lock().copy(register, &lines, regtype);
+                    EVENT_AGGREGATOR.send(AppMessage::WriteClipboard(register, text));
+                }
+                None => log::warn!("invalid clipboard copy {:?}", arguments),
+            },
             _ => {}
         }
     }
 }
+
+/// Lines, type and register of copy to clipboard, `[lines, regtype, register]`.
+fn parse_clipboard_copy(arguments: &[Value]) -> Option<(Vec<String>, &str, Register)> {
+    match arguments {
+        [lines, regtype, register] => {
+            let lines = lines
+                .as_array()?
+                .iter()
+                .map(|line| line.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()?;
+            let register = register.as_str().and_then(Register::parse)?;
+            Some((lines, regtype.as_str()?, register))
+        }
+        _ => None,
+    }
+}
+
+/// Window and its grid columns of `colorcolumn`,
+/// `[window, colorcolumn, textwidth, textoff, leftcol]`.
+fn parse_colorcolumn(arguments: &[Value]) -> Option<(u64, Vec<usize>)> {
+    match arguments {
+        [window, colorcolumn, textwidth, textoff, leftcol] => {
+            let columns = vimview::colorcolumns(
+                colorcolumn.as_str()?,
+                textwidth.as_u64()?,
+                textoff.as_u64()?,
+                leftcol.as_u64()?,
+            );
+            Some((window.as_u64()?, columns))
+        }
+        _ => None,
+    }
+}
+
+/// Window and whether it shows a terminal buffer, `[window, terminal]`.
+fn parse_terminal(arguments: &[Value]) -> Option<(u64, bool)> {
+    match arguments {
+        [window, terminal] => Some((window.as_u64()?, terminal.as_bool()?)),
+        _ => None,
+    }
+}
+
+/// Opacity from neovim, number clamped to [0, 1].
+fn parse_opacity(value: &Value) -> Option<f64> {
+    let opacity = value
+        .as_f64()
+        .or_else(|| value.as_i64().map(|v| v as f64))?;
+    if opacity.is_nan() {
+        return None;
+    }
+    Some(opacity.clamp(0., 1.))
+}
+
+/// Cell size as dictionary of neovim, `{width, height, scale_factor, dpi}`.
+fn cell_size_value(cell_size: &CellSize) -> Value {
+    Value::Map(vec![
+        (Value::from("width"), Value::from(cell_size.width)),
+        (Value::from("height"), Value::from(cell_size.height)),
+        (Value::from("scale_factor"), Value::from(cell_size.scale_factor)),
+        (Value::from("dpi"), Value::from(cell_size.dpi)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_size_value() {
+        let cell_size = CellSize {
+            width: 9.,
+            height: 18.5,
+            scale_factor: 2.,
+            dpi: 192.,
+        };
+        let value = cell_size_value(&cell_size);
+        let map = value.as_map().unwrap();
+        let get = |key: &str| {
+            map.iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .and_then(|(_, v)| v.as_f64())
+        };
+        assert_eq!(get("width"), Some(9.));
+        assert_eq!(get("height"), Some(18.5));
+        assert_eq!(get("scale_factor"), Some(2.));
+        assert_eq!(get("dpi"), Some(192.));
+        assert_eq!(map.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_opacity() {
+        assert_eq!(parse_opacity(&Value::from(0.8)), Some(0.8));
+        assert_eq!(parse_opacity(&Value::from(1)), Some(1.));
+        assert_eq!(parse_opacity(&Value::from(0)), Some(0.));
+        assert_eq!(parse_opacity(&Value::from(1.5)), Some(1.));
+        assert_eq!(parse_opacity(&Value::from(-0.5)), Some(0.));
+        assert_eq!(parse_opacity(&Value::from(f64::NAN)), None);
+        assert_eq!(parse_opacity(&Value::from("0.5")), None);
+    }
+
+    #[test]
+    fn test_parse_colorcolumn() {
+        let arguments = [1000, 0, 78, 4, 0].map(Value::from);
+        let mut arguments = arguments.to_vec();
+        arguments[1] = Value::from("+1,100");
+        assert_eq!(parse_colorcolumn(&arguments), Some((1000, vec![82, 103])));
+        arguments[1] = Value::from(80);
+        assert_eq!(parse_colorcolumn(&arguments), None);
+        assert_eq!(parse_colorcolumn(&arguments[..4]), None);
+    }
+
+    #[test]
+    fn test_parse_terminal() {
+        let arguments = [Value::from(1000), Value::from(true)];
+        assert_eq!(parse_terminal(&arguments), Some((1000, true)));
+        // `&buftype ==# 'terminal'` is a number unless converted.
+        assert_eq!(parse_terminal(&[Value::from(1000), Value::from(1)]), None);
+        assert_eq!(parse_terminal(&arguments[..1]), None);
+    }
+
+    #[test]
+    fn test_parse_clipboard_copy() {
+        let lines = Value::from(vec![Value::from("one"), Value::from("two")]);
+        let arguments = vec![lines.clone(), Value::from("V"), Value::from("+")];
+        assert_eq!(
+            parse_clipboard_copy(&arguments),
+            Some((
+                vec!["one".to_string(), "two".to_string()],
+                "V",
+                Register::Clipboard
+            ))
+        );
+        let arguments = vec![lines.clone(), Value::from("v"), Value::from("a")];
+        assert_eq!(parse_clipboard_copy(&arguments), None);
+        let arguments = vec![Value::from("one"), Value::from("v"), Value::from("*")];
+        assert_eq!(parse_clipboard_copy(&arguments), None);
+        assert_eq!(parse_clipboard_copy(&[lines]), None);
+    }
+}
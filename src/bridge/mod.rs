@@ -1,4 +1,4 @@
-// mod clipboard;
+mod clipboard;
 mod command;
 pub mod create;
 mod events;
@@ -14,7 +14,9 @@ use nvim::UiAttachOptions;
 
 use crate::{running_tracker::*, settings::*, ConnectionMode, Opts};
 
+pub use clipboard::{ClipboardReply, Register};
 pub use command::create_nvim_command;
+pub use create::is_tcp_address;
 pub use events::*;
 use handler::NeovimHandler;
 use setup::setup_neovide_specific_state;
@@ -25,13 +27,27 @@ pub use ui_commands::{
 
 pub async fn open(opts: Opts) {
     let handler = NeovimHandler::new();
-    let (nvim, io_handler) = match opts.connection_mode() {
-        ConnectionMode::Child => {
-            create::new_child_cmd(&mut create_nvim_command(&opts), handler).await
+    let (connected, target) = match opts.connection_mode() {
+        ConnectionMode::Child => (
+            create::new_child_cmd(&mut create_nvim_command(&opts), handler).await,
+            "start neovim process".to_string(),
+        ),
+        ConnectionMode::RemoteTcp(address) => (
+            create::new_tcp(&address, handler).await,
+            format!("connect to neovim at {}", address),
+        ),
+        ConnectionMode::RemoteSocket(path) => (
+            create::new_socket(&path, handler).await,
+            format!("connect to neovim at {}", path.display()),
+        ),
+    };
+    let (nvim, io_handler) = match connected {
+        Ok(connected) => connected,
+        Err(err) => {
+            error!("Could not {}: {}", target, err);
+            std::process::exit(1);
         }
-        ConnectionMode::RemoteTcp(address) => create::new_tcp(address, handler).await,
-    }
-    .expect("Could not locate or start neovim process");
+    };
 
     // Check the neovim version to ensure its high enough
     match nvim.command_output("echo has('nvim-0.6')").await.as_deref() {
@@ -42,24 +58,19 @@ pub async fn open(opts: Opts) {
         }
     }
 
-    let mut is_remote = false;
-    #[cfg(windows)]
-    {
-        is_remote = opts.wsl;
-    }
-
-    if let ConnectionMode::RemoteTcp(_) = opts.connection_mode() {
-        is_remote = true;
-    }
-    setup_neovide_specific_state(&nvim, is_remote).await;
+    setup_neovide_specific_state(&nvim).await;
 
     let mut options = UiAttachOptions::new();
     options
         .set_rgb(true)
         .set_hlstate_external(true)
-        // .set_messages_external(true)
+        .set_messages_external(opts.ext_messages)
+        // implied by ext_messages.
+        .set_cmdline_external(opts.ext_cmdline || opts.ext_messages)
         .set_linegrid_external(true)
-        .set_multigrid_external(true);
+        .set_popupmenu_external(true)
+        .set_tabline_external(true)
+        .set_multigrid_external(!opts.no_multigrid);
 
     let (cols, rows) = opts.size.unwrap();
     // Triggers loading the user's config
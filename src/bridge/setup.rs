@@ -3,37 +3,38 @@ use nvim::{Neovim, Value};
 
 use crate::bridge::{events::*, TxWrapper};
 
-pub async fn setup_neovide_remote_clipboard(nvim: &Neovim<TxWrapper>, neovide_channel: u64) {
+/// Clipboards of display as `"*` and `"+`, works remotely too.
+pub async fn setup_clipboard(nvim: &Neovim<TxWrapper>, neovide_channel: u64) {
     // users can opt-out with
-    // vim: `let g:neovide_no_custom_clipboard = v:true`
-    // lua: `vim.g.neovide_no_custom_clipboard = true`
+    // vim: `let g:reovim_no_custom_clipboard = v:true`
+    // lua: `vim.g.reovim_no_custom_clipboard = true`
     let no_custom_clipboard = nvim
-        .get_var("neovide_no_custom_clipboard")
+        .get_var("reovim_no_custom_clipboard")
         .await
         .ok()
         .and_then(|v| v.as_bool());
     if Some(true) == no_custom_clipboard {
-        info!("Neovide working remotely but custom clipboard is disabled");
+        info!("custom clipboard is disabled");
         return;
     }
 
     // don't know how to setup lambdas with Value, so use string as command
     let custom_clipboard = r#"
         let g:clipboard = {
-          'name': 'custom',
+          'name': 'reovim',
           'copy': {
             '+': {
               lines,
-              regtype -> rpcnotify(neovide_channel, 'neovide.set_clipboard', lines, regtype, '+')
+              regtype -> rpcnotify(neovide_channel, 'reovim.set_clipboard', lines, regtype, '+')
             },
             '*': {
               lines,
-              regtype -> rpcnotify(neovide_channel, 'neovide.set_clipboard', lines, regtype, '*')
+              regtype -> rpcnotify(neovide_channel, 'reovim.set_clipboard', lines, regtype, '*')
             },
           },
           'paste': {
-            '+': {-> rpcrequest(neovide_channel, 'neovide.get_clipboard', '+')},
-            '*': {-> rpcrequest(neovide_channel, 'neovide.get_clipboard', '*')},
+            '+': {-> rpcrequest(neovide_channel, 'reovim.get_clipboard', '+')},
+            '*': {-> rpcrequest(neovide_channel, 'reovim.get_clipboard', '*')},
           },
           'cache_enabled': 0
         }
@@ -43,7 +44,107 @@ pub async fn setup_neovide_remote_clipboard(nvim: &Neovim<TxWrapper>, neovide_ch
     nvim.command(&custom_clipboard).await.ok();
 }
 
-pub async fn setup_neovide_specific_state(nvim: &Neovim<TxWrapper>, is_remote: bool) {
+pub async fn setup_reovim_functions(nvim: &Neovim<TxWrapper>, neovide_channel: u64) {
+    let functions = r#"
+        function! reovim#set_opacity(opacity)
+          call rpcnotify(neovide_channel, 'reovim.set_opacity', a:opacity)
+        endfunction
+        function! reovim#opacity()
+          return rpcrequest(neovide_channel, 'reovim.get_opacity')
+        endfunction
+        function! reovim#toggle_terminal_cursor()
+          call rpcnotify(neovide_channel, 'reovim.toggle_terminal_cursor')
+        endfunction
+        function! reovim#toggle_trailing_whitespace()
+          call rpcnotify(neovide_channel, 'reovim.toggle_trailing_whitespace')
+        endfunction
+        function! reovim#toggle_ligatures()
+          call rpcnotify(neovide_channel, 'reovim.toggle_ligatures')
+        endfunction
+        function! reovim#set_title(title)
+          call rpcnotify(neovide_channel, 'reovim.set_title', a:title)
+        endfunction
+        function! reovim#toggle_fullscreen()
+          call rpcnotify(neovide_channel, 'reovim.toggle_fullscreen')
+        endfunction
+        function! reovim#cell_size()
+          return rpcrequest(neovide_channel, 'reovim.get_cell_size')
+        endfunction
+        function! ReovimMatchTrailingWhitespace(enabled)
+          " special color only, a highlight id of its own without changing look.
+          highlight default ReovimTrailingWhitespace guisp=#ff5050
+          for win in nvim_list_wins()
+            if nvim_win_get_config(win).relative != ''
+              continue
+            endif
+            let matches = filter(getmatches(win), {_, m -> m.group ==# 'ReovimTrailingWhitespace'})
+            if a:enabled && empty(matches)
+              call matchadd('ReovimTrailingWhitespace', '\s\+$', -1, -1, {'window': win})
+            elseif !a:enabled
+              for m in matches
+                call matchdelete(m.id, win)
+              endfor
+            endif
+          endfor
+        endfunction
+        function! ReovimTrailingWhitespace(enabled)
+          call ReovimMatchTrailingWhitespace(a:enabled)
+          augroup ReovimTrailingWhitespace
+            autocmd!
+            if a:enabled
+              autocmd WinNew,WinEnter,ColorScheme * call ReovimMatchTrailingWhitespace(v:true)
+            endif
+          augroup END
+        endfunction
+        function! ReovimNotifyColorColumn(win)
+          let info = getwininfo(a:win)
+          if empty(info)
+            return
+          endif
+          let leftcol = str2nr(win_execute(a:win, 'echon winsaveview().leftcol'))
+          let textwidth = getbufvar(winbufnr(a:win), '&textwidth')
+          let colorcolumn = getwinvar(a:win, '&colorcolumn')
+          call rpcnotify(neovide_channel, 'reovim.colorcolumn',
+                \ a:win, colorcolumn, textwidth, info[0].textoff, leftcol)
+        endfunction
+        function! ReovimWatchColorColumn()
+          augroup ReovimColorColumn
+            autocmd!
+            autocmd OptionSet colorcolumn,textwidth,number,relativenumber,numberwidth,signcolumn,foldcolumn
+                  \ call ReovimNotifyColorColumn(win_getid())
+            autocmd BufWinEnter,WinEnter,WinNew * call ReovimNotifyColorColumn(win_getid())
+            autocmd WinScrolled * call ReovimNotifyColorColumn(str2nr(expand('<amatch>')))
+          augroup END
+          for win in nvim_list_wins()
+            call ReovimNotifyColorColumn(win)
+          endfor
+        endfunction
+        function! ReovimNotifyTerminal(win)
+          call rpcnotify(neovide_channel, 'reovim.terminal',
+                \ a:win, getbufvar(winbufnr(a:win), '&buftype') ==# 'terminal' ? v:true : v:false)
+        endfunction
+        augroup ReovimTerminal
+          autocmd!
+          autocmd TermOpen,BufEnter,BufWinEnter,WinNew * call ReovimNotifyTerminal(win_getid())
+        augroup END
+        function! ReovimNotifyGuiOptions()
+          call rpcnotify(neovide_channel, 'reovim.guioptions', &guioptions)
+        endfunction
+        augroup ReovimGuiOptions
+          autocmd!
+          autocmd OptionSet guioptions call ReovimNotifyGuiOptions()
+          " never triggered by OptionSet while starting up.
+          autocmd VimEnter * call ReovimNotifyGuiOptions()
+        augroup END
+        call ReovimNotifyGuiOptions()
+        "#
+    .replace("neovide_channel", &neovide_channel.to_string());
+    if let Err(err) = nvim.exec(&functions, false).await {
+        warn!("Could not define reovim functions: {}", err);
+    }
+}
+
+pub async fn setup_neovide_specific_state(nvim: &Neovim<TxWrapper>) {
     // Set variable indicating to user config that neovide is being used
     nvim.set_var("neovide", Value::Boolean(true))
         .await
@@ -124,9 +225,9 @@ pub async fn setup_neovide_specific_state(nvim: &Neovim<TxWrapper>, is_remote: b
         .await
         .ok();
 
-        if is_remote {
-            setup_neovide_remote_clipboard(nvim, neovide_channel).await;
-        }
+        setup_clipboard(nvim, neovide_channel).await;
+
+        setup_reovim_functions(nvim, neovide_channel).await;
     } else {
         warn!("Neovide could not find the correct channel id. Some functionality may be disabled.");
     }
@@ -11,10 +11,19 @@ use tokio::{
     process::ChildStdin,
 };
 
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::NamedPipeClient;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
 #[pin_project(project = TxProj)]
 pub enum TxWrapper {
     Child(#[pin] ChildStdin),
     Tcp(#[pin] WriteHalf<TcpStream>),
+    #[cfg(unix)]
+    Unix(#[pin] WriteHalf<UnixStream>),
+    #[cfg(windows)]
+    Pipe(#[pin] WriteHalf<NamedPipeClient>),
 }
 
 impl futures::io::AsyncWrite for TxWrapper {
@@ -26,6 +35,10 @@ impl futures::io::AsyncWrite for TxWrapper {
         match self.project() {
             TxProj::Child(inner) => inner.poll_write(cx, buf),
             TxProj::Tcp(inner) => inner.poll_write(cx, buf),
+            #[cfg(unix)]
+            TxProj::Unix(inner) => inner.poll_write(cx, buf),
+            #[cfg(windows)]
+            TxProj::Pipe(inner) => inner.poll_write(cx, buf),
         }
     }
 
@@ -33,6 +46,10 @@ impl futures::io::AsyncWrite for TxWrapper {
         match self.project() {
             TxProj::Child(inner) => inner.poll_flush(cx),
             TxProj::Tcp(inner) => inner.poll_flush(cx),
+            #[cfg(unix)]
+            TxProj::Unix(inner) => inner.poll_flush(cx),
+            #[cfg(windows)]
+            TxProj::Pipe(inner) => inner.poll_flush(cx),
         }
     }
 
@@ -40,6 +57,10 @@ impl futures::io::AsyncWrite for TxWrapper {
         match self.project() {
             TxProj::Child(inner) => inner.poll_shutdown(cx),
             TxProj::Tcp(inner) => inner.poll_shutdown(cx),
+            #[cfg(unix)]
+            TxProj::Unix(inner) => inner.poll_shutdown(cx),
+            #[cfg(windows)]
+            TxProj::Pipe(inner) => inner.poll_shutdown(cx),
         }
     }
 }
@@ -59,3 +80,17 @@ impl WrapTx for WriteHalf<TcpStream> {
         TxWrapper::Tcp(self)
     }
 }
+
+#[cfg(unix)]
+impl WrapTx for WriteHalf<UnixStream> {
+    fn wrap_tx(self) -> TxWrapper {
+        TxWrapper::Unix(self)
+    }
+}
+
+#[cfg(windows)]
+impl WrapTx for WriteHalf<NamedPipeClient> {
+    fn wrap_tx(self) -> TxWrapper {
+        TxWrapper::Pipe(self)
+    }
+}
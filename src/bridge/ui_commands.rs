@@ -1,7 +1,8 @@
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
-use nvim::{call_args, rpc::model::IntoVal, Neovim};
+use nvim::{call_args, rpc::model::IntoVal, Neovim, Value};
 use tokio::sync::mpsc::unbounded_channel;
 
 #[cfg(windows)]
@@ -107,6 +108,8 @@ impl std::fmt::Display for MouseButton {
 #[derive(Clone, Debug)]
 pub enum SerialCommand {
     Keyboard(String),
+    /// Text inserted literally by `nvim_paste`, no autoindent or abbreviations on it.
+    Paste(String),
     MouseButton {
         action: MouseAction,
         button: MouseButton,
@@ -135,6 +138,22 @@ impl SerialCommand {
                 log::trace!("Keyboard Input Sent: {}", input_command);
                 nvim.input(&input_command).await.expect("Input failed");
             }
+            SerialCommand::Paste(text) => {
+                log::trace!("Paste {} bytes", text.len());
+                for (data, phase) in paste_chunks(&text, PASTE_CHUNK) {
+                    match nvim.paste(data, true, phase).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            log::info!("paste cancelled by neovim.");
+                            break;
+                        }
+                        Err(err) => {
+                            log::warn!("paste failed: {}", err);
+                            break;
+                        }
+                    }
+                }
+            }
             SerialCommand::MouseButton {
                 action,
                 button,
@@ -213,14 +232,30 @@ impl SerialCommand {
 #[derive(Debug, Clone)]
 pub enum ParallelCommand {
     Quit,
+    /// `:qa` lets neovim run autocmds, force quit if still running after `timeout`,
+    /// or ask to save modified buffers by `:confirm qa` if `confirm`.
+    QuitGracefully {
+        timeout: Option<Duration>,
+        confirm: bool,
+    },
     Resize {
         width: u64,
         height: u64,
     },
-    FileDrop(String),
+    /// Edit dropped files in window of handle, current window if 0.
+    FileDrop {
+        paths: Vec<String>,
+        window: u64,
+    },
     FocusLost,
     FocusGained,
     DisplayAvailableFonts(Vec<String>),
+    /// Notify `colorcolumn` of windows whenever it may change, see `reovim.colorcolumn`.
+    WatchColorColumn,
+    /// Switch to tab page of handle from `tabline_update`.
+    SwitchTab(Value),
+    /// Match trailing whitespace of all windows to `ReovimTrailingWhitespace`, or clear it.
+    TrailingWhitespace(bool),
     #[cfg(windows)]
     RegisterRightClick,
     #[cfg(windows)]
@@ -233,6 +268,19 @@ impl ParallelCommand {
             ParallelCommand::Quit => {
                 nvim.command("qa!").await.ok();
             }
+            ParallelCommand::QuitGracefully { timeout, confirm } => {
+                for (delay, command) in quit_sequence(timeout, confirm) {
+                    tokio::time::sleep(delay).await;
+                    log::info!("quit neovim by `{}`", command);
+                    // never wait for answer, neovim may block on prompts.
+                    let nvim = nvim.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = nvim.command(command).await {
+                            log::warn!("`{}` failed: {}", command, err);
+                        }
+                    });
+                }
+            }
             ParallelCommand::Resize { width, height } => nvim
                 .ui_try_resize(width.max(10) as i64, height.max(3) as i64)
                 .await
@@ -245,8 +293,21 @@ impl ParallelCommand {
                 .command("if exists('#FocusGained') | doautocmd <nomodeline> FocusGained | endif")
                 .await
                 .expect("Focus Gained Failed"),
-            ParallelCommand::FileDrop(path) => {
-                nvim.command(format!("e {}", path).as_str()).await.ok();
+            ParallelCommand::FileDrop { paths, window } => {
+                const EDIT: &str = r#"
+                    local win, paths = ...
+                    if win > 0 and vim.api.nvim_win_is_valid(win) then
+                        vim.api.nvim_set_current_win(win)
+                    end
+                    for _, path in ipairs(paths) do
+                        vim.cmd('edit ' .. vim.fn.fnameescape(path))
+                    end
+                "#;
+                let paths = paths.into_iter().map(Value::from).collect();
+                let args = vec![window.into(), Value::Array(paths)];
+                if let Err(err) = nvim.exec_lua(EDIT, args).await {
+                    log::warn!("edit dropped files failed: {}", err);
+                }
             }
             ParallelCommand::DisplayAvailableFonts(fonts) => {
                 let mut content: Vec<String> = vec![
@@ -261,6 +322,10 @@ impl ParallelCommand {
                     "",
                     "    :set guifont=Cascadia\\ Code\\ PL:h12",
                     "",
+                    "The size is in points, append px for a height in pixels:",
+                    "",
+                    "    :set guifont=Cascadia\\ Code\\ PL:h16px",
+                    "",
                     "You may specify multiple fonts for fallback purposes separated by commas like so:",
                     "",
                     "    :set guifont=Cascadia\\ Code\\ PL,Delugia\\ Nerd\\ Font:h12",
@@ -291,6 +356,23 @@ impl ParallelCommand {
                 .await
                 .ok();
             }
+            ParallelCommand::WatchColorColumn => {
+                if let Err(err) = nvim.call_function("ReovimWatchColorColumn", vec![]).await {
+                    log::warn!("watch colorcolumn failed: {}", err);
+                }
+            }
+            ParallelCommand::TrailingWhitespace(enabled) => {
+                let args = vec![Value::from(enabled)];
+                if let Err(err) = nvim.call_function("ReovimTrailingWhitespace", args).await {
+                    log::warn!("match trailing whitespace failed: {}", err);
+                }
+            }
+            ParallelCommand::SwitchTab(tab) => {
+                let tabpage = nvim::Tabpage::new(tab, nvim.clone());
+                if let Err(err) = nvim.set_current_tabpage(&tabpage).await {
+                    log::warn!("switch tab page failed: {}", err);
+                }
+            }
             #[cfg(windows)]
             ParallelCommand::RegisterRightClick => {
                 if unregister_rightclick() {
@@ -407,3 +489,79 @@ pub fn start_ui_command_handler(nvim: Arc<Neovim<TxWrapper>>) {
         */
     });
 }
+
+/// Bytes of text sent by each `nvim_paste` call of a large paste.
+const PASTE_CHUNK: usize = 64 * 1024;
+
+/// Chunks of `text` by about `size` bytes at char boundaries with phase of `nvim_paste`,
+/// `-1` for a single chunk, otherwise `1` starts, `2` continues and `3` ends the stream.
+fn paste_chunks(text: &str, size: usize) -> Vec<(&str, i64)> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.len() > size {
+        let mut end = size.max(1);
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    let last = chunks.len() - 1;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(idx, chunk)| match idx {
+            _ if last == 0 => (chunk, -1),
+            0 => (chunk, 1),
+            idx if idx == last => (chunk, 3),
+            _ => (chunk, 2),
+        })
+        .collect()
+}
+
+/// Commands to quit neovim gracefully, each sent after its delay.
+/// Nothing is sent after neovim exited, the executor stops with it.
+fn quit_sequence(timeout: Option<Duration>, confirm: bool) -> Vec<(Duration, &'static str)> {
+    let mut sequence = vec![(Duration::ZERO, "qa")];
+    if let Some(timeout) = timeout {
+        // `confirm qa` leaves modified buffers to the user, window stays open if cancelled.
+        let command = if confirm { "confirm qa" } else { "qa!" };
+        sequence.push((timeout, command));
+    }
+    sequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quit_sequence() {
+        assert_eq!(quit_sequence(None, false), vec![(Duration::ZERO, "qa")]);
+        assert_eq!(quit_sequence(None, true), vec![(Duration::ZERO, "qa")]);
+        let timeout = Duration::from_millis(3000);
+        assert_eq!(
+            quit_sequence(Some(timeout), false),
+            vec![(Duration::ZERO, "qa"), (timeout, "qa!")]
+        );
+        assert_eq!(
+            quit_sequence(Some(timeout), true),
+            vec![(Duration::ZERO, "qa"), (timeout, "confirm qa")]
+        );
+    }
+
+    #[test]
+    fn test_paste_chunks() {
+        assert_eq!(paste_chunks("a\n  b", 16), vec![("a\n  b", -1)]);
+        assert_eq!(paste_chunks("", 16), vec![("", -1)]);
+        assert_eq!(
+            paste_chunks("abcdefg", 3),
+            vec![("abc", 1), ("def", 2), ("g", 3)]
+        );
+        assert_eq!(paste_chunks("abcdef", 3), vec![("abc", 1), ("def", 3)]);
+        // never splits a char.
+        assert_eq!(paste_chunks("a中b", 2), vec![("a中", 1), ("b", 3)]);
+    }
+}
@@ -1,16 +1,25 @@
-use std::{cell::Cell, collections::LinkedList, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::LinkedList,
+    rc::Rc,
+};
 
-use gtk::prelude::*;
+use gtk::{gdk, prelude::*};
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use relm4::{
     factory::{FactoryPrototype, FactoryVec},
     ComponentUpdate, Model, Sender, WidgetPlus, Widgets,
 };
+use rustc_hash::FxHashMap;
 
 use crate::{
     app::{AppMessage, AppModel},
-    bridge::{MessageKind, StyledContent},
+    bridge::{MessageKind, ParallelCommand, PopupMenuItem, StyledContent, Tab, UiCommand},
+    event_aggregator::EVENT_AGGREGATOR,
+    metrics::Metrics,
+    popupmenu::PopupMenu,
+    tabline::{self, Tabline},
     vimview::{self, HighlightDefinitions},
 };
 
@@ -93,6 +102,10 @@ impl FactoryPrototype for VimMessage {
                         tag.set_underline(pango::Underline::Error);
                         tag.set_underline_rgba(Some(&style.special(&default_colors)));
                     }
+                    if style.underdouble {
+                        tag.set_underline(pango::Underline::Double);
+                        tag.set_underline_rgba(Some(&style.special(&default_colors)));
+                    }
                     if style.strikethrough {
                         tag.set_strikethrough(true);
                         tag.set_strikethrough_rgba(Some(&style.special(&default_colors)))
@@ -207,9 +220,14 @@ struct VimCommandPrompt {
     changed: Cell<bool>,
     name: String,
     text: String,
+    // bytes of first character or prompt and indent before content.
+    offset: usize,
+    // byte position of caret in content.
     position: u64,
     attrs: pango::AttrList,
     widget: OnceCell<gtk::Popover>,
+    label: OnceCell<gtk::Label>,
+    caret: OnceCell<gtk::DrawingArea>,
 }
 
 impl VimCommandPrompt {
@@ -218,11 +236,39 @@ impl VimCommandPrompt {
             level,
             changed: true.into(),
             name: name.to_string(),
+            offset: 0,
             position: 0,
             text: String::new(),
             attrs: pango::AttrList::new(),
             widget: OnceCell::new(),
+            label: OnceCell::new(),
+            caret: OnceCell::new(),
+        }
+    }
+}
+
+/// Lines of command block above the prompt, e.g. `:function` typed interactively.
+#[derive(Default)]
+struct VimCommandBlock {
+    changed: Cell<bool>,
+    text: String,
+    attrs: pango::AttrList,
+    widget: OnceCell<gtk::Popover>,
+}
+
+impl VimCommandBlock {
+    fn append(&mut self, hldefs: &HighlightDefinitions, line: StyledContent) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
         }
+        push_styled_content(&mut self.text, &self.attrs, hldefs, line);
+        self.changed.set(true);
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+        self.attrs = pango::AttrList::new();
+        self.changed.set(true);
     }
 }
 
@@ -230,6 +276,12 @@ impl VimCommandPrompt {
 pub enum VimCmdEvent {
     Show(StyledContent, u64, String, String, u64, u64),
     Hide,
+    /// position of caret and level of prompt.
+    Position(u64, u64),
+    /// blinking css of cursor in cmdline mode, caret blinks along.
+    Blink(Option<String>),
+    BlockShow(Vec<StyledContent>),
+    BlockAppend(StyledContent),
     BlockHide,
 }
 
@@ -238,6 +290,10 @@ pub struct VimCmdPrompts {
     hldefs: Rc<RwLock<HighlightDefinitions>>,
     prompts: LinkedList<VimCommandPrompt>,
     #[derivative(Debug = "ignore")]
+    block: VimCommandBlock,
+    #[derivative(Debug = "ignore")]
+    blinking: gtk::CssProvider,
+    #[derivative(Debug = "ignore")]
     removed: Cell<Option<Vec<gtk::Popover>>>,
 }
 
@@ -253,6 +309,8 @@ impl ComponentUpdate<AppModel> for VimCmdPrompts {
             hldefs: parent_model.hldefs.clone(),
             removed: Cell::new(None),
             prompts: LinkedList::new(),
+            block: VimCommandBlock::default(),
+            blinking: gtk::CssProvider::new(),
         }
     }
 
@@ -263,10 +321,30 @@ impl ComponentUpdate<AppModel> for VimCmdPrompts {
         _sender: Sender<VimCmdEvent>,
         _parent_sender: Sender<AppMessage>,
     ) {
-        const U16MAX: f32 = u16::MAX as f32;
         match event {
+            VimCmdEvent::Position(position, level) => {
+                if let Some(prompt) = self.prompts.iter_mut().find(|p| p.level == level) {
+                    prompt.position = position;
+                    prompt.changed.set(true);
+                }
+            }
+            VimCmdEvent::Blink(blinking) => {
+                // carets share the provider, all of them follow.
+                self.blinking
+                    .load_from_data(blinking.unwrap_or_default().as_bytes());
+            }
+            VimCmdEvent::BlockShow(lines) => {
+                self.block.clear();
+                let hldefs = self.hldefs.read();
+                for line in lines {
+                    self.block.append(&hldefs, line);
+                }
+            }
+            VimCmdEvent::BlockAppend(line) => {
+                self.block.append(&self.hldefs.read(), line);
+            }
             VimCmdEvent::BlockHide => {
-                todo!()
+                self.block.clear();
             }
             VimCmdEvent::Hide => {
                 self.prompts
@@ -329,76 +407,16 @@ impl ComponentUpdate<AppModel> for VimCmdPrompts {
                 }
                 let prompt = prompt_opt.unwrap();
 
+                prompt.offset = text.len();
                 prompt.position = position;
+                prompt.attrs = pango::AttrList::new();
 
-                let hldefs = self.hldefs.read();
-                let defaults = hldefs.defaults().unwrap();
-                let attrs = &prompt.attrs;
-                for (hldef, s) in styled_content {
-                    let start_index = text.len() as u32;
-                    text.push_str(&s);
-                    let end_index = text.len() as u32;
-                    let style = hldefs.get(hldef).unwrap();
-
-                    if style.italic {
-                        let mut attr = pango::AttrInt::new_style(pango::Style::Italic);
-                        attr.set_start_index(start_index);
-                        attr.set_end_index(end_index);
-                        attrs.insert(attr);
-                    }
-                    if style.bold {
-                        let mut attr = pango::AttrInt::new_weight(pango::Weight::Semibold);
-                        attr.set_start_index(start_index);
-                        attr.set_end_index(end_index);
-                        attrs.insert(attr);
-                    }
-                    if style.strikethrough {
-                        let mut attr = pango::AttrInt::new_strikethrough(true);
-                        attr.set_start_index(start_index);
-                        attr.set_end_index(end_index);
-                        attrs.insert(attr);
-                    }
-                    if style.underline {
-                        let mut attr = pango::AttrInt::new_underline(pango::Underline::Single);
-                        attr.set_start_index(start_index);
-                        attr.set_end_index(end_index);
-                        attrs.insert(attr);
-                    }
-                    if style.undercurl {
-                        let mut attr = pango::AttrInt::new_underline(pango::Underline::Error);
-                        attr.set_start_index(start_index);
-                        attr.set_end_index(end_index);
-                        attrs.insert(attr);
-                    }
-                    let fg = style.foreground(defaults);
-                    let mut attr = pango::AttrColor::new_foreground(
-                        (fg.red() * U16MAX).round() as u16,
-                        (fg.green() * U16MAX).round() as u16,
-                        (fg.blue() * U16MAX).round() as u16,
-                    );
-                    attr.set_start_index(start_index);
-                    attr.set_end_index(end_index);
-                    attrs.insert(attr);
-                    if let Some(bg) = style.background().or(defaults.background) {
-                        let mut attr = pango::AttrColor::new_background(
-                            (bg.red() * U16MAX).round() as u16,
-                            (bg.green() * U16MAX).round() as u16,
-                            (bg.blue() * U16MAX).round() as u16,
-                        );
-                        attr.set_start_index(start_index);
-                        attr.set_end_index(end_index);
-                        attrs.insert(attr);
-                    }
-                    let special = style.special(defaults);
-                    let mut attr = pango::AttrColor::new_underline_color(
-                        (special.red() * U16MAX).round() as u16,
-                        (special.green() * U16MAX).round() as u16,
-                        (special.blue() * U16MAX).round() as u16,
-                    );
-                    attr.set_start_index(start_index);
-                    attr.set_end_index(end_index);
-                    attrs.insert(attr);
-                }
+                push_styled_content(
+                    &mut text,
+                    &prompt.attrs,
+                    &self.hldefs.read(),
+                    styled_content,
+                );
                 prompt.text = text;
                 // label.inline_css(b"border: 0 solid #e5e7eb");
             }
@@ -406,16 +424,110 @@ impl ComponentUpdate<AppModel> for VimCmdPrompts {
     }
 }
 
+/// Byte index of caret in `text`, moved back to a character boundary.
+fn caret_index(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|index| text.is_char_boundary(*index))
+        .unwrap_or(0)
+}
+
+/// Append `styled_content` to `text`, highlighted by `attrs` of its highlights.
+pub fn push_styled_content(
+    text: &mut String,
+    attrs: &pango::AttrList,
+    hldefs: &HighlightDefinitions,
+    styled_content: StyledContent,
+) {
+    const U16MAX: f32 = u16::MAX as f32;
+    let defaults = hldefs.defaults().unwrap();
+    for (hldef, s) in styled_content {
+        let start_index = text.len() as u32;
+        text.push_str(&s);
+        let end_index = text.len() as u32;
+        let style = hldefs.get(hldef).unwrap();
+
+        if style.italic {
+            let mut attr = pango::AttrInt::new_style(pango::Style::Italic);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        if style.bold {
+            let mut attr = pango::AttrInt::new_weight(pango::Weight::Semibold);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        if style.strikethrough {
+            let mut attr = pango::AttrInt::new_strikethrough(true);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        if style.underline {
+            let mut attr = pango::AttrInt::new_underline(pango::Underline::Single);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        if style.undercurl {
+            let mut attr = pango::AttrInt::new_underline(pango::Underline::Error);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        if style.underdouble {
+            let mut attr = pango::AttrInt::new_underline(pango::Underline::Double);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        let fg = style.foreground(defaults);
+        let mut attr = pango::AttrColor::new_foreground(
+            (fg.red() * U16MAX).round() as u16,
+            (fg.green() * U16MAX).round() as u16,
+            (fg.blue() * U16MAX).round() as u16,
+        );
+        attr.set_start_index(start_index);
+        attr.set_end_index(end_index);
+        attrs.insert(attr);
+        if let Some(bg) = style.resolved_background(defaults).or(defaults.background) {
+            let mut attr = pango::AttrColor::new_background(
+                (bg.red() * U16MAX).round() as u16,
+                (bg.green() * U16MAX).round() as u16,
+                (bg.blue() * U16MAX).round() as u16,
+            );
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        let special = style.special(defaults);
+        let mut attr = pango::AttrColor::new_underline_color(
+            (special.red() * U16MAX).round() as u16,
+            (special.green() * U16MAX).round() as u16,
+            (special.blue() * U16MAX).round() as u16,
+        );
+        attr.set_start_index(start_index);
+        attr.set_end_index(end_index);
+        attrs.insert(attr);
+    }
+}
+
 #[relm_macros::widget(pub)]
 impl Widgets<VimCmdPrompts, AppModel> for VimCmdPromptWidgets {
     view! {
         view = gtk::Fixed {
             set_visible: false,
+            set_can_target: false,
             inline_css: b"border: 0 solid #e5e7eb;",
         }
     }
 
     fn pre_view() {
+        // popovers of prompts and block are shown by their parent only.
+        let visible = !model.prompts.is_empty() || !model.block.text.is_empty();
+        self.view.set_visible(visible);
         if let Some(removed) = model.removed.take() {
             for popover in removed.into_iter() {
                 self.view.remove(&popover);
@@ -479,29 +591,782 @@ impl Widgets<VimCmdPrompts, AppModel> for VimCmdPromptWidgets {
                 });
             }
         }
+        let block = &model.block;
+        if block.changed.replace(false) {
+            let popover = block.widget.get_or_init(|| {
+                let popover = gtk::Popover::builder()
+                    .autohide(false)
+                    .has_arrow(false)
+                    .valign(gtk::Align::Center)
+                    .halign(gtk::Align::Start)
+                    .position(gtk::PositionType::Top)
+                    .child(
+                        &gtk::Label::builder()
+                            .selectable(false)
+                            .halign(gtk::Align::Start)
+                            .build(),
+                    )
+                    .build();
+                popover.set_parent(&self.view);
+                popover
+            });
+            let child = popover.child().unwrap();
+            let label = child.downcast_ref::<gtk::Label>().unwrap();
+            label.set_text(&block.text);
+            label.set_attributes(Some(&block.attrs));
+            popover.set_visible(!block.text.is_empty());
+        }
         let mut iter = model.prompts.iter().peekable();
         while let Some(prompt) = iter.next() {
             unsafe { prompt.widget.get_unchecked() }
                 .insert_before(&self.view, iter.peek().and_then(|p| p.widget.get()));
             if prompt.changed.replace(false) {
                 let popover = unsafe { prompt.widget.get_unchecked() };
+                let label = prompt.label.get_or_init(|| {
+                    gtk::Label::builder()
+                        .selectable(false)
+                        .valign(gtk::Align::Center)
+                        .halign(gtk::Align::Start)
+                        .visible(true)
+                        .hexpand(true)
+                        .vexpand(true)
+                        .build()
+                });
+                let caret = prompt.caret.get_or_init(|| {
+                    let caret = gtk::DrawingArea::builder()
+                        .halign(gtk::Align::Start)
+                        .can_target(false)
+                        .width_request(2)
+                        .css_classes(vec!["vim-cmdline-caret".into(), "blink".into()])
+                        .build();
+                    caret.set_draw_func(
+                        gtk::glib::clone!(@strong model.hldefs as hldefs => move |_, cr, w, h| {
+                            let hldefs = hldefs.read();
+                            if let Some(fg) = hldefs.defaults().and_then(|c| c.foreground) {
+                                cr.rectangle(0., 0., w.into(), h.into());
+                                cr.set_source_rgba(
+                                    fg.red() as _,
+                                    fg.green() as _,
+                                    fg.blue() as _,
+                                    fg.alpha() as _,
+                                );
+                                cr.fill().ok();
+                            }
+                        }),
+                    );
+                    caret
+                        .style_context()
+                        .add_provider(&model.blinking, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+                    caret
+                });
                 if popover.child().is_none() {
-                    popover.set_child(Some(
-                        &gtk::Label::builder()
-                            .selectable(false)
-                            .valign(gtk::Align::Center)
-                            .halign(gtk::Align::Start)
-                            .visible(true)
-                            .hexpand(true)
-                            .vexpand(true)
-                            .build(),
-                    ));
+                    let overlay = gtk::Overlay::new();
+                    overlay.set_child(Some(label));
+                    overlay.add_overlay(caret);
+                    popover.set_child(Some(&overlay));
                 }
-                let child = popover.child().unwrap();
-                let label = child.downcast_ref::<gtk::Label>().unwrap();
                 label.set_text(&prompt.text);
                 label.set_attributes(Some(&prompt.attrs));
+                // caret sits after text laid out before it.
+                let index = caret_index(&prompt.text, prompt.offset + prompt.position as usize);
+                let layout = pango::Layout::new(&label.pango_context());
+                layout.set_text(&prompt.text[..index]);
+                layout.set_attributes(Some(&prompt.attrs));
+                let (x, _) = layout.pixel_size();
+                caret.set_margin_start(x);
+                caret.queue_draw();
+            }
+        }
+    }
+}
+
+/// Highlight groups of popup menu item, selected item, scrollbar and its thumb.
+const POPUPMENU_HLGROUPS: [&str; 4] = ["Pmenu", "PmenuSel", "PmenuSbar", "PmenuThumb"];
+
+#[derive(Debug)]
+pub enum VimPopupMenuEvent {
+    /// items, selected item, row and column of the completed word in window.
+    Show(Vec<PopupMenuItem>, Option<usize>, usize, usize),
+    Select(Option<usize>),
+    Hide,
+    /// `pumblend`, 0 opaque to 100 fully transparent.
+    Blend(u64),
+}
+
+/// States shared with draw function of popup menu.
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+struct PopupMenuRenderer {
+    menu: Rc<RefCell<PopupMenu>>,
+    // rows shown and widths of word, kind, menu and info columns in pixel.
+    layout: Rc<Cell<(usize, [f64; 4])>>,
+    #[derivative(Debug = "ignore")]
+    hldefs: Rc<RwLock<HighlightDefinitions>>,
+    hlgroups: Rc<RwLock<FxHashMap<String, u64>>>,
+    metrics: Rc<Cell<Metrics>>,
+    #[derivative(Debug = "ignore")]
+    pctx: Rc<pango::Context>,
+}
+
+impl PopupMenuRenderer {
+    fn columns(item: &PopupMenuItem) -> [&str; 4] {
+        // first line of info only, it could be full documentation.
+        let info = item.info.lines().next().unwrap_or("");
+        [&item.word, &item.kind, &item.menu, info]
+    }
+
+    /// Widest text of each column in pixel.
+    fn measure(&self, menu: &PopupMenu) -> [f64; 4] {
+        let layout = pango::Layout::new(&self.pctx);
+        let mut widths = [0f64; 4];
+        for item in menu.items() {
+            for (width, text) in widths.iter_mut().zip(Self::columns(item)) {
+                if text.is_empty() {
+                    continue;
+                }
+                layout.set_text(text);
+                *width = width.max(layout.pixel_size().0 as f64);
+            }
+        }
+        widths
+    }
+
+    /// Width of menu in pixel, columns separated by one cell.
+    fn width(widths: &[f64; 4], cell_width: f64, scrollbar: bool) -> f64 {
+        let scrollbar = if scrollbar { cell_width / 2. } else { 0. };
+        widths
+            .iter()
+            .filter(|width| **width > 0.)
+            .fold(cell_width + scrollbar, |total, width| {
+                total + width + cell_width
+            })
+    }
+
+    /// Foreground and background of highlight group `name`.
+    fn colors(
+        hldefs: &HighlightDefinitions,
+        hlgroups: &FxHashMap<String, u64>,
+        name: &str,
+    ) -> (Option<gdk::RGBA>, Option<gdk::RGBA>) {
+        let defaults = hldefs.defaults();
+        let style = hlgroups.get(name).and_then(|id| hldefs.get(*id));
+        let foreground = match (style, defaults) {
+            (Some(style), Some(defaults)) => Some(style.foreground(defaults)),
+            (_, defaults) => defaults.and_then(|defaults| defaults.foreground),
+        };
+        let background = match (style, defaults) {
+            (Some(style), Some(defaults)) => style.resolved_background(defaults),
+            _ => None,
+        }
+        .or_else(|| defaults.and_then(|defaults| defaults.background));
+        (foreground, background)
+    }
+
+    fn draw(&self, cr: &cairo::Context, width: f64) {
+        let set_source = |color: &gdk::RGBA| {
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                color.alpha() as f64,
+            )
+        };
+        let metrics = self.metrics.get();
+        let (cell_width, cell_height) = (metrics.width(), metrics.height());
+        let (rows, widths) = self.layout.get();
+        let menu = self.menu.borrow();
+        let hldefs = self.hldefs.read();
+        let hlgroups = self.hlgroups.read();
+        let [normal, selected, scrollbar, thumb] =
+            POPUPMENU_HLGROUPS.map(|name| Self::colors(&hldefs, &hlgroups, name));
+
+        let layout = pango::Layout::new(&self.pctx);
+        for (row, (index, item)) in menu.visible(rows).enumerate() {
+            let (foreground, background) = if menu.selected() == Some(index) {
+                selected
+            } else {
+                normal
+            };
+            let y = row as f64 * cell_height;
+            if let Some(background) = background {
+                set_source(&background);
+                cr.rectangle(0., y, width, cell_height);
+                cr.fill().ok();
+            }
+            if let Some(foreground) = foreground {
+                set_source(&foreground);
+            }
+            let mut x = cell_width;
+            for (text, column) in Self::columns(item).into_iter().zip(widths) {
+                if column <= 0. {
+                    continue;
+                }
+                layout.set_text(text);
+                let (_, height) = layout.pixel_size();
+                cr.move_to(x, y + (cell_height - height as f64) / 2.);
+                pangocairo::update_layout(cr, &layout);
+                pangocairo::show_layout(cr, &layout);
+                x += column + cell_width;
+            }
+        }
+
+        // scrollbar on the right, thumb marks items shown.
+        let count = menu.items().len();
+        if rows > 0 && count > rows {
+            let x = width - cell_width / 2.;
+            let height = rows as f64 * cell_height;
+            if let Some(background) = scrollbar.1 {
+                set_source(&background);
+                cr.rectangle(x, 0., cell_width / 2., height);
+                cr.fill().ok();
+            }
+            if let Some(background) = thumb.1 {
+                let top = menu.offset() as f64 / count as f64 * height;
+                let length = (rows as f64 / count as f64 * height).max(cell_height / 2.);
+                set_source(&background);
+                cr.rectangle(x, top, cell_width / 2., length);
+                cr.fill().ok();
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct VimPopupMenu {
+    visible: bool,
+    opacity: f64,
+    renderer: PopupMenuRenderer,
+}
+
+impl Model for VimPopupMenu {
+    type Msg = VimPopupMenuEvent;
+    type Widgets = VimPopupMenuWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<AppModel> for VimPopupMenu {
+    fn init_model(parent_model: &AppModel) -> Self {
+        VimPopupMenu {
+            visible: false,
+            opacity: 1.,
+            renderer: PopupMenuRenderer {
+                menu: Rc::new(RefCell::new(PopupMenu::default())),
+                layout: Rc::new(Cell::new((0, [0.; 4]))),
+                hldefs: parent_model.hldefs.clone(),
+                hlgroups: parent_model.hlgroups.clone(),
+                metrics: parent_model.metrics.clone(),
+                pctx: parent_model.pctx.clone(),
+            },
+        }
+    }
+
+    fn update(
+        &mut self,
+        event: VimPopupMenuEvent,
+        _components: &(),
+        _sender: Sender<VimPopupMenuEvent>,
+        _parent_sender: Sender<AppMessage>,
+    ) {
+        let mut menu = self.renderer.menu.borrow_mut();
+        match event {
+            VimPopupMenuEvent::Show(items, selected, row, col) => {
+                log::debug!("popup menu of {} items at {}x{}", items.len(), col, row);
+                menu.show(items, selected, row, col);
+                self.visible = true;
+            }
+            VimPopupMenuEvent::Select(selected) => {
+                menu.select(selected);
+            }
+            VimPopupMenuEvent::Hide => {
+                menu.hide();
+                self.visible = false;
+            }
+            VimPopupMenuEvent::Blend(blend) => {
+                self.opacity = (100 - blend.min(100)) as f64 / 100.;
+            }
+        }
+    }
+}
+
+#[relm_macros::widget(pub)]
+impl Widgets<VimPopupMenu, AppModel> for VimPopupMenuWidgets {
+    view! {
+        view = gtk::DrawingArea {
+            set_widget_name: "vim-popupmenu",
+            set_visible: watch!(model.visible),
+            set_opacity: watch!(model.opacity),
+            set_halign: gtk::Align::Start,
+            set_valign: gtk::Align::Start,
+            set_can_target: false,
+            set_focus_on_click: false,
+            set_draw_func[renderer = model.renderer.clone()] => move |_da, cr, width, _height| {
+                renderer.draw(cr, width as f64);
+            }
+        }
+    }
+
+    fn pre_view() {
+        if model.visible {
+            let renderer = &model.renderer;
+            let metrics = renderer.metrics.get();
+            // clamp to window, the overlay covers all of it.
+            let (total, window_width) = self.view.parent().map_or((0, 0.), |parent| {
+                (
+                    (parent.height() as f64 / metrics.height()) as usize,
+                    parent.width() as f64,
+                )
+            });
+            let mut menu = renderer.menu.borrow_mut();
+            let placement = menu.placement(total);
+            menu.scroll(placement.rows);
+            let widths = renderer.measure(&menu);
+            let scrollbar = menu.items().len() > placement.rows;
+            let width = PopupMenuRenderer::width(&widths, metrics.width(), scrollbar)
+                .min(window_width)
+                .ceil();
+            let x = (menu.col() as f64 * metrics.width())
+                .min(window_width - width)
+                .max(0.);
+            renderer.layout.set((placement.rows, widths));
+            self.view.set_margin_start(x as i32);
+            self.view
+                .set_margin_top((placement.row as f64 * metrics.height()) as i32);
+            self.view.set_size_request(
+                width as i32,
+                (placement.rows as f64 * metrics.height()).ceil() as i32,
+            );
+            self.view.queue_draw();
+        }
+    }
+}
+
+/// Highlight groups of tab, the current tab and the rest of tabline.
+const TABLINE_HLGROUPS: [&str; 3] = ["TabLine", "TabLineSel", "TabLineFill"];
+
+#[derive(Debug)]
+pub enum VimTablineEvent {
+    /// handle of the current tab page and all tab pages.
+    Update(nvim::Value, Vec<Tab>),
+    ShowTabLine(u64),
+    /// highlights or font may have changed, redraw with them.
+    Redraw,
+}
+
+/// States shared with draw function and click handler of tabline.
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+struct TablineRenderer {
+    tabline: Rc<RefCell<Tabline>>,
+    // width of each tab in pixel.
+    widths: Rc<RefCell<Vec<f64>>>,
+    #[derivative(Debug = "ignore")]
+    hldefs: Rc<RwLock<HighlightDefinitions>>,
+    hlgroups: Rc<RwLock<FxHashMap<String, u64>>>,
+    metrics: Rc<Cell<Metrics>>,
+    #[derivative(Debug = "ignore")]
+    pctx: Rc<pango::Context>,
+}
+
+impl TablineRenderer {
+    /// Tabs are padded by one cell on both sides of title.
+    fn measure(&self) -> Vec<f64> {
+        let cell_width = self.metrics.get().width();
+        let layout = pango::Layout::new(&self.pctx);
+        self.tabline
+            .borrow()
+            .tabs()
+            .iter()
+            .map(|tab| {
+                layout.set_text(tabline::title(&tab.name));
+                layout.pixel_size().0 as f64 + cell_width * 2.
+            })
+            .collect()
+    }
+
+    fn draw(&self, cr: &cairo::Context, width: f64, height: f64) {
+        let set_source = |color: &gdk::RGBA| {
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                color.alpha() as f64,
+            )
+        };
+        let cell_width = self.metrics.get().width();
+        let tabline = self.tabline.borrow();
+        let widths = self.widths.borrow();
+        let hldefs = self.hldefs.read();
+        let hlgroups = self.hlgroups.read();
+        let [normal, selected, fill] =
+            TABLINE_HLGROUPS.map(|name| PopupMenuRenderer::colors(&hldefs, &hlgroups, name));
+
+        if let Some(background) = fill.1 {
+            set_source(&background);
+            cr.rectangle(0., 0., width, height);
+            cr.fill().ok();
+        }
+        let layout = pango::Layout::new(&self.pctx);
+        let mut x = 0.;
+        for (index, (tab, tab_width)) in tabline.tabs().iter().zip(widths.iter()).enumerate() {
+            let (foreground, background) = if tabline.current() == Some(index) {
+                selected
+            } else {
+                normal
+            };
+            if let Some(background) = background {
+                set_source(&background);
+                cr.rectangle(x, 0., *tab_width, height);
+                cr.fill().ok();
+            }
+            if let Some(foreground) = foreground {
+                set_source(&foreground);
             }
+            layout.set_text(tabline::title(&tab.name));
+            let (_, text_height) = layout.pixel_size();
+            cr.move_to(x + cell_width, (height - text_height as f64) / 2.);
+            pangocairo::update_layout(cr, &layout);
+            pangocairo::show_layout(cr, &layout);
+            x += tab_width;
         }
     }
 }
+
+#[derive(Debug)]
+pub struct VimTabline {
+    showtabline: u64,
+    renderer: TablineRenderer,
+}
+
+impl VimTabline {
+    fn visible(&self) -> bool {
+        self.renderer.tabline.borrow().is_visible(self.showtabline)
+    }
+}
+
+impl Model for VimTabline {
+    type Msg = VimTablineEvent;
+    type Widgets = VimTablineWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<AppModel> for VimTabline {
+    fn init_model(parent_model: &AppModel) -> Self {
+        VimTabline {
+            // default of `showtabline`.
+            showtabline: 1,
+            renderer: TablineRenderer {
+                tabline: Rc::new(RefCell::new(Tabline::default())),
+                widths: Rc::new(RefCell::new(Vec::new())),
+                hldefs: parent_model.hldefs.clone(),
+                hlgroups: parent_model.hlgroups.clone(),
+                metrics: parent_model.metrics.clone(),
+                pctx: parent_model.pctx.clone(),
+            },
+        }
+    }
+
+    fn update(
+        &mut self,
+        event: VimTablineEvent,
+        _components: &(),
+        _sender: Sender<VimTablineEvent>,
+        _parent_sender: Sender<AppMessage>,
+    ) {
+        match event {
+            VimTablineEvent::Update(current, tabs) => {
+                log::debug!("tabline of {} tabs, current {:?}", tabs.len(), current);
+                self.renderer.tabline.borrow_mut().update(&current, tabs);
+            }
+            VimTablineEvent::ShowTabLine(showtabline) => {
+                self.showtabline = showtabline;
+            }
+            VimTablineEvent::Redraw => {}
+        }
+        // font may have changed too.
+        let widths = self.renderer.measure();
+        self.renderer.widths.replace(widths);
+    }
+}
+
+#[relm_macros::widget(pub)]
+impl Widgets<VimTabline, AppModel> for VimTablineWidgets {
+    view! {
+        view = gtk::DrawingArea {
+            set_widget_name: "vim-tabline",
+            set_visible: watch!(model.visible()),
+            set_hexpand: true,
+            set_vexpand: false,
+            set_focus_on_click: false,
+            set_draw_func[renderer = model.renderer.clone()] => move |_da, cr, width, height| {
+                renderer.draw(cr, width as f64, height as f64);
+            }
+        }
+    }
+
+    fn post_init() {
+        let click_listener = gtk::GestureClick::builder()
+            .button(gdk::BUTTON_PRIMARY)
+            .name("tabline-click-listener")
+            .build();
+        let renderer = model.renderer.clone();
+        click_listener.connect_pressed(move |_, _, x, _| {
+            let index = Tabline::tab_at(&renderer.widths.borrow(), x);
+            let tabline = renderer.tabline.borrow();
+            if let Some(tab) = index.and_then(|index| tabline.tabs().get(index)) {
+                EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::SwitchTab(
+                    tab.tab.clone(),
+                )));
+            }
+        });
+        view.add_controller(&click_listener);
+    }
+
+    fn pre_view() {
+        if model.visible() {
+            let height = model.renderer.metrics.get().height().ceil();
+            self.view.set_size_request(-1, height as i32);
+            self.view.queue_draw();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum VimMessageHistoryEvent {
+    /// entries of `:messages`, the oldest first.
+    Show(Vec<(MessageKind, StyledContent)>),
+    Hide,
+}
+
+#[derive(Debug)]
+pub struct VimMessageHistory {
+    visible: bool,
+    entries: Vec<(MessageKind, StyledContent)>,
+    changed: Cell<bool>,
+    hldefs: Rc<RwLock<HighlightDefinitions>>,
+    metrics: Rc<Cell<Metrics>>,
+    pctx: Rc<pango::Context>,
+}
+
+impl Model for VimMessageHistory {
+    type Msg = VimMessageHistoryEvent;
+    type Widgets = VimMessageHistoryWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<AppModel> for VimMessageHistory {
+    fn init_model(parent_model: &AppModel) -> Self {
+        VimMessageHistory {
+            visible: false,
+            entries: Vec::new(),
+            changed: Cell::new(false),
+            hldefs: parent_model.hldefs.clone(),
+            metrics: parent_model.metrics.clone(),
+            pctx: parent_model.pctx.clone(),
+        }
+    }
+
+    fn update(
+        &mut self,
+        event: VimMessageHistoryEvent,
+        _components: &(),
+        _sender: Sender<VimMessageHistoryEvent>,
+        parent_sender: Sender<AppMessage>,
+    ) {
+        match event {
+            VimMessageHistoryEvent::Show(entries) => {
+                log::debug!("message history of {} entries", entries.len());
+                self.entries = entries;
+                self.changed.set(true);
+                self.visible = true;
+            }
+            VimMessageHistoryEvent::Hide => {
+                self.visible = false;
+                parent_sender
+                    .send(AppMessage::MessageHistoryHidden)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[relm_macros::widget(pub)]
+impl Widgets<VimMessageHistory, AppModel> for VimMessageHistoryWidgets {
+    view! {
+        window = gtk::Window {
+            set_widget_name: "vim-message-history",
+            set_title: Some("Messages"),
+            set_modal: true,
+            set_hide_on_close: true,
+            set_default_width: 640,
+            set_default_height: 480,
+            set_transient_for: parent!(Some(&parent_widgets.main_window)),
+            set_visible: watch!(model.visible),
+            connect_close_request(sender) => move |_| {
+                sender.send(VimMessageHistoryEvent::Hide).unwrap();
+                gtk::Inhibit(false)
+            },
+            set_child: scrolled = Some(&gtk::ScrolledWindow) {
+                set_hscrollbar_policy: gtk::PolicyType::Automatic,
+                set_vscrollbar_policy: gtk::PolicyType::Automatic,
+                set_child: entries = Some(&gtk::Box) {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 0,
+                }
+            }
+        }
+    }
+
+    fn post_init() {
+        let key_controller = gtk::EventControllerKey::builder()
+            .name("message-history-key-controller")
+            .build();
+        key_controller.connect_key_pressed(move |_, keyval, _, modifier| {
+            if keyval == gdk::Key::Escape && modifier.is_empty() {
+                sender.send(VimMessageHistoryEvent::Hide).unwrap();
+                return gtk::Inhibit(true);
+            }
+            gtk::Inhibit(false)
+        });
+        window.add_controller(&key_controller);
+    }
+
+    fn pre_view() {
+        if model.changed.replace(false) {
+            while let Some(child) = self.entries.first_child() {
+                self.entries.remove(&child);
+            }
+            // rendered as notifications are, colors match.
+            for (kind, content) in model.entries.iter() {
+                let view = vimview::VimMessageView::new(
+                    *kind,
+                    content.clone(),
+                    model.hldefs.clone(),
+                    model.metrics.clone(),
+                    model.pctx.clone(),
+                );
+                view.set_halign(gtk::Align::Start);
+                self.entries.append(&view);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum VimStatusStripEvent {
+    /// `showmode` text, e.g. `-- INSERT --recording @q`.
+    Mode(StyledContent),
+    /// `ruler` text, e.g. `12,5   All`.
+    Ruler(StyledContent),
+    /// `showcmd` text, partial command or size of selection.
+    Command(StyledContent),
+}
+
+/// Mode, partial command and ruler at the bottom of window, cleared by empty content.
+#[derive(Debug)]
+pub struct VimStatusStrip {
+    mode: StyledContent,
+    command: StyledContent,
+    ruler: StyledContent,
+    hldefs: Rc<RwLock<HighlightDefinitions>>,
+    pctx: Rc<pango::Context>,
+}
+
+impl VimStatusStrip {
+    fn visible(&self) -> bool {
+        [&self.mode, &self.command, &self.ruler]
+            .iter()
+            .any(|content| content.iter().any(|(_, text)| !text.is_empty()))
+    }
+
+    /// Text and highlights of `content` in font of grids.
+    fn label(&self, content: &StyledContent) -> (String, pango::AttrList) {
+        let mut text = String::new();
+        let attrs = pango::AttrList::new();
+        if let Some(desc) = self.pctx.font_description() {
+            attrs.insert(pango::AttrFontDesc::new(&desc));
+        }
+        push_styled_content(&mut text, &attrs, &self.hldefs.read(), content.clone());
+        (text, attrs)
+    }
+}
+
+impl Model for VimStatusStrip {
+    type Msg = VimStatusStripEvent;
+    type Widgets = VimStatusStripWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<AppModel> for VimStatusStrip {
+    fn init_model(parent_model: &AppModel) -> Self {
+        VimStatusStrip {
+            mode: StyledContent::new(),
+            command: StyledContent::new(),
+            ruler: StyledContent::new(),
+            hldefs: parent_model.hldefs.clone(),
+            pctx: parent_model.pctx.clone(),
+        }
+    }
+
+    fn update(
+        &mut self,
+        event: VimStatusStripEvent,
+        _components: &(),
+        _sender: Sender<VimStatusStripEvent>,
+        _parent_sender: Sender<AppMessage>,
+    ) {
+        match event {
+            VimStatusStripEvent::Mode(mode) => {
+                log::debug!("show mode {:?}", mode);
+                self.mode = mode;
+            }
+            VimStatusStripEvent::Ruler(ruler) => {
+                log::debug!("ruler {:?}", ruler);
+                self.ruler = ruler;
+            }
+            VimStatusStripEvent::Command(command) => {
+                log::debug!("show command {:?}", command);
+                self.command = command;
+            }
+        }
+    }
+}
+
+#[relm_macros::widget(pub)]
+impl Widgets<VimStatusStrip, AppModel> for VimStatusStripWidgets {
+    view! {
+        view = gtk::Box {
+            set_widget_name: "vim-status-strip",
+            set_orientation: gtk::Orientation::Horizontal,
+            set_visible: watch!(model.visible()),
+            set_hexpand: true,
+            set_vexpand: false,
+            set_focus_on_click: false,
+            set_spacing: 16,
+            append: mode = &gtk::Label {
+                set_hexpand: true,
+                set_halign: gtk::Align::Start,
+                set_selectable: false,
+            },
+            append: command = &gtk::Label {
+                set_halign: gtk::Align::End,
+                set_selectable: false,
+            },
+            append: ruler = &gtk::Label {
+                set_halign: gtk::Align::End,
+                set_selectable: false,
+            },
+        }
+    }
+
+    fn pre_view() {
+        let (text, attrs) = model.label(&model.mode);
+        self.mode.set_text(&text);
+        self.mode.set_attributes(Some(&attrs));
+        let (text, attrs) = model.label(&model.command);
+        self.command.set_text(&text);
+        self.command.set_attributes(Some(&attrs));
+        let (text, attrs) = model.label(&model.ruler);
+        self.ruler.set_text(&text);
+        self.ruler.set_attributes(Some(&attrs));
+    }
+}
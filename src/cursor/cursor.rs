@@ -1,4 +1,4 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use parking_lot::RwLock;
@@ -26,8 +26,40 @@ impl CursorShape {
     }
 }
 
+/// How many cells a glyph run with `advance` (pango units) covers.
+///
+/// Emoji may shape to one glyph which advances two cells even neovim reports one,
+/// so the measured advance wins, but never less than reported and never over two cells.
+pub fn cells_for_advance(advance: i32, cell_width: f64, reported: f64) -> f64 {
+    if reported <= 0. {
+        return 0.;
+    }
+    let measured = (advance as f64 / pango::SCALE as f64 / cell_width).round();
+    measured.max(reported).min(2.)
+}
+
+/// How many cells cursor covers over `cell`, double width cells take two.
+pub fn cell_width(cell: &TextCell) -> f64 {
+    if cell.double_width {
+        2.
+    } else {
+        1.
+    }
+}
+
+/// How cursor is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorRendering {
+    /// block cursor redraws the glyph under it with the cell's font.
+    Glyph,
+    /// plain rectangle like terminals, no glyph redraw.
+    Rect,
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CursorMode {
+    /// mode name of `guicursor`, e.g. `normal`, `insert`.
+    pub name: Option<String>,
     pub shape: Option<CursorShape>,
     pub style: Option<u64>,
     pub cell_percentage: Option<f64>,
@@ -36,6 +68,117 @@ pub struct CursorMode {
     pub blinkoff: Option<u64>,
 }
 
+/// Cursor mode of `index`, falls back to the first mode when `mode_info_set`
+/// provided fewer modes, `None` if no modes at all.
+pub fn cursor_mode(modes: &[CursorMode], index: usize) -> Option<&CursorMode> {
+    modes.get(index).or_else(|| {
+        log::warn!("cursor mode {} out of {} modes.", index, modes.len());
+        modes.first()
+    })
+}
+
+/// Mode names of `mode_info_set`, see `:help guicursor`.
+const MODE_NAMES: &[&str] = &[
+    "all",
+    "normal",
+    "visual",
+    "insert",
+    "replace",
+    "cmdline_normal",
+    "cmdline_insert",
+    "cmdline_replace",
+    "operator",
+    "visual_select",
+    "cmdline_hover",
+    "statusline_hover",
+    "statusline_drag",
+    "vsep_hover",
+    "vsep_drag",
+    "more",
+    "more_lastline",
+    "showmatch",
+];
+
+/// Cursor shape forced by user regardless of `guicursor`, `all` matches every mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorOverride {
+    pub mode: String,
+    pub shape: CursorShape,
+    pub blink: Option<bool>,
+}
+
+impl std::str::FromStr for CursorOverride {
+    type Err = String;
+
+    /// `MODE=SHAPE[:blink|:noblink]`, e.g. `insert=block:noblink`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expect MODE=SHAPE, got '{}'", s))?;
+        if !MODE_NAMES.contains(&mode) {
+            return Err(format!(
+                "unknown mode '{}', expect one of {}",
+                mode,
+                MODE_NAMES.join(", ")
+            ));
+        }
+        let (shape, blink) = match value.split_once(':') {
+            Some((shape, "blink")) => (shape, Some(true)),
+            Some((shape, "noblink")) => (shape, Some(false)),
+            Some((_, blink)) => return Err(format!("expect blink or noblink, got '{}'", blink)),
+            None => (value, None),
+        };
+        let shape = CursorShape::from_type_name(shape).ok_or_else(|| {
+            format!(
+                "unknown shape '{}', expect block, horizontal or vertical",
+                shape
+            )
+        })?;
+        Ok(CursorOverride {
+            mode: mode.to_string(),
+            shape,
+            blink,
+        })
+    }
+}
+
+impl CursorOverride {
+    /// Apply overrides of `mode` in order, later wins.
+    pub fn apply(overrides: &[CursorOverride], mode: &mut CursorMode) {
+        for o in overrides.iter() {
+            if o.mode != "all" && mode.name.as_deref() != Some(o.mode.as_str()) {
+                continue;
+            }
+            if mode.shape.as_ref() != Some(&o.shape) {
+                // percentage of neovim's shape is meaningless for the forced one.
+                mode.cell_percentage = match o.shape {
+                    CursorShape::Block => None,
+                    CursorShape::Vertical => Some(0.25),
+                    CursorShape::Horizontal => Some(0.2),
+                };
+                mode.shape = Some(o.shape.clone());
+            }
+            match o.blink {
+                Some(false) => {
+                    mode.blinkwait = Some(0);
+                    mode.blinkon = Some(0);
+                    mode.blinkoff = Some(0);
+                }
+                Some(true) if mode.blinkon.unwrap_or(0) == 0 || mode.blinkoff.unwrap_or(0) == 0 => {
+                    // defaults of `guicursor` blinking.
+                    mode.blinkwait = Some(700);
+                    mode.blinkon = Some(400);
+                    mode.blinkoff = Some(250);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Glyphs shaped for text and highlight of a cell, at a generation of colors.
+type Shaped = ((Rc<str>, Option<u64>, u64), pango::Item, pango::GlyphString);
+
 #[derive(Clone, Debug)]
 pub struct Cursor {
     // {cols}x{rows}
@@ -52,6 +195,10 @@ pub struct Cursor {
     pub enabled: bool,
     pub width: f64,
     pub cell: TextCell,
+    // draw as terminals do instead of redrawing glyph.
+    pub terminal_style: bool,
+    // reused while cursor redrawn over the same character, until font changed.
+    shaped: RefCell<Option<Shaped>>,
 
     pub pctx: Rc<pango::Context>,
     pub metrics: Rc<Cell<Metrics>>,
@@ -76,6 +223,8 @@ impl Cursor {
             enabled: true,
             width: 1.,
             cell: TextCell::default(),
+            terminal_style: false,
+            shaped: RefCell::new(None),
 
             pctx,
             hldefs,
@@ -83,7 +232,9 @@ impl Cursor {
         }
     }
 
-    pub fn rectangle(&self, width: f64, height: f64) -> (f64, f64, f64, f64) {
+    /// Region of cursor `(x, y, width, height)` in pixel, bars are at least
+    /// `pixel` thick, the size of one device pixel.
+    pub fn rectangle(&self, width: f64, height: f64, pixel: f64) -> (f64, f64, f64, f64) {
         let percentage = self.cell_percentage.unwrap_or(1.);
         log::debug!(
             "cursor percentage {:?} {}",
@@ -94,26 +245,29 @@ impl Cursor {
             CursorShape::Block => (
                 self.coord.col * width,
                 self.coord.row * height,
-                width,
+                width * self.width,
                 height,
             ),
             CursorShape::Vertical => (
                 self.coord.col * width,
                 self.coord.row * height,
-                width * percentage,
+                (width * percentage).max(pixel),
                 height,
             ),
-            CursorShape::Horizontal => (
-                self.coord.col * width,
-                self.coord.row * height + height - height * percentage,
-                width,
-                height * percentage,
-            ),
+            CursorShape::Horizontal => {
+                let thickness = (height * percentage).max(pixel);
+                (
+                    self.coord.col * width,
+                    self.coord.row * height + height - thickness,
+                    width * self.width,
+                    thickness,
+                )
+            }
         }
     }
 
     pub fn foreground(&self) -> Color {
-        let hldefs = self.hldefs.read();
+        let hldefs = self.hldefs.read().snapshot();
         let default_colors = hldefs.defaults().unwrap();
         if let Some(style_id) = self.style.filter(|&s| s != HighlightDefinitions::DEFAULT) {
             let style = hldefs.get(style_id).unwrap();
@@ -127,7 +281,7 @@ impl Cursor {
     }
 
     pub fn background(&self) -> Color {
-        let hldefs = self.hldefs.read();
+        let hldefs = self.hldefs.read().snapshot();
         let default_colors = hldefs.defaults().unwrap();
         let (mut color, blend) =
             if let Some(style_id) = self.style.filter(|&s| s != HighlightDefinitions::DEFAULT) {
@@ -166,25 +320,38 @@ impl Cursor {
     }
 
     pub fn set_cell(&mut self, cell: TextCell) {
-        let width = if cell.text.is_empty() {
-            0.
-        } else {
-            let character = cell.text.chars().next().unwrap();
+        self.width = cell_width(&cell);
+        self.cell = cell;
+    }
 
-            if cell.double_width {
-                2.
-            } else if pango::is_zero_width(character) {
-                0.
-            } else {
-                1.
+    /// Item and glyphs of cell with `attrs`, shaped once per text and highlight.
+    ///
+    /// Redefined highlights bump generation of colors, e.g. `bold` changed.
+    pub fn shaped(&self, attrs: &pango::AttrList) -> (pango::Item, pango::GlyphString) {
+        let colors = self.hldefs.read().colors();
+        let key = (self.cell.text.clone(), self.cell.hldef, colors);
+        if let Some((cached, item, glyphs)) = &*self.shaped.borrow() {
+            if *cached == key {
+                return (item.clone(), glyphs.clone());
             }
-        };
-        self.cell = cell;
-        self.width = width;
+        }
+        let text = &self.cell.text;
+        let item = pango::itemize(&self.pctx, text, 0, text.len() as _, attrs, None).remove(0);
+        let mut glyphs = pango::GlyphString::new();
+        pango::shape(text, item.analysis(), &mut glyphs);
+        self.shaped
+            .replace(Some((key, item.clone(), glyphs.clone())));
+        (item, glyphs)
+    }
+
+    /// Drop shaped glyphs, font or highlights changed.
+    pub fn reset_cache(&mut self) {
+        self.shaped.replace(None);
     }
 
     pub fn set_mode(&mut self, cursor_mode: CursorMode) {
         let CursorMode {
+            name: _,
             shape,
             style,
             cell_percentage,
@@ -205,6 +372,17 @@ impl Cursor {
         self.blinkoff = blinkoff;
     }
 
+    pub fn set_terminal_style(&mut self, terminal_style: bool) {
+        self.terminal_style = terminal_style;
+    }
+
+    pub fn rendering(&self) -> CursorRendering {
+        match self.shape {
+            CursorShape::Block if !self.terminal_style => CursorRendering::Glyph,
+            _ => CursorRendering::Rect,
+        }
+    }
+
     pub fn set_grid(&mut self, grid: u64) {
         self.grid = grid;
     }
@@ -276,6 +454,157 @@ mod tests {
         special: None,
     });
 
+    #[test]
+    fn test_cells_for_advance() {
+        let scale = pango::SCALE;
+        // ascii
+        assert_eq!(cells_for_advance(8 * scale, 8., 1.), 1.);
+        // color emoji "😀" shaped to one glyph advances about two cells.
+        assert_eq!(cells_for_advance(15 * scale, 8., 1.), 2.);
+        assert_eq!(cells_for_advance(15 * scale, 8., 2.), 2.);
+        // CJK reported as double width.
+        assert_eq!(cells_for_advance(16 * scale, 8., 2.), 2.);
+        // narrow glyph of wide cell keeps reported width.
+        assert_eq!(cells_for_advance(7 * scale, 8., 2.), 2.);
+        // never wider than two cells.
+        assert_eq!(cells_for_advance(40 * scale, 8., 1.), 2.);
+        // empty cell
+        assert_eq!(cells_for_advance(8 * scale, 8., 0.), 0.);
+    }
+
+    #[test]
+    fn test_cell_width() {
+        let mut cell = TextCell {
+            text: "a".into(),
+            ..TextCell::default()
+        };
+        assert_eq!(cell_width(&cell), 1.);
+        cell.text = "中".into();
+        cell.double_width = true;
+        assert_eq!(cell_width(&cell), 2.);
+        // trailing half of wide cell.
+        let cell = TextCell {
+            text: "".into(),
+            ..TextCell::default()
+        };
+        assert_eq!(cell_width(&cell), 1.);
+    }
+
+    #[test]
+    fn test_cursor_mode() {
+        // mode_change before any mode_info_set.
+        assert_eq!(cursor_mode(&[], 0), None);
+        let modes = vec![
+            CursorMode {
+                shape: Some(CursorShape::Block),
+                ..CursorMode::default()
+            },
+            CursorMode {
+                shape: Some(CursorShape::Vertical),
+                ..CursorMode::default()
+            },
+        ];
+        assert_eq!(cursor_mode(&modes, 1), Some(&modes[1]));
+        assert_eq!(cursor_mode(&modes, 5), Some(&modes[0]));
+    }
+
+    #[test]
+    fn test_cursor_override() {
+        let parse = |s: &str| s.parse::<CursorOverride>();
+        assert_eq!(
+            parse("insert=block:noblink"),
+            Ok(CursorOverride {
+                mode: "insert".into(),
+                shape: CursorShape::Block,
+                blink: Some(false),
+            })
+        );
+        assert_eq!(parse("all=vertical").unwrap().blink, None);
+        assert!(parse("insrt=block").is_err());
+        assert!(parse("insert=beam").is_err());
+        assert!(parse("insert=block:fast").is_err());
+        assert!(parse("insert").is_err());
+
+        let insert = CursorMode {
+            name: Some("insert".into()),
+            shape: Some(CursorShape::Vertical),
+            cell_percentage: Some(0.25),
+            blinkwait: Some(700),
+            blinkon: Some(400),
+            blinkoff: Some(250),
+            ..CursorMode::default()
+        };
+        let overrides = vec![parse("insert=block:noblink").unwrap()];
+        let mut mode = insert.clone();
+        CursorOverride::apply(&overrides, &mut mode);
+        assert_eq!(mode.shape, Some(CursorShape::Block));
+        assert_eq!(mode.blinkon, Some(0));
+
+        // drawn as block instead of a thin bar.
+        let mut cursor = Cursor::new(
+            Rc::new(pango::Context::new()),
+            Rc::new(Cell::new(Metrics::new())),
+            Rc::new(RwLock::new(HighlightDefinitions::new())),
+        );
+        cursor.set_mode(insert.clone());
+        assert_eq!(cursor.rectangle(8., 16., 1.), (0., 0., 2., 16.));
+        cursor.set_mode(mode);
+        assert_eq!(cursor.rectangle(8., 16., 1.), (0., 0., 8., 16.));
+
+        // other modes untouched, unset overrides use neovim's values.
+        let normal = CursorMode {
+            name: Some("normal".into()),
+            shape: Some(CursorShape::Block),
+            ..CursorMode::default()
+        };
+        let mut mode = normal.clone();
+        CursorOverride::apply(&overrides, &mut mode);
+        assert_eq!(mode, normal);
+        let mut mode = insert.clone();
+        CursorOverride::apply(&[], &mut mode);
+        assert_eq!(mode, insert);
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let mut cursor = Cursor::new(
+            Rc::new(pango::Context::new()),
+            Rc::new(Cell::new(Metrics::new())),
+            Rc::new(RwLock::new(HighlightDefinitions::new())),
+        );
+        cursor.coord = (2, 1).into();
+        assert_eq!(cursor.rectangle(8., 16., 1.), (16., 16., 8., 16.));
+
+        cursor.shape = CursorShape::Vertical;
+        cursor.cell_percentage = Some(0.25);
+        assert_eq!(cursor.rectangle(8., 16., 1.), (16., 16., 2., 16.));
+        // thin bar stays visible.
+        cursor.cell_percentage = Some(0.01);
+        assert_eq!(cursor.rectangle(8., 16., 1.), (16., 16., 1., 16.));
+        assert_eq!(cursor.rectangle(8., 16., 0.5), (16., 16., 0.5, 16.));
+
+        cursor.shape = CursorShape::Horizontal;
+        cursor.cell_percentage = Some(0.25);
+        assert_eq!(cursor.rectangle(8., 16., 1.), (16., 28., 8., 4.));
+        cursor.cell_percentage = Some(0.01);
+        assert_eq!(cursor.rectangle(8., 16., 1.), (16., 31., 8., 1.));
+    }
+
+    #[test]
+    fn test_rendering() {
+        let mut cursor = Cursor::new(
+            Rc::new(pango::Context::new()),
+            Rc::new(Cell::new(Metrics::new())),
+            Rc::new(RwLock::new(HighlightDefinitions::new())),
+        );
+        assert_eq!(cursor.rendering(), CursorRendering::Glyph);
+        cursor.set_terminal_style(true);
+        assert_eq!(cursor.rendering(), CursorRendering::Rect);
+        cursor.set_terminal_style(false);
+        cursor.shape = CursorShape::Vertical;
+        assert_eq!(cursor.rendering(), CursorRendering::Rect);
+    }
+
     #[test]
     fn test_from_type_name() {
         assert_eq!(
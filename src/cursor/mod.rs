@@ -2,14 +2,17 @@ mod cursor;
 // mod state;
 // mod vfx;
 
-pub use cursor::{Cursor as VimCursor, CursorMode, CursorShape};
+pub use cursor::{
+    cells_for_advance, cursor_mode, Cursor as VimCursor, CursorMode, CursorOverride,
+    CursorRendering, CursorShape,
+};
 use gtk::prelude::{StyleContextExt, WidgetExt};
 
 use relm4::drawing::DrawContext;
 use relm4::{MicroModel, MicroWidgets, Sender};
 
 use crate::grapheme::Coord;
-use crate::vimview::TextCell;
+use crate::vimview::{self, TextCell};
 
 impl MicroModel for VimCursor {
     type Msg = CursorMessage;
@@ -62,6 +65,8 @@ pub struct CursorWidgets {
     da: gtk::DrawingArea,
     dh: relm4::drawing::DrawHandler,
     css_provider: gtk::CssProvider,
+    // alternates keyframes name, gtk keeps running animation of the same name.
+    blink_phase: bool,
 }
 
 impl MicroWidgets<VimCursor> for CursorWidgets {
@@ -88,6 +93,7 @@ impl MicroWidgets<VimCursor> for CursorWidgets {
             da,
             dh,
             css_provider,
+            blink_phase: false,
         }
     }
 
@@ -98,11 +104,17 @@ impl MicroWidgets<VimCursor> for CursorWidgets {
         self.da.remove_css_class("blink");
         self.da.style_context().remove_provider(&self.css_provider);
         let cr = self.dh.get_context().unwrap();
-        vc.drawing(&cr);
+        vc.drawing(&cr, self.da.scale_factor() as f64);
         self.da
             .style_context()
             .add_provider(&self.css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-        if let Some(blinking) = vc.maybe_blinking() {
+        self.blink_phase = !self.blink_phase;
+        let keyframes = if self.blink_phase {
+            "blinking"
+        } else {
+            "blinking-again"
+        };
+        if let Some(blinking) = vc.maybe_blinking(keyframes) {
             self.css_provider.load_from_data(blinking.as_bytes());
             self.da
                 .style_context()
@@ -121,32 +133,35 @@ impl MicroWidgets<VimCursor> for CursorWidgets {
 }
 
 impl VimCursor {
-    fn maybe_blinking(&self) -> Option<String> {
+    /// Blinking by css animation of `keyframes`, restarts once named differently.
+    pub(crate) fn maybe_blinking(&self, keyframes: &str) -> Option<String> {
         let blinkon = self.blinkon().filter(|blinkon| *blinkon > 0)?;
         let blinkoff = self.blinkoff().filter(|blinkoff| *blinkoff > 0)?;
         let blinkwait = self.blinkwait().filter(|blinkwait| *blinkwait > 0)?;
         let css = format!(
             ".blink {{
-  animation-name: blinking;
+  animation-name: {};
   animation-delay: {}ms;
   animation-duration: {}ms;
   animation-iteration-count: infinite;
   animation-timing-function: steps(2, start);
 }}
 
-@keyframes blinking {{
+@keyframes {} {{
   {}% {{ opacity: 0; }}
 }}
 ",
+            keyframes,
             blinkwait,
             blinkon + blinkoff,
+            keyframes,
             blinkon * 100 / (blinkon + blinkoff)
         );
         log::debug!("css {} {}: \n{}", blinkon, blinkoff, &css);
         Some(css)
     }
 
-    fn drawing(&self, cr: &DrawContext) {
+    fn drawing(&self, cr: &DrawContext, scale_factor: f64) {
         // clear previous position.
         cr.set_operator(cairo::Operator::Clear);
         cr.set_source_rgba(0.0, 0.0, 0.0, 0.0);
@@ -157,10 +172,13 @@ impl VimCursor {
         let fg = self.foreground();
         let cell = self.cell();
         let metrics = self.metrics.get();
-        let (x, y, width, height) = self.rectangle(metrics.width(), metrics.height());
+        // one device pixel in user space, the surface is not scaled by gtk.
+        let (pixel, _) = cr.device_to_user_distance(1., 1.).unwrap_or((1., 1.));
+        let pixel = pixel / scale_factor;
+        let (x, y, width, height) = self.rectangle(metrics.width(), metrics.height(), pixel.abs());
         log::debug!("drawing cursor at {}x{}.", x, y);
-        match self.shape {
-            CursorShape::Block => {
+        match self.rendering() {
+            CursorRendering::Glyph => {
                 use pango::AttrType;
                 let attrs = pango::AttrList::new();
                 cell.attrs
@@ -182,25 +200,36 @@ impl VimCursor {
                     })
                     .for_each(|attr| attrs.insert(attr));
                 log::debug!("cursor cell '{}' wide {}", cell.text, self.width);
-                let itemized = &pango::itemize(
-                    &self.pctx,
-                    &cell.text,
-                    0,
-                    cell.text.len() as _,
-                    &attrs,
-                    None,
-                )[0];
-                let mut glyph_string = pango::GlyphString::new();
-                pango::shape(&cell.text, itemized.analysis(), &mut glyph_string);
+                let (itemized, mut glyph_string) = self.shaped(&attrs);
+                // emoji may advance more cells than reported.
+                let cells = cells_for_advance(glyph_string.width(), metrics.width(), self.width);
+                let width = (metrics.width() * cells).ceil() as i32;
                 let glyphs = glyph_string.glyph_info_mut();
-                assert_eq!(glyphs.len(), 1);
-                let geometry = glyphs[0].geometry_mut();
-                let width = (metrics.width() * self.width).ceil() as i32;
-                if geometry.width() > 0 && geometry.width() != width {
-                    let x_offset = geometry.x_offset() - (geometry.width() - width) / 2;
-                    geometry.set_width(width);
-                    geometry.set_x_offset(x_offset);
-                    log::debug!("cursor glyph width {}", width);
+                let mut x_shift = 0.;
+                if let [glyph] = glyphs {
+                    let geometry = glyph.geometry_mut();
+                    if geometry.width() > 0 && geometry.width() != width {
+                        let x_offset = geometry.x_offset() - (geometry.width() - width) / 2;
+                        geometry.set_width(width);
+                        geometry.set_x_offset(x_offset);
+                        log::debug!("cursor glyph width {}", width);
+                    }
+                    x_shift = geometry.width() as f64 / 2.;
+                } else {
+                    // emoji sequence without ligature, glyphs share the cells.
+                    log::debug!("cursor cell shaped to {} glyphs", glyphs.len());
+                    let advances: Vec<i32> = glyphs
+                        .iter()
+                        .map(|glyph| glyph.geometry().width())
+                        .collect();
+                    let clusters = vimview::glyph_clusters(&vec![0; glyphs.len()], &advances);
+                    for (glyph, cluster) in glyphs.iter_mut().zip(clusters) {
+                        if let Some((nth, count)) = cluster {
+                            glyph
+                                .geometry_mut()
+                                .set_width(vimview::share_width(width, nth, count));
+                        }
+                    }
                 }
                 // 试试汉字
                 cr.save().unwrap();
@@ -219,17 +248,18 @@ impl VimCursor {
                     fg.blue() as f64,
                     fg.alpha() as f64,
                 );
-                cr.move_to(x + geometry.width() as f64 / 2., y + metrics.ascent());
+                cr.move_to(x + x_shift, y + metrics.baseline());
                 pangocairo::show_glyph_string(cr, &itemized.analysis().font(), &mut glyph_string);
             }
-            _ => {
+            CursorRendering::Rect => {
                 log::debug!("drawing cursor with {}x{}", width, height);
-                cr.set_source_rgba(
-                    bg.red() as f64,
-                    bg.green() as f64,
-                    bg.blue() as f64,
-                    bg.alpha() as f64,
-                );
+                // glyph is not redrawn, keep it readable under block.
+                let alpha = if matches!(self.shape, CursorShape::Block) {
+                    bg.alpha() as f64 * 0.6
+                } else {
+                    bg.alpha() as f64
+                };
+                cr.set_source_rgba(bg.red() as f64, bg.green() as f64, bg.blue() as f64, alpha);
                 cr.rectangle(x, y, width, height);
                 cr.fill().unwrap();
             }
@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::fmt::Debug;
 
 use gtk::glib::Sender;
+use gtk::prelude::*;
 use relm4::factory::{Factory, FactoryPrototype, FactoryView};
 use rustc_hash::FxHashMap;
 use vector_map::VecMap;
@@ -34,6 +35,26 @@ impl Default for ChangeType {
     }
 }
 
+impl ChangeType {
+    /// Merge a newer change into pending one of the same key.
+    ///
+    /// `None` means nothing left to do, e.g. widget removed before it was ever created.
+    fn merge(pending: Option<ChangeType>, change: ChangeType) -> Option<ChangeType> {
+        use ChangeType::*;
+        match (pending, change) {
+            (None, change) => Some(change),
+            (Some(Add), Update) => Some(Add),
+            (Some(Add), Remove) => None,
+            (Some(Add), Add | Recreate) => Some(Add),
+            (Some(Remove), Add | Recreate) => Some(Recreate),
+            (Some(Remove), Remove | Update) => Some(Remove),
+            (Some(Recreate), Update | Add | Recreate) => Some(Recreate),
+            (Some(Recreate), Remove) => Some(Remove),
+            (Some(Update), change) => Some(change),
+        }
+    }
+}
+
 /// A container similar to [`HashMap`] that implements [`Factory`].
 #[allow(clippy::type_complexity)]
 #[derive(Default, Debug)]
@@ -159,11 +180,24 @@ where
         self.data.get_mut(&key)
     }
 
+    /// Mark all elements as modified, all widgets will be updated after next flush.
+    pub fn update_all(&mut self) {
+        let mut staged = self.staged.borrow_mut();
+        for key in self.data.keys() {
+            if !staged.contains_key(key) {
+                staged.insert(*key, ChangeType::Update);
+            }
+        }
+    }
+
     pub fn flush(&mut self) {
         let mut staged = self.staged.borrow_mut();
         let mut flushes = self.flushes.borrow_mut();
         for (k, v) in staged.iter() {
-            flushes.insert(*k, *v);
+            match ChangeType::merge(flushes.get(k).copied(), *v) {
+                Some(change) => flushes.insert(*k, change),
+                None => flushes.remove(k),
+            };
         }
         staged.clear();
     }
@@ -172,7 +206,8 @@ where
 impl<Data, View> Factory<Data, View> for FactoryMap<Data>
 where
     Data: FactoryPrototype<Factory = Self, View = View>,
-    View: FactoryView<Data::Root>,
+    View: FactoryView<Data::Root> + AsRef<gtk::Widget>,
+    <View as FactoryView<Data::Root>>::Root: AsRef<gtk::Widget>,
 {
     type Key = u64;
 
@@ -201,13 +236,15 @@ where
                         .view(index, &widgets.get(index).unwrap().widgets);
                 }
                 ChangeType::Remove => {
-                    widgets
-                        .remove(index)
-                        .map(|widget| view.remove(&widget.root));
+                    // dropping widgets releases controllers and caches hold by root.
+                    if let Some(widget) = widgets.remove(index) {
+                        remove_root(view, &widget.root);
+                    }
                 }
                 ChangeType::Recreate => {
-                    let remove_widget = widgets.remove(index).unwrap();
-                    view.remove(&remove_widget.root);
+                    if let Some(widget) = widgets.remove(index) {
+                        remove_root(view, &widget.root);
+                    }
                     let data = self.data.get(index).unwrap();
                     let new_widgets = data.init_view(index, sender.clone());
                     let position = data.position(index);
@@ -223,6 +260,21 @@ where
             }
         }
         self.flushes.borrow_mut().clear();
+        log::trace!("factory map holds {} widgets", self.widgets.borrow().len());
+    }
+}
+
+/// Remove `root` from `view`, or from wherever it was moved by its data, e.g. floats.
+fn remove_root<View, Root>(view: &View, root: &<View as FactoryView<Root>>::Root)
+where
+    View: FactoryView<Root> + AsRef<gtk::Widget>,
+    <View as FactoryView<Root>>::Root: AsRef<gtk::Widget>,
+{
+    let widget: &gtk::Widget = root.as_ref();
+    match widget.parent() {
+        Some(parent) if &parent == view.as_ref() => view.remove(root),
+        Some(_) => widget.unparent(),
+        None => {}
     }
 }
 
@@ -240,3 +292,114 @@ where
         self.data.iter_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use relm4::factory::positions::FixedPosition;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Item;
+
+    impl FactoryPrototype for Item {
+        type Factory = FactoryMap<Self>;
+        type Widgets = gtk::Label;
+        type Root = gtk::Label;
+        type View = gtk::Fixed;
+        type Msg = ();
+
+        fn init_view(&self, _key: &u64, _sender: Sender<()>) -> gtk::Label {
+            gtk::Label::new(None)
+        }
+
+        fn position(&self, _key: &u64) -> FixedPosition {
+            FixedPosition { x: 0., y: 0. }
+        }
+
+        fn view(&self, _key: &u64, _widgets: &gtk::Label) {}
+
+        fn root_widget(widgets: &gtk::Label) -> &gtk::Label {
+            widgets
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        use ChangeType::*;
+        assert!(matches!(ChangeType::merge(None, Add), Some(Add)));
+        assert!(matches!(ChangeType::merge(Some(Add), Update), Some(Add)));
+        assert!(matches!(ChangeType::merge(Some(Add), Remove), None));
+        assert!(matches!(ChangeType::merge(Some(Remove), Add), Some(Recreate)));
+        assert!(matches!(ChangeType::merge(Some(Recreate), Remove), Some(Remove)));
+        assert!(matches!(ChangeType::merge(Some(Update), Remove), Some(Remove)));
+    }
+
+    #[test]
+    fn test_insert_remove_repeatedly() {
+        // widgets need a display, nothing to generate without one.
+        if gtk::init().is_err() {
+            return;
+        }
+        let view = gtk::Fixed::new();
+        let (sender, _receiver) = gtk::glib::MainContext::channel(gtk::glib::PRIORITY_DEFAULT);
+        let children = |view: &gtk::Fixed| {
+            std::iter::successors(view.first_child(), |child| child.next_sibling()).count()
+        };
+        let mut map = FactoryMap::new();
+        for _ in 0..100 {
+            for key in 0..10 {
+                map.insert(key, Item);
+                map.flush();
+                map.generate(&view, sender.clone());
+                assert_eq!(children(&view), 1);
+                map.remove(key);
+                map.flush();
+                map.generate(&view, sender.clone());
+                assert_eq!(children(&view), 0);
+            }
+        }
+        assert!(map.is_empty());
+        // every widget released, nothing left to flush.
+        assert!(map.widgets.borrow().is_empty());
+        assert!(map.flushes.borrow().is_empty());
+        assert!(map.staged.borrow().is_empty());
+
+        // removed before generated, never created.
+        map.insert(1, Item);
+        map.insert(2, Item);
+        map.flush();
+        map.remove(2);
+        map.flush();
+        map.generate(&view, sender);
+        assert_eq!(children(&view), 1);
+        assert_eq!(map.widgets.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_update_all() {
+        let mut map = FactoryMap::new();
+        map.insert(1, Item);
+        map.insert(2, Item);
+        map.insert(3, Item);
+        map.flush();
+        map.update_all();
+        let staged = map.staged.borrow();
+        assert_eq!(staged.len(), 3);
+        for key in [1, 2, 3] {
+            assert!(matches!(staged.get(&key), Some(ChangeType::Update)));
+        }
+    }
+
+    #[test]
+    fn test_update_all_keeps_pending_changes() {
+        let mut map = FactoryMap::new();
+        map.insert(1, Item);
+        map.flush();
+        map.insert(2, Item);
+        map.update_all();
+        let staged = map.staged.borrow();
+        assert!(matches!(staged.get(&1), Some(ChangeType::Update)));
+        assert!(matches!(staged.get(&2), Some(ChangeType::Add)));
+    }
+}
@@ -0,0 +1,256 @@
+/// Size of font given by `guifont`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FontSize {
+    /// `:h12`, points as vim's convention.
+    Points(f64),
+    /// `:h16px`, height in pixels.
+    Pixels(f64),
+}
+
+impl FontSize {
+    /// Size in points, pixels are converted by `dpi`.
+    pub fn points(&self, dpi: f64) -> f64 {
+        match *self {
+            FontSize::Points(points) => points,
+            FontSize::Pixels(pixels) => pixels * 72. / dpi,
+        }
+    }
+}
+
+/// Font parsed from vim's `guifont`, e.g. `Cascadia Code PL,Delugia Nerd Font:h12`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GuiFont {
+    pub family: String,
+    pub size: Option<FontSize>,
+    /// `:b`
+    pub bold: bool,
+    /// `:i`
+    pub italic: bool,
+    /// `:w600`, css like weight, overrides `:b`.
+    pub weight: Option<i32>,
+}
+
+impl GuiFont {
+    pub fn parse(guifont: &str) -> GuiFont {
+        let mut options = guifont.split(':');
+        let family = options.next().unwrap_or_default().trim().to_string();
+        let mut guifont = GuiFont {
+            family,
+            ..GuiFont::default()
+        };
+        for option in options {
+            if let Some(height) = option.strip_prefix('h') {
+                let parsed = match height.strip_suffix("px") {
+                    Some(pixels) => pixels.parse().map(FontSize::Pixels),
+                    None => height.parse().map(FontSize::Points),
+                };
+                match parsed {
+                    Ok(parsed) => guifont.size = Some(parsed),
+                    Err(err) => log::warn!("invalid font height '{}': {}", height, err),
+                }
+            } else if let Some(weight) = option.strip_prefix('w') {
+                match weight.parse::<i32>() {
+                    Ok(weight) => guifont.weight = Some(weight.clamp(MIN_WEIGHT, MAX_WEIGHT)),
+                    Err(err) => log::warn!("invalid font weight '{}': {}", weight, err),
+                }
+            } else if option == "b" {
+                guifont.bold = true;
+            } else if option == "i" {
+                guifont.italic = true;
+            } else {
+                log::debug!("guifont option '{}' not supported.", option);
+            }
+        }
+        guifont
+    }
+
+    /// Families in order of fallback, glyphs missing in one are taken from the next.
+    pub fn families(&self) -> Vec<&str> {
+        self.family
+            .split(',')
+            .map(str::trim)
+            .filter(|family| !family.is_empty())
+            .collect()
+    }
+
+    /// Description of font for pango, size in points converted by `dpi`.
+    pub fn font_description(&self, dpi: f64) -> pango::FontDescription {
+        let families = self.families();
+        // style words like `Light` are parsed from the primary family only.
+        let mut desc = pango::FontDescription::from_string(families.first().unwrap_or(&""));
+        if families.len() > 1 {
+            let primary = desc.family();
+            let fallbacks = primary
+                .as_deref()
+                .into_iter()
+                .chain(families[1..].iter().copied())
+                .collect::<Vec<_>>()
+                .join(",");
+            desc.set_family(&fallbacks);
+        }
+        if let Some(size) = self.size {
+            let points = size.points(dpi);
+            desc.set_size((points * pango::SCALE as f64).round() as i32);
+        }
+        if let Some(weight) = self.weight {
+            desc.set_weight(to_weight(weight));
+        } else if self.bold {
+            desc.set_weight(pango::Weight::Bold);
+        }
+        if self.italic {
+            desc.set_style(pango::Style::Italic);
+        }
+        desc
+    }
+}
+
+const MIN_WEIGHT: i32 = 100;
+const MAX_WEIGHT: i32 = 1000;
+
+fn to_weight(weight: i32) -> pango::Weight {
+    use glib::translate::FromGlib;
+    // pango keeps weights between named ones as they are.
+    unsafe { pango::Weight::from_glib(weight.clamp(MIN_WEIGHT, MAX_WEIGHT)) }
+}
+
+/// Weight of bold highlights over text of font `weight`, heavier than the font
+/// itself when `guifont` is bold already.
+pub fn bolder(weight: pango::Weight) -> pango::Weight {
+    use glib::translate::IntoGlib;
+    let weight = weight.into_glib();
+    let semibold = pango::Weight::Semibold.into_glib();
+    if weight < semibold {
+        pango::Weight::Semibold
+    } else {
+        to_weight(weight + 200)
+    }
+}
+
+/// Font size changed at runtime by Ctrl+scroll or Ctrl+`=`/`-`/`0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zoom {
+    In,
+    Out,
+    /// back to size of `guifont`.
+    Reset,
+}
+
+impl Zoom {
+    /// Pango size zoomed from `size` by a point, `original` if reset.
+    pub fn size(self, size: i32, original: i32) -> i32 {
+        match self {
+            Zoom::In => size + pango::SCALE,
+            Zoom::Out => (size - pango::SCALE).max(pango::SCALE),
+            Zoom::Reset => original,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom() {
+        let size = 11 * pango::SCALE;
+        assert_eq!(Zoom::In.size(size, size), 12 * pango::SCALE);
+        assert_eq!(Zoom::Out.size(size, size), 10 * pango::SCALE);
+        assert_eq!(Zoom::Out.size(pango::SCALE, size), pango::SCALE);
+        assert_eq!(Zoom::Reset.size(14 * pango::SCALE, size), size);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            GuiFont::parse("Cascadia Code PL,Delugia Nerd Font:h12"),
+            GuiFont {
+                family: "Cascadia Code PL,Delugia Nerd Font".to_string(),
+                size: Some(FontSize::Points(12.)),
+                ..GuiFont::default()
+            }
+        );
+        assert_eq!(
+            GuiFont::parse("Monospace:h16px"),
+            GuiFont {
+                family: "Monospace".to_string(),
+                size: Some(FontSize::Pixels(16.)),
+                ..GuiFont::default()
+            }
+        );
+        assert_eq!(GuiFont::parse("Monospace").size, None);
+        assert_eq!(GuiFont::parse("Monospace:hxx").size, None);
+        assert_eq!(
+            GuiFont::parse("Monospace:b:h10.5").size,
+            Some(FontSize::Points(10.5))
+        );
+    }
+
+    #[test]
+    fn test_parse_style() {
+        let guifont = GuiFont::parse("Monospace:b:i:h12");
+        assert!(guifont.bold);
+        assert!(guifont.italic);
+        assert_eq!(guifont.weight, None);
+        assert_eq!(GuiFont::parse("Monospace:w300").weight, Some(300));
+        assert_eq!(GuiFont::parse("Monospace:w5000").weight, Some(1000));
+        assert_eq!(GuiFont::parse("Monospace:wxx").weight, None);
+
+        let desc = GuiFont::parse("Monospace:b:i").font_description(96.);
+        assert_eq!(desc.weight(), pango::Weight::Bold);
+        assert_eq!(desc.style(), pango::Style::Italic);
+        let desc = GuiFont::parse("Monospace:b:w300").font_description(96.);
+        assert_eq!(desc.weight(), pango::Weight::Light);
+        let desc = GuiFont::parse("Monospace").font_description(96.);
+        assert_eq!(desc.weight(), pango::Weight::Normal);
+        assert_eq!(desc.style(), pango::Style::Normal);
+    }
+
+    #[test]
+    fn test_fallback_families() {
+        let guifont = GuiFont::parse("Cascadia Code PL, Noto Sans CJK JP,:h12");
+        assert_eq!(
+            guifont.families(),
+            vec!["Cascadia Code PL", "Noto Sans CJK JP"]
+        );
+        let desc = guifont.font_description(96.);
+        assert_eq!(
+            desc.family().as_deref(),
+            Some("Cascadia Code PL,Noto Sans CJK JP")
+        );
+        assert_eq!(desc.size(), 12 * pango::SCALE);
+
+        let desc = GuiFont::parse("Fira Code Light,Noto Sans CJK JP").font_description(96.);
+        assert_eq!(desc.family().as_deref(), Some("Fira Code,Noto Sans CJK JP"));
+        assert_eq!(desc.weight(), pango::Weight::Light);
+
+        let desc = GuiFont::parse("Monospace").font_description(96.);
+        assert_eq!(desc.family().as_deref(), Some("Monospace"));
+    }
+
+    #[test]
+    fn test_bolder() {
+        assert_eq!(bolder(pango::Weight::Normal), pango::Weight::Semibold);
+        assert_eq!(bolder(pango::Weight::Light), pango::Weight::Semibold);
+        // bold guifont, bold highlights stand out still.
+        assert_eq!(bolder(pango::Weight::Bold), pango::Weight::Heavy);
+        assert_eq!(bolder(pango::Weight::Heavy), pango::Weight::Ultraheavy);
+        assert_eq!(bolder(pango::Weight::Ultraheavy), pango::Weight::Ultraheavy);
+    }
+
+    #[test]
+    fn test_font_size() {
+        let points = GuiFont::parse("Monospace:h12").font_description(96.);
+        assert_eq!(points.size(), 12 * pango::SCALE);
+        // points never depend on dpi.
+        let points = GuiFont::parse("Monospace:h12").font_description(192.);
+        assert_eq!(points.size(), 12 * pango::SCALE);
+
+        // 16px at 96 dpi is 12pt.
+        let pixels = GuiFont::parse("Monospace:h16px").font_description(96.);
+        assert_eq!(pixels.size(), 12 * pango::SCALE);
+        assert_eq!(pixels.family().as_deref(), Some("Monospace"));
+        // 16px at 192 dpi is 6pt.
+        let pixels = GuiFont::parse("Monospace:h16px").font_description(192.);
+        assert_eq!(pixels.size(), 6 * pango::SCALE);
+    }
+}
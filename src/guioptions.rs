@@ -0,0 +1,50 @@
+/// Gui chrome hinted by vim's `guioptions`, notified by neovim on `OptionSet`.
+///
+/// Default to no chrome.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GuiOptions {
+    /// `r` or `R`
+    pub scrollbar_right: bool,
+    /// `l` or `L`
+    pub scrollbar_left: bool,
+}
+
+impl GuiOptions {
+    pub fn parse(flags: &str) -> GuiOptions {
+        let mut options = GuiOptions::default();
+        for flag in flags.chars() {
+            match flag {
+                'r' | 'R' => options.scrollbar_right = true,
+                'l' | 'L' => options.scrollbar_left = true,
+                _ => log::debug!("guioptions flag '{}' not supported.", flag),
+            }
+        }
+        options
+    }
+
+    pub fn has_scrollbar(&self) -> bool {
+        self.scrollbar_right || self.scrollbar_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(GuiOptions::parse(""), GuiOptions::default());
+        assert!(!GuiOptions::parse("").has_scrollbar());
+        let options = GuiOptions::parse("aegimrLtT");
+        assert_eq!(
+            options,
+            GuiOptions {
+                scrollbar_right: true,
+                scrollbar_left: true,
+            }
+        );
+        assert!(options.has_scrollbar());
+        // horizontal scrollbar and menu are not supported.
+        assert!(!GuiOptions::parse("bm").has_scrollbar());
+    }
+}
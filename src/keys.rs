@@ -1,9 +1,87 @@
 use gtk::gdk;
 
+use crate::guifont::Zoom;
+
 pub trait ToInput {
     fn to_input(&self) -> Option<String>;
 }
 
+/// Alt combos skip input method composition when Alt is treated as Meta, go to
+/// neovim as `<M-key>` even if the input method would compose them.
+///
+/// `modifier` should be the [`effective_modifier`], Alt consumed by the layout is
+/// no Meta.
+pub fn bypass_im_context(alt_as_meta: bool, modifier: gdk::ModifierType) -> bool {
+    alt_as_meta && modifier.intersects(gdk::ModifierType::ALT_MASK | gdk::ModifierType::META_MASK)
+}
+
+/// Modifiers the keyval was produced by are left out, e.g. AltGr as Ctrl+Alt on
+/// Windows or Alt of European layouts, so `@` comes as `@` not `<C-A-@>`.
+/// Shift stays consumed or not, `<S-Tab>` needs it.
+pub fn effective_modifier(
+    modifier: gdk::ModifierType,
+    consumed: gdk::ModifierType,
+) -> gdk::ModifierType {
+    let altgr = gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK;
+    modifier.difference(consumed & altgr)
+}
+
+/// Gui overlays shown over grids.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Overlays {
+    /// messages of `ext_messages`.
+    pub messages: bool,
+    /// find on screen bar.
+    pub find: bool,
+    /// window of `:messages`.
+    pub message_history: bool,
+    /// completion popup menu.
+    pub popupmenu: bool,
+}
+
+impl Overlays {
+    pub fn any(&self) -> bool {
+        self.messages || self.find || self.message_history || self.popupmenu
+    }
+}
+
+/// Plain Escape goes to gui overlays first only if enabled and there is any overlay
+/// opened, otherwise it is sent to neovim as usual.
+pub fn escape_dismisses_overlay(
+    keyval: gdk::Key,
+    modifier: gdk::ModifierType,
+    enabled: bool,
+    overlays: Overlays,
+) -> bool {
+    keyval == gdk::Key::Escape && modifier.is_empty() && enabled && overlays.any()
+}
+
+/// Text committed at once is pasted literally if it has many lines or more than
+/// `threshold` chars, single typed chars go as keys. `0` never pastes.
+pub fn commit_as_paste(text: &str, threshold: usize) -> bool {
+    threshold > 0 && (text.contains('\n') || text.chars().count() > threshold)
+}
+
+/// Ctrl+Shift+V pastes clipboard by gui, like terminals.
+pub fn paste(keyval: gdk::Key, modifier: gdk::ModifierType) -> bool {
+    let ctrl_shift = gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK;
+    modifier == ctrl_shift && matches!(keyval, gdk::Key::V | gdk::Key::v)
+}
+
+/// Ctrl+`=`/`+`, Ctrl+`-` and Ctrl+`0` zoom font by gui, not sent to neovim.
+pub fn zoom(keyval: gdk::Key, modifier: gdk::ModifierType) -> Option<Zoom> {
+    // shift is needed for `+` on most layouts.
+    if modifier.difference(gdk::ModifierType::SHIFT_MASK) != gdk::ModifierType::CONTROL_MASK {
+        return None;
+    }
+    match keyval {
+        gdk::Key::equal | gdk::Key::plus | gdk::Key::KP_Add => Some(Zoom::In),
+        gdk::Key::minus | gdk::Key::KP_Subtract => Some(Zoom::Out),
+        gdk::Key::_0 | gdk::Key::KP_0 => Some(Zoom::Reset),
+        _ => None,
+    }
+}
+
 fn map_keyname(keyname: String) -> Option<&'static str> {
     // Originally sourced from python-gui.
     match keyname.as_ref() {
@@ -27,9 +105,7 @@ fn map_keyname(keyname: String) -> Option<&'static str> {
         "bracketright" => "]".into(),
         "braceleft" => "{".into(),
         "braceright" => "}".into(),
-        "dead_diaeresis" => "\"".into(),
-        "dead_acute" => "\'".into(),
-        "less" => "<".into(),
+        "less" => "lt".into(),
         "greater" => ">".into(),
         "comma" => ",".into(),
         "period" => ".".into(),
@@ -65,27 +141,39 @@ fn map_keyname(keyname: String) -> Option<&'static str> {
     }
 }
 
+/// Prefixes of modifiers in order of `<C-S-M-D-x>`, Alt and Meta are both `M-`,
+/// Super (Cmd on macOS) is `D-`.
 impl ToInput for gdk::ModifierType {
     fn to_input(&self) -> Option<String> {
         let mut input = String::with_capacity(8);
 
-        if self.contains(gdk::ModifierType::SHIFT_MASK) {
-            input.push_str("S-");
-        }
         if self.contains(gdk::ModifierType::CONTROL_MASK) {
             input.push_str("C-");
         }
-        if self.contains(gdk::ModifierType::ALT_MASK) {
-            input.push_str("A-");
+        if self.contains(gdk::ModifierType::SHIFT_MASK) {
+            input.push_str("S-");
         }
-        if self.contains(gdk::ModifierType::SUPER_MASK) {
+        if self.intersects(gdk::ModifierType::ALT_MASK | gdk::ModifierType::META_MASK) {
             input.push_str("M-");
         }
+        if self.contains(gdk::ModifierType::SUPER_MASK) {
+            input.push_str("D-");
+        }
 
         Some(format!("{}", input).into())
     }
 }
 
+/// Text typed as it is, e.g. committed by input method, only `<` would start a keycode.
+impl ToInput for str {
+    fn to_input(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.replace('<', "<lt>"))
+    }
+}
+
 impl ToInput for (gdk::Key, gdk::ModifierType) {
     fn to_input(&self) -> Option<String> {
         let modkey = self.1.to_input()?;
@@ -98,8 +186,180 @@ impl ToInput for (gdk::Key, gdk::ModifierType) {
             if !self.1.is_empty() {
                 format!("<{}{}>", modkey, k).into()
             } else {
-                k.to_string().into()
+                k.to_string().to_input()
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alt_as_meta() {
+        let alt = gdk::ModifierType::ALT_MASK;
+        // meta mode, translated by to_input.
+        assert!(bypass_im_context(true, alt));
+        assert!(bypass_im_context(true, alt | gdk::ModifierType::CONTROL_MASK));
+        assert_eq!((gdk::Key::a, alt).to_input(), Some("<M-a>".to_string()));
+        // compose mode, goes to input method.
+        assert!(!bypass_im_context(false, alt));
+        // not an alt combo.
+        assert!(!bypass_im_context(true, gdk::ModifierType::CONTROL_MASK));
+        assert!(!bypass_im_context(true, gdk::ModifierType::empty()));
+    }
+
+    #[test]
+    fn test_commit_as_paste() {
+        assert!(!commit_as_paste("a", 16));
+        assert!(!commit_as_paste("你好", 16));
+        assert!(commit_as_paste("if x:\n    y", 16));
+        assert!(commit_as_paste(&"中".repeat(17), 16));
+        assert!(!commit_as_paste(&"中".repeat(16), 16));
+        assert!(!commit_as_paste("a\nb", 0));
+    }
+
+    #[test]
+    fn test_paste() {
+        let ctrl = gdk::ModifierType::CONTROL_MASK;
+        let shift = gdk::ModifierType::SHIFT_MASK;
+        let alt = gdk::ModifierType::ALT_MASK;
+        assert!(paste(gdk::Key::V, ctrl | shift));
+        assert!(paste(gdk::Key::v, ctrl | shift));
+        // left to neovim, e.g. visual block.
+        assert!(!paste(gdk::Key::v, ctrl));
+        assert!(!paste(gdk::Key::V, ctrl | shift | alt));
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let ctrl = gdk::ModifierType::CONTROL_MASK;
+        let shift = gdk::ModifierType::SHIFT_MASK;
+        let alt = gdk::ModifierType::ALT_MASK;
+        let meta = gdk::ModifierType::META_MASK;
+        let sup = gdk::ModifierType::SUPER_MASK;
+        assert_eq!(sup.to_input(), Some("D-".to_string()));
+        assert_eq!(meta.to_input(), Some("M-".to_string()));
+        assert_eq!((alt | meta).to_input(), Some("M-".to_string()));
+        assert_eq!((shift | ctrl).to_input(), Some("C-S-".to_string()));
+        let all = ctrl | shift | alt | sup;
+        assert_eq!(
+            (gdk::Key::x, all).to_input(),
+            Some("<C-S-M-D-x>".to_string())
+        );
+        assert_eq!((gdk::Key::s, sup).to_input(), Some("<D-s>".to_string()));
+        assert_eq!(
+            (gdk::Key::Return, alt).to_input(),
+            Some("<M-CR>".to_string())
+        );
+        assert_eq!(
+            (gdk::Key::Tab, sup | shift).to_input(),
+            Some("<S-D-Tab>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dead_keys() {
+        let empty = gdk::ModifierType::empty();
+        // left to input method, composed chars come by commit.
+        assert_eq!((gdk::Key::dead_acute, empty).to_input(), None);
+        assert_eq!((gdk::Key::dead_diaeresis, empty).to_input(), None);
+        assert_eq!((gdk::Key::dead_circumflex, empty).to_input(), None);
+    }
+
+    #[test]
+    fn test_altgr() {
+        let ctrl = gdk::ModifierType::CONTROL_MASK;
+        let alt = gdk::ModifierType::ALT_MASK;
+        let shift = gdk::ModifierType::SHIFT_MASK;
+        // AltGr+q gives `@` on german layout.
+        let modifier = effective_modifier(ctrl | alt, ctrl | alt);
+        assert!(modifier.is_empty());
+        assert!(!bypass_im_context(true, modifier));
+        // a real Alt combo.
+        let meta = effective_modifier(alt, gdk::ModifierType::empty());
+        assert_eq!((gdk::Key::a, meta).to_input(), Some("<M-a>".to_string()));
+        assert_eq!(effective_modifier(shift, shift), shift);
+    }
+
+    #[test]
+    fn test_text_input() {
+        assert_eq!("<".to_input(), Some("<lt>".to_string()));
+        assert_eq!("a<b>".to_input(), Some("a<lt>b>".to_string()));
+        // typed literally, not taken as a keycode.
+        assert_eq!("<lt>".to_input(), Some("<lt>lt>".to_string()));
+        assert_eq!("<CR>".to_input(), Some("<lt>CR>".to_string()));
+        // commits of input method.
+        assert_eq!("「」".to_input(), Some("「」".to_string()));
+        assert_eq!("你好".to_input(), Some("你好".to_string()));
+        assert_eq!("".to_input(), None);
+    }
+
+    #[test]
+    fn test_key_input() {
+        let empty = gdk::ModifierType::empty();
+        let ctrl = gdk::ModifierType::CONTROL_MASK;
+        assert_eq!((gdk::Key::less, empty).to_input(), Some("<lt>".to_string()));
+        assert_eq!(
+            (gdk::Key::less, ctrl).to_input(),
+            Some("<C-lt>".to_string())
+        );
+        assert_eq!((gdk::Key::a, empty).to_input(), Some("a".to_string()));
+        assert_eq!(
+            (gdk::Key::Return, empty).to_input(),
+            Some("<CR>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_zoom() {
+        let ctrl = gdk::ModifierType::CONTROL_MASK;
+        let shift = gdk::ModifierType::SHIFT_MASK;
+        assert_eq!(zoom(gdk::Key::equal, ctrl), Some(Zoom::In));
+        assert_eq!(zoom(gdk::Key::plus, ctrl | shift), Some(Zoom::In));
+        assert_eq!(zoom(gdk::Key::minus, ctrl), Some(Zoom::Out));
+        assert_eq!(zoom(gdk::Key::_0, ctrl), Some(Zoom::Reset));
+        assert_eq!(zoom(gdk::Key::equal, gdk::ModifierType::empty()), None);
+        let alt = gdk::ModifierType::ALT_MASK;
+        assert_eq!(zoom(gdk::Key::equal, ctrl | alt), None);
+        assert_eq!(zoom(gdk::Key::a, ctrl), None);
+    }
+
+    #[test]
+    fn test_escape_dismisses_overlay() {
+        let empty = gdk::ModifierType::empty();
+        let escape = |enabled, overlays| {
+            escape_dismisses_overlay(gdk::Key::Escape, empty, enabled, overlays)
+        };
+        let opened = [
+            Overlays {
+                messages: true,
+                ..Overlays::default()
+            },
+            Overlays {
+                find: true,
+                ..Overlays::default()
+            },
+            Overlays {
+                message_history: true,
+                ..Overlays::default()
+            },
+            Overlays {
+                popupmenu: true,
+                ..Overlays::default()
+            },
+        ];
+        for overlays in opened {
+            assert!(escape(true, overlays), "{:?}", overlays);
+            assert!(!escape(false, overlays));
+        }
+        // nothing opened, goes to neovim.
+        assert!(!escape(true, Overlays::default()));
+        let find = opened[1];
+        let ctrl = gdk::ModifierType::CONTROL_MASK;
+        let key = gdk::Key::Escape;
+        assert!(!escape_dismisses_overlay(key, ctrl, true, find));
+        assert!(!escape_dismisses_overlay(gdk::Key::a, empty, true, find));
+    }
+}
@@ -6,6 +6,8 @@ extern crate derivative;
 use clap::{IntoApp, Parser};
 
 mod app;
+mod background;
+mod bell;
 mod bridge;
 mod color;
 mod components;
@@ -13,29 +15,46 @@ mod cursor;
 mod event_aggregator;
 mod factory;
 mod grapheme;
+mod guifont;
+mod guioptions;
 mod keys;
 mod loggingchan;
 mod messager;
 mod metrics;
+mod popupmenu;
+mod preedit;
+mod redraw;
 mod running_tracker;
+mod session;
 mod settings;
 mod style;
+mod tabline;
 mod vimview;
+mod wheel;
 
+#[derive(Debug, PartialEq)]
 enum ConnectionMode {
     Child,
     RemoteTcp(String),
+    RemoteSocket(std::path::PathBuf),
 }
 
 #[derive(Parser, Clone, Debug, Default, PartialEq)]
 pub struct Opts {
-    /// Path to neovim binary
-    #[clap(long = "nvim", env = "NVIM", value_name = "NVIM")]
+    /// Path to neovim binary, or `$REOVIM_NVIM`. `$NVIM` is no longer read for it,
+    /// neovim sets it to its server address in terminals.
+    #[clap(long = "nvim", env = "REOVIM_NVIM", value_name = "NVIM")]
     nvim_path: Option<String>,
 
-    /// Remote nvim via tcp
-    #[clap(long = "remote", env = "REMOTE", value_name = "HOST:PORT")]
-    remote_tcp: Option<String>,
+    /// Attach to a running neovim at HOST:PORT, or path of its socket or named pipe,
+    /// e.g. `--server 127.0.0.1:6666` or `--server $NVIM`.
+    #[clap(
+        long = "server",
+        alias = "remote",
+        env = "REMOTE",
+        value_name = "ADDRESS"
+    )]
+    server: Option<String>,
 
     // initial window width
     #[clap(long = "window-width", env = "WIDTH", default_value_t = 800)]
@@ -44,6 +63,192 @@ pub struct Opts {
     #[clap(long = "window-height", env = "HEIGHT", default_value_t = 600)]
     height: i32,
 
+    /// Initial size of window in cells, e.g. `90x30`, overrides window width and height.
+    #[clap(long = "geometry", value_name = "COLSxROWS")]
+    geometry: Option<metrics::Geometry>,
+
+    /// Open window maximized.
+    #[clap(long = "maximized")]
+    maximized: bool,
+
+    /// Open window fullscreen, toggle it by `reovim#toggle_fullscreen()` at runtime.
+    #[clap(long = "fullscreen")]
+    fullscreen: bool,
+
+    /// Open window at size of last session, saved under `$XDG_STATE_HOME/reovim` on close.
+    #[clap(long = "remember-window-size")]
+    remember_window_size: bool,
+
+    /// Treat Alt as Meta, Alt+key is sent as `<M-key>` instead of composed by input method.
+    /// Without it Alt+key goes to input method first, `<M-key>` only if nothing composed.
+    /// AltGr, or Alt consumed by the layout, always composes.
+    #[clap(long = "alt-as-meta")]
+    alt_as_meta: bool,
+
+    /// Input committed at once with newlines or more than CHARS chars, e.g. by input
+    /// method, is pasted literally by `nvim_paste` instead of typed. 0 always types.
+    #[clap(long = "paste-threshold", value_name = "CHARS", default_value_t = 16)]
+    paste_threshold: usize,
+
+    /// Escape dismisses gui overlays (e.g. messages) first, before sent to neovim.
+    #[clap(long = "escape-dismiss-overlays")]
+    escape_dismiss_overlays: bool,
+
+    /// Disable ext_multigrid, all windows are drawn in a single grid.
+    #[clap(long = "no-multigrid")]
+    no_multigrid: bool,
+
+    /// Attach ext_messages, messages, mode, ruler and showcmd are shown by gui instead of
+    /// the message grid and `:messages` opens a window. Neovim attaches ext_cmdline with it.
+    #[clap(long = "ext-messages")]
+    ext_messages: bool,
+
+    /// Attach ext_cmdline, cmdline is shown by gui in a popover instead of the last row.
+    #[clap(long = "ext-cmdline")]
+    ext_cmdline: bool,
+
+    /// Draw window separators by gui, works with `--no-multigrid` only.
+    #[clap(long = "split-separators")]
+    split_separators: bool,
+
+    /// Fill sign and number column full height with its background by gui.
+    #[clap(long = "fill-gutter")]
+    fill_gutter: bool,
+
+    /// Overlap cell backgrounds by a sub-pixel, hides seams between powerline glyphs.
+    #[clap(long = "seamless-background")]
+    seamless_background: bool,
+
+    /// Draw cells highlighted by `ReovimImagePlaceholder` as solid rectangles for inline images.
+    #[clap(long = "image-placeholders")]
+    image_placeholders: bool,
+
+    /// Darken windows without focus by AMOUNT of alpha, e.g. `0.3`.
+    #[clap(long = "dim-inactive", value_name = "AMOUNT")]
+    dim_inactive: Option<f64>,
+
+    /// Fade out echo and info messages after SECONDS, errors stay until cleared.
+    #[clap(long = "message-timeout", value_name = "SECONDS")]
+    message_timeout: Option<f64>,
+
+    /// Ctrl+F finds text on screen by gui, independent of neovim's search.
+    #[clap(long = "find-on-screen")]
+    find_on_screen: bool,
+
+    /// Color of matches found on screen.
+    #[clap(long = "find-color", value_name = "COLOR", default_value = "rgba(255,200,0,0.4)")]
+    find_color: String,
+
+    /// Highlight trailing whitespace of visible lines by gui, independent of `list`.
+    /// Neovim matches it to `ReovimTrailingWhitespace` in every window.
+    /// Map a key to `reovim#toggle_trailing_whitespace()` to switch at runtime.
+    #[clap(long = "trailing-whitespace")]
+    trailing_whitespace: bool,
+
+    /// Color of trailing whitespace.
+    #[clap(
+        long = "trailing-whitespace-color",
+        value_name = "COLOR",
+        default_value = "rgba(255,80,80,0.35)"
+    )]
+    trailing_whitespace_color: String,
+
+    /// Draw characters of ligatures as separate glyphs, e.g. `==` and `->`.
+    /// Map a key to `reovim#toggle_ligatures()` to switch at runtime.
+    #[clap(long = "no-ligatures")]
+    no_ligatures: bool,
+
+    /// Animate scrolling of grids.
+    #[clap(long = "smooth-scroll")]
+    smooth_scroll: bool,
+
+    /// Lines scrolled per wheel step, touchpads scroll by fractions of it like `mousescroll`.
+    #[clap(
+        long = "scroll-multiplier",
+        value_name = "LINES",
+        default_value = "1.0"
+    )]
+    scroll_multiplier: f64,
+
+    /// Middle click pastes primary selection at cursor by `nvim_paste`, instead of a
+    /// middle mouse button sent to neovim.
+    #[clap(long = "middle-click-paste")]
+    middle_click_paste: bool,
+
+    /// Hide mouse pointer while typing, shown again once moved, like `mousehide` of vim.
+    #[clap(long = "mousehide")]
+    mousehide: bool,
+
+    /// Show scrollbar at right edge of windows while scrolling, fades out when idle.
+    /// `r` and `l` of `guioptions` keep scrollbars shown at right and left edges.
+    #[clap(long = "scrollbars")]
+    scrollbars: bool,
+
+    /// Background color used before colorscheme loaded, e.g. `#282c34`.
+    #[clap(long = "background", value_name = "COLOR")]
+    background: Option<String>,
+
+    /// Outline float windows by `FloatBorder` unless they have a `border` of their own.
+    #[clap(long = "float-borders")]
+    float_borders: bool,
+
+    /// Draw `colorcolumn` as thin vertical rules by gui, instead of cells painted by neovim.
+    #[clap(long = "colorcolumn-rule")]
+    colorcolumn_rule: bool,
+
+    /// Show clickable indicator while neovim waits at more-prompt.
+    #[clap(long = "more-prompt-indicator")]
+    more_prompt_indicator: bool,
+
+    /// Opacity of window at startup, change it by `reovim#set_opacity()` at runtime.
+    #[clap(long = "opacity", default_value = "1.0")]
+    opacity: f64,
+
+    /// Opacity of default background only, text stays opaque, e.g. `0.85`.
+    #[clap(
+        long = "background-opacity",
+        value_name = "ALPHA",
+        default_value = "1.0"
+    )]
+    background_opacity: f64,
+
+    /// PNG image painted beneath grids, scaled to cover the window.
+    /// Shows through backgrounds by `--background-opacity`.
+    #[clap(long = "background-image", value_name = "PATH")]
+    background_image: Option<std::path::PathBuf>,
+
+    /// Snap cell size to integer device pixels, font size will be adjusted slightly to fit.
+    #[clap(long = "integer-cell-size")]
+    integer_cell_size: bool,
+
+    /// Closing window sends `:qa` instead of `:qa!`, autocmds and plugins run before exit.
+    #[clap(long = "graceful-quit")]
+    graceful_quit: bool,
+
+    /// Milliseconds to wait before force quit with `--graceful-quit`, 0 never force.
+    #[clap(long = "quit-timeout", value_name = "MS", default_value_t = 3000)]
+    quit_timeout: u64,
+
+    /// Ask to save modified buffers by `:confirm qa` once `--quit-timeout` passed,
+    /// instead of force quit.
+    #[clap(long = "quit-confirm")]
+    quit_confirm: bool,
+
+    /// Force cursor shape of mode regardless of `guicursor`, repeatable.
+    /// e.g. `--cursor-shape all=block --cursor-shape insert=vertical:noblink`.
+    #[clap(long = "cursor-shape", value_name = "MODE=SHAPE[:blink|:noblink]")]
+    cursor_shapes: Vec<cursor::CursorOverride>,
+
+    /// Ring bells of neovim by beep or flash: audible, visual or none.
+    /// `audible` still flashes when `visualbell` is set.
+    #[clap(long = "bell", value_name = "BELL", default_value = "audible")]
+    bell: bell::Bell,
+
+    /// Draw cursor as a plain rectangle like terminals, no glyph redraw.
+    /// Map a key to `reovim#toggle_terminal_cursor()` to switch at runtime.
+    #[clap(long = "terminal-cursor")]
+    terminal_cursor: bool,
+
     /// A level of log, see: https://docs.rs/env_logger/latest/env_logger/#enabling-logging
     #[clap(short, long, value_name = "RUST_LOG", parse(from_occurrences))]
     verbose: i32,
@@ -65,10 +270,12 @@ pub struct Opts {
 
 impl Opts {
     fn connection_mode(&self) -> ConnectionMode {
-        if let Some(ref remote) = self.remote_tcp {
-            ConnectionMode::RemoteTcp(remote.to_owned())
-        } else {
-            ConnectionMode::Child
+        match self.server {
+            Some(ref address) if bridge::is_tcp_address(address) => {
+                ConnectionMode::RemoteTcp(address.to_owned())
+            }
+            Some(ref path) => ConnectionMode::RemoteSocket(path.into()),
+            None => ConnectionMode::Child,
         }
     }
 }
@@ -94,3 +301,30 @@ fn main() {
 
     relm.run_with_args(&[title]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_mode() {
+        let mode = |server: Option<&str>| {
+            let opts = Opts {
+                server: server.map(str::to_string),
+                ..Opts::default()
+            };
+            opts.connection_mode()
+        };
+        // never attaches to neovim running the terminal without `--server`.
+        std::env::set_var("NVIM", "/run/user/1000/nvim.1234.0");
+        assert_eq!(mode(None), ConnectionMode::Child);
+        assert_eq!(
+            mode(Some("127.0.0.1:6666")),
+            ConnectionMode::RemoteTcp("127.0.0.1:6666".to_string())
+        );
+        assert_eq!(
+            mode(Some("/run/user/1000/nvim.1234.0")),
+            ConnectionMode::RemoteSocket("/run/user/1000/nvim.1234.0".into())
+        );
+    }
+}
@@ -18,6 +18,7 @@ impl MessageHandler<crate::app::AppModel> for VimMessager {
 
     fn init(app_model: &crate::app::AppModel, parent_sender: Sender<AppMessage>) -> Self {
         let mut rx = EVENT_AGGREGATOR.register_event::<RedrawEvent>();
+        let mut app_rx = EVENT_AGGREGATOR.register_event::<AppMessage>();
         let sender = parent_sender.clone();
         let running_tracker = RUNNING_TRACKER.clone();
         app_model.rt.spawn(async move {
@@ -25,6 +26,10 @@ impl MessageHandler<crate::app::AppModel> for VimMessager {
                 tokio::select! {
                     _ = running_tracker.wait_quit() => {
                         log::info!("messager quit.");
+                        // render the last redraws, may show why neovim exited.
+                        while let Ok(event) = rx.try_recv() {
+                            sender.send(AppMessage::RedrawEvent(event)).ok();
+                        }
                         sender.send(AppMessage::Quit).unwrap();
                         // 保证最后一个退出, 避免其他task还在写,这里已经关闭,报错.
                         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
@@ -36,6 +41,12 @@ impl MessageHandler<crate::app::AppModel> for VimMessager {
                             .send(AppMessage::RedrawEvent(event))
                             .expect("Failed to send RedrawEvent to main thread");
                     },
+                    Some(message) = app_rx.recv() => {
+                        log::trace!("AppMessage {:?}", message);
+                        sender
+                            .send(message)
+                            .expect("Failed to send AppMessage to main thread");
+                    },
                     else => {
                         log::info!("messager None RedrawEvent event received, quit.");
                         sender.send(AppMessage::Quit).unwrap();
@@ -1,5 +1,5 @@
 // cellwidth: f64, charwidth: f64, charheight: f64
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Metrics {
     /// by pango glyph string extens -> logical width.
     charwidth: f64,
@@ -68,6 +68,7 @@ impl Metrics {
 
     pub fn set_linespace(&mut self, linespace: f64) {
         self.linespace = linespace;
+        self.height = self.charheight + linespace;
     }
 
     pub fn ascent(&self) -> f64 {
@@ -76,4 +77,179 @@ impl Metrics {
     pub fn set_ascent(&mut self, ascent: f64) {
         self.ascent = ascent;
     }
+
+    /// baseline from top of row, linespace split above and below text.
+    pub fn baseline(&self) -> f64 {
+        (self.linespace / 2.).floor() + self.ascent
+    }
+
+    /// snap cell size to integer device pixels under scale factor `scale`.
+    pub fn snap_to_device_pixels(&mut self, scale: f64) {
+        let snap = |v: f64| (v * scale).round().max(1.) / scale;
+        self.width = snap(self.width);
+        self.charwidth = snap(self.charwidth);
+        self.charheight = snap(self.charheight);
+        self.ascent = (self.ascent * scale).round() / scale;
+        self.height = snap(self.charheight + self.linespace);
+    }
+
+    /// x offset of column `col` in pango units.
+    /// derived from the column index, never by summing cell widths, so wide grids do not drift.
+    pub fn column_offset(&self, col: usize) -> i32 {
+        (col as f64 * self.width * pango::SCALE as f64).round() as i32
+    }
+
+    /// width of `cols` cells start from column `col` in pango units.
+    pub fn columns_width(&self, col: usize, cols: usize) -> i32 {
+        self.column_offset(col + cols) - self.column_offset(col)
+    }
+
+    pub fn cell_size(&self, scale_factor: f64, dpi: f64) -> CellSize {
+        CellSize {
+            width: self.width,
+            height: self.height,
+            scale_factor,
+            dpi,
+        }
+    }
+}
+
+/// Cell size in logical pixels exposed to neovim, for plugins doing pixel math.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellSize {
+    pub width: f64,
+    pub height: f64,
+    pub scale_factor: f64,
+    pub dpi: f64,
+}
+
+impl Default for CellSize {
+    fn default() -> CellSize {
+        Metrics::new().cell_size(1., 96.)
+    }
+}
+
+/// Size of window in cells, `COLSxROWS` on command line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    pub cols: u64,
+    pub rows: u64,
+}
+
+impl Geometry {
+    /// Size in pixels of cells by `metrics`.
+    pub fn pixels(&self, metrics: &Metrics) -> (i32, i32) {
+        (
+            (self.cols as f64 * metrics.width()).ceil() as i32,
+            (self.rows as f64 * metrics.height()).ceil() as i32,
+        )
+    }
+}
+
+impl std::str::FromStr for Geometry {
+    type Err = String;
+
+    /// `COLSxROWS`, e.g. `90x30`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (cols, rows) = s
+            .split_once(|c| c == 'x' || c == 'X')
+            .ok_or_else(|| format!("expect COLSxROWS, got '{}'", s))?;
+        let parse = |n: &str| match n.trim().parse::<u64>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("expect positive number, got '{}'", n)),
+        };
+        Ok(Geometry {
+            cols: parse(cols)?,
+            rows: parse(rows)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_grid_alignment() {
+        let mut metrics = Metrics::new();
+        for width in [8., 8.4, 9.333333, 7.75] {
+            metrics.set_width(width);
+            let mut x = 0;
+            for col in 0..1000 {
+                assert_eq!(x, metrics.column_offset(col));
+                x += metrics.columns_width(col, 1);
+            }
+            // tab stops of terminal are 8 columns.
+            for stop in (0..1000).step_by(8) {
+                let expected = (stop as f64 * width * pango::SCALE as f64).round() as i32;
+                assert_eq!(metrics.column_offset(stop), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_geometry() {
+        assert_eq!("90x30".parse(), Ok(Geometry { cols: 90, rows: 30 }));
+        assert_eq!(
+            "120X40".parse(),
+            Ok(Geometry {
+                cols: 120,
+                rows: 40
+            })
+        );
+        assert!("90".parse::<Geometry>().is_err());
+        assert!("0x30".parse::<Geometry>().is_err());
+        assert!("ax30".parse::<Geometry>().is_err());
+
+        let mut metrics = Metrics::new();
+        metrics.set_width(8.4);
+        metrics.set_charheight(17.);
+        metrics.set_linespace(2.);
+        let geometry = Geometry { cols: 90, rows: 30 };
+        assert_eq!(geometry.pixels(&metrics), (756, 570));
+    }
+
+    #[test]
+    fn test_linespace() {
+        let mut metrics = Metrics::new();
+        metrics.set_charheight(17.);
+        metrics.set_ascent(13.);
+        assert_eq!(metrics.height(), 17.);
+        assert_eq!(metrics.baseline(), 13.);
+
+        metrics.set_linespace(5.);
+        assert_eq!(metrics.height(), 22.);
+        assert_eq!(metrics.baseline(), 15.);
+
+        // new font keeps linespace.
+        metrics.set_charheight(20.);
+        assert_eq!(metrics.height(), 25.);
+
+        metrics.set_linespace(0.);
+        assert_eq!(metrics.height(), 20.);
+        assert_eq!(metrics.baseline(), 13.);
+    }
+
+    #[test]
+    fn test_snap_to_device_pixels() {
+        for scale in [1., 1.25, 1.5, 1.75, 2., 3.] {
+            let mut metrics = Metrics::new();
+            metrics.set_width(8.4);
+            metrics.set_charwidth(8.4);
+            metrics.set_linespace(1.3);
+            metrics.set_charheight(17.2);
+            metrics.set_ascent(13.6);
+            metrics.snap_to_device_pixels(scale);
+            for v in [
+                metrics.width(),
+                metrics.charwidth(),
+                metrics.charheight(),
+                metrics.height(),
+            ] {
+                let device = v * scale;
+                assert!((device - device.round()).abs() < 1e-9, "{} x {}", v, scale);
+                assert!(device >= 1.);
+            }
+        }
+    }
 }
@@ -0,0 +1,175 @@
+use crate::bridge::PopupMenuItem;
+
+/// Rows of window taken by popup menu, `rows` from `row`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Placement {
+    pub row: usize,
+    pub rows: usize,
+}
+
+/// Completion candidates from `popupmenu_show`, anchored at the completed word.
+#[derive(Clone, Debug, Default)]
+pub struct PopupMenu {
+    items: Vec<PopupMenuItem>,
+    selected: Option<usize>,
+    row: usize,
+    col: usize,
+    // first item shown, menu scrolls with selection.
+    offset: usize,
+}
+
+impl PopupMenu {
+    pub fn show(
+        &mut self,
+        items: Vec<PopupMenuItem>,
+        selected: Option<usize>,
+        row: usize,
+        col: usize,
+    ) {
+        self.items = items;
+        self.row = row;
+        self.col = col;
+        self.offset = 0;
+        self.select(selected);
+    }
+
+    pub fn select(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|selected| *selected < self.items.len());
+    }
+
+    pub fn hide(&mut self) {
+        *self = PopupMenu::default();
+    }
+
+    pub fn items(&self) -> &[PopupMenuItem] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Column of the completed word.
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Placement in window of `total` rows, below the completed word, or above it
+    /// when there is more room, clamped to window.
+    pub fn placement(&self, total: usize) -> Placement {
+        let items = self.items.len();
+        let above = self.row.min(total);
+        let below = total.saturating_sub(self.row + 1);
+        if items <= below || below >= above {
+            Placement {
+                row: self.row + 1,
+                rows: items.min(below),
+            }
+        } else {
+            let rows = items.min(above);
+            Placement {
+                row: above - rows,
+                rows,
+            }
+        }
+    }
+
+    /// Scroll to keep the selected item in `rows` shown.
+    pub fn scroll(&mut self, rows: usize) {
+        if let Some(selected) = self.selected {
+            if selected < self.offset {
+                self.offset = selected;
+            } else if rows > 0 && selected >= self.offset + rows {
+                self.offset = selected + 1 - rows;
+            }
+        }
+        self.offset = self.offset.min(self.items.len().saturating_sub(rows));
+    }
+
+    /// Items shown in `rows` with their index.
+    pub fn visible(&self, rows: usize) -> impl Iterator<Item = (usize, &PopupMenuItem)> {
+        self.items.iter().enumerate().skip(self.offset).take(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu(count: usize, selected: Option<usize>, row: usize) -> PopupMenu {
+        let items = (0..count)
+            .map(|n| PopupMenuItem {
+                word: format!("word{}", n),
+                ..PopupMenuItem::default()
+            })
+            .collect();
+        let mut menu = PopupMenu::default();
+        menu.show(items, selected, row, 4);
+        menu
+    }
+
+    #[test]
+    fn test_placement() {
+        // enough room below.
+        assert_eq!(
+            menu(5, None, 2).placement(30),
+            Placement { row: 3, rows: 5 }
+        );
+        // more room above, opens upward.
+        assert_eq!(
+            menu(10, None, 25).placement(30),
+            Placement { row: 15, rows: 10 }
+        );
+        // too many items, clamped to window.
+        assert_eq!(
+            menu(100, None, 4).placement(30),
+            Placement { row: 5, rows: 25 }
+        );
+        assert_eq!(
+            menu(100, None, 20).placement(30),
+            Placement { row: 0, rows: 20 }
+        );
+        assert_eq!(
+            menu(3, None, 29).placement(30),
+            Placement { row: 26, rows: 3 }
+        );
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut pum = menu(20, None, 0);
+        pum.scroll(5);
+        assert_eq!(pum.offset(), 0);
+
+        pum.select(Some(7));
+        pum.scroll(5);
+        assert_eq!(pum.offset(), 3);
+        let shown: Vec<usize> = pum.visible(5).map(|(index, _)| index).collect();
+        assert_eq!(shown, vec![3, 4, 5, 6, 7]);
+
+        pum.select(Some(1));
+        pum.scroll(5);
+        assert_eq!(pum.offset(), 1);
+
+        // selection wraps to the last item.
+        pum.select(Some(19));
+        pum.scroll(5);
+        assert_eq!(pum.offset(), 15);
+
+        // selection reset keeps position.
+        pum.select(None);
+        pum.scroll(5);
+        assert_eq!(pum.offset(), 15);
+
+        // out of range is no selection.
+        pum.select(Some(20));
+        assert_eq!(pum.selected(), None);
+
+        pum.hide();
+        assert!(pum.items().is_empty());
+    }
+}
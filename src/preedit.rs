@@ -0,0 +1,96 @@
+use gtk::gdk;
+
+/// Text composed by input method, drawn at cursor until committed.
+#[derive(Debug, Default)]
+pub struct Preedit {
+    text: String,
+    attrs: Option<pango::AttrList>,
+    // cursor position in chars of text.
+    cursor: i32,
+}
+
+impl Preedit {
+    pub fn new(text: &str, attrs: pango::AttrList, cursor: i32) -> Preedit {
+        Preedit {
+            text: text.to_string(),
+            attrs: Some(attrs),
+            cursor,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Byte index of cursor in text, input method counts it by chars.
+    pub fn cursor_index(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor.max(0) as usize)
+            .map_or(self.text.len(), |(index, _)| index)
+    }
+
+    /// Draw text over background by attributes of input method at `x` and `y`,
+    /// `height` of cell keeps it in the cursor row.
+    pub fn draw(
+        &self,
+        cr: &cairo::Context,
+        pctx: &pango::Context,
+        font: &pango::FontDescription,
+        (x, y, height): (f64, f64, f64),
+        foreground: &gdk::RGBA,
+        background: &gdk::RGBA,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        let layout = pango::Layout::new(pctx);
+        layout.set_font_description(Some(font));
+        layout.set_text(&self.text);
+        layout.set_attributes(self.attrs.as_ref());
+        let (width, text_height) = layout.pixel_size();
+        let top = y + (height - text_height as f64) / 2.;
+
+        let set_source = |color: &gdk::RGBA| {
+            cr.set_source_rgba(
+                color.red() as _,
+                color.green() as _,
+                color.blue() as _,
+                color.alpha() as _,
+            );
+        };
+        set_source(background);
+        cr.rectangle(x, y, width as f64, height);
+        cr.fill().ok();
+
+        set_source(foreground);
+        cr.move_to(x, top);
+        pangocairo::update_layout(cr, &layout);
+        pangocairo::show_layout(cr, &layout);
+
+        // cursor of composing text, a bar before char under it.
+        let pos = layout.index_to_pos(self.cursor_index() as i32);
+        let left = x + pos.x() as f64 / pango::SCALE as f64;
+        cr.rectangle(left, y, 1., height);
+        cr.fill().ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_index() {
+        let preedit = Preedit::new("ni'hao", pango::AttrList::new(), 2);
+        assert_eq!(preedit.cursor_index(), 2);
+        let preedit = Preedit::new("你好", pango::AttrList::new(), 1);
+        assert_eq!(preedit.cursor_index(), "你".len());
+        // at end of text.
+        let preedit = Preedit::new("你好", pango::AttrList::new(), 2);
+        assert_eq!(preedit.cursor_index(), "你好".len());
+        let preedit = Preedit::new("ni", pango::AttrList::new(), -1);
+        assert_eq!(preedit.cursor_index(), 0);
+        assert!(Preedit::default().is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use std::cell::Cell;
+
+/// Widgets drawn by gui outside of the grid factory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Cursor,
+    Background,
+    MessageGrid,
+}
+
+impl Target {
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Redraws requested by events between two flushes.
+///
+/// Neovim expects the screen drawn at flush only, each target is drawn once
+/// after flush however many events asked for it.
+#[derive(Debug, Default)]
+pub struct RedrawScheduler {
+    pending: Cell<u8>,
+    ready: Cell<u8>,
+}
+
+impl RedrawScheduler {
+    pub fn request(&self, target: Target) {
+        self.pending.set(self.pending.get() | target.bit());
+    }
+
+    /// Requests so far are drawn by next view.
+    pub fn flush(&self) {
+        self.ready.set(self.ready.get() | self.pending.replace(0));
+    }
+
+    /// Whether `target` is due to draw, it is drawn only once.
+    pub fn take(&self, target: Target) -> bool {
+        let ready = self.ready.get();
+        self.ready.set(ready & !target.bit());
+        ready & target.bit() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coalesce() {
+        let redraw = RedrawScheduler::default();
+        redraw.request(Target::Cursor);
+        redraw.request(Target::Cursor);
+        // not flushed yet.
+        assert!(!redraw.take(Target::Cursor));
+
+        redraw.flush();
+        assert!(redraw.take(Target::Cursor));
+        assert!(!redraw.take(Target::Cursor));
+        assert!(!redraw.take(Target::Background));
+    }
+
+    #[test]
+    fn test_targets() {
+        let redraw = RedrawScheduler::default();
+        redraw.request(Target::Background);
+        redraw.flush();
+        redraw.request(Target::MessageGrid);
+        redraw.flush();
+        assert!(redraw.take(Target::MessageGrid));
+        assert!(redraw.take(Target::Background));
+        assert!(!redraw.take(Target::Cursor));
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::{atomic, Arc};
+use std::time::{Duration, Instant};
 
 use once_cell::sync::Lazy;
 
@@ -41,3 +42,58 @@ impl RunningTracker {
         self.notify.notified().await
     }
 }
+
+/// How long the last error stays on screen before the window closes.
+pub const ERROR_LINGER: Duration = Duration::from_millis(1500);
+
+/// Close window after neovim quit, but keep an error shown right before
+/// visible for a while, otherwise the cause of exit is never seen.
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    error_at: Option<Instant>,
+    quitting: bool,
+}
+
+impl Shutdown {
+    pub fn error_shown(&mut self, now: Instant) {
+        self.error_at.replace(now);
+    }
+
+    /// Time to wait before closing window at `now`, `None` to close right away.
+    /// Only the first quit waits, the deferred quit closes.
+    pub fn quit(&mut self, now: Instant) -> Option<Duration> {
+        if std::mem::replace(&mut self.quitting, true) {
+            return None;
+        }
+        let shown = now.saturating_duration_since(self.error_at?);
+        ERROR_LINGER
+            .checked_sub(shown)
+            .filter(|wait| !wait.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_before_quit() {
+        let now = Instant::now();
+        let mut shutdown = Shutdown::default();
+        shutdown.error_shown(now);
+        let wait = shutdown.quit(now + Duration::from_millis(10));
+        assert_eq!(wait, Some(ERROR_LINGER - Duration::from_millis(10)));
+        // deferred quit closes.
+        assert_eq!(shutdown.quit(now + ERROR_LINGER), None);
+    }
+
+    #[test]
+    fn test_quit_without_error() {
+        let now = Instant::now();
+        assert_eq!(Shutdown::default().quit(now), None);
+        // error long ago already seen.
+        let mut shutdown = Shutdown::default();
+        shutdown.error_shown(now);
+        assert_eq!(shutdown.quit(now + ERROR_LINGER * 2), None);
+    }
+}
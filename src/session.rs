@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gtk::prelude::*;
+
+/// Size of main window kept between sessions, by `--remember-window-size`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowState {
+    pub width: i32,
+    pub height: i32,
+    pub maximized: bool,
+}
+
+impl WindowState {
+    /// Lines of `key=value`, `None` if any of them missing or invalid.
+    pub fn parse(text: &str) -> Option<WindowState> {
+        let (mut width, mut height, mut maximized) = (None, None, None);
+        for line in text.lines() {
+            match line.trim().split_once('=') {
+                Some(("width", value)) => width = value.parse().ok().filter(|w| *w > 0),
+                Some(("height", value)) => height = value.parse().ok().filter(|h| *h > 0),
+                Some(("maximized", value)) => maximized = value.parse().ok(),
+                _ => {}
+            }
+        }
+        Some(WindowState {
+            width: width?,
+            height: height?,
+            maximized: maximized?,
+        })
+    }
+
+    /// State of `window` to restore, unmaximized size while maximized.
+    pub fn of(window: &impl IsA<gtk::Window>) -> WindowState {
+        // default size follows resizing.
+        let (width, height) = window.default_size();
+        WindowState {
+            width,
+            height,
+            maximized: window.is_maximized(),
+        }
+    }
+
+    pub fn format(&self) -> String {
+        format!(
+            "width={}\nheight={}\nmaximized={}\n",
+            self.width, self.height, self.maximized
+        )
+    }
+
+    /// State of last session, falls back to defaults if missing or corrupt.
+    pub fn load() -> Option<WindowState> {
+        let path = state_file()?;
+        Self::load_from(&path)
+    }
+
+    pub fn save(&self) {
+        let path = match state_file() {
+            Some(path) => path,
+            None => return,
+        };
+        if let Err(err) = self.save_to(&path) {
+            log::warn!("could not save window state to {}: {}", path.display(), err);
+        }
+    }
+
+    fn load_from(path: &Path) -> Option<WindowState> {
+        let text = fs::read_to_string(path).ok()?;
+        let state = WindowState::parse(&text);
+        if state.is_none() {
+            log::warn!("ignored corrupt window state {}", path.display());
+        }
+        state
+    }
+
+    fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.format())
+    }
+}
+
+/// `$XDG_STATE_HOME/reovim/window`, or `~/.local/state/reovim/window`.
+fn state_file() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })?;
+    Some(dir.join("reovim").join("window"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE: WindowState = WindowState {
+        width: 1024,
+        height: 768,
+        maximized: true,
+    };
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(WindowState::parse(&STATE.format()), Some(STATE));
+        assert_eq!(
+            WindowState::parse("maximized=false\n width=80\nheight=60\nunknown=1"),
+            Some(WindowState {
+                width: 80,
+                height: 60,
+                maximized: false
+            })
+        );
+        assert_eq!(WindowState::parse(""), None);
+        assert_eq!(WindowState::parse("width=80\nheight=60"), None);
+        assert_eq!(
+            WindowState::parse("width=-1\nheight=60\nmaximized=false"),
+            None
+        );
+        assert_eq!(
+            WindowState::parse("width=a\nheight=60\nmaximized=false"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_save_load() {
+        let dir = std::env::temp_dir().join(format!("reovim-session-{}", std::process::id()));
+        let path = dir.join("reovim").join("window");
+        assert_eq!(WindowState::load_from(&path), None);
+
+        STATE.save_to(&path).unwrap();
+        assert_eq!(WindowState::load_from(&path), Some(STATE));
+
+        fs::write(&path, "garbage").unwrap();
+        assert_eq!(WindowState::load_from(&path), None);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
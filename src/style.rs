@@ -16,6 +16,12 @@ pub struct Style {
     #[new(default)]
     pub undercurl: bool,
     #[new(default)]
+    pub underdouble: bool,
+    #[new(default)]
+    pub underdotted: bool,
+    #[new(default)]
+    pub underdashed: bool,
+    #[new(default)]
     pub blend: u8,
 }
 
@@ -40,6 +46,25 @@ impl Style {
         }
     }
 
+    /// Opacity by `blend`, 0 is opaque and 100 fully transparent.
+    pub fn alpha(&self) -> f32 {
+        (100 - self.blend.min(100)) as f32 / 100.
+    }
+
+    /// Background painted under cells, `None` leaves the default background.
+    ///
+    /// Reversed highlight always paints, by the default foreground if it has none.
+    /// The default background itself is never painted again by cells, it is
+    /// translucent by `--background-opacity`.
+    pub fn resolved_background(&self, default_colors: &Colors) -> Option<Color> {
+        let background = if self.reverse {
+            self.colors.foreground.or(default_colors.foreground)
+        } else {
+            self.colors.background
+        };
+        background.filter(|color| Some(*color) != default_colors.background)
+    }
+
     pub fn special(&self, default_colors: &Colors) -> Color {
         self.colors
             .special
@@ -114,6 +139,53 @@ mod tests {
         assert_eq!(style.background(), None);
     }
 
+    #[test]
+    fn test_resolved_background() {
+        let mut style = Style::new(COLORS);
+
+        assert_eq!(
+            style.resolved_background(&DEFAULT_COLORS),
+            COLORS.background
+        );
+        style.colors.background = None;
+        assert_eq!(style.resolved_background(&DEFAULT_COLORS), None);
+
+        style.reverse = true;
+        assert_eq!(
+            style.resolved_background(&DEFAULT_COLORS),
+            COLORS.foreground
+        );
+        style.colors.foreground = None;
+        assert_eq!(
+            style.resolved_background(&DEFAULT_COLORS),
+            DEFAULT_COLORS.foreground
+        );
+        // explicit background becomes foreground.
+        style.colors.background = COLORS.background;
+        assert_eq!(
+            style.foreground(&DEFAULT_COLORS),
+            COLORS.background.unwrap()
+        );
+
+        // same as default background, left to it.
+        let mut style = Style::new(COLORS);
+        style.colors.background = DEFAULT_COLORS.background;
+        assert_eq!(style.resolved_background(&DEFAULT_COLORS), None);
+        style.reverse = true;
+        style.colors.foreground = DEFAULT_COLORS.background;
+        assert_eq!(style.resolved_background(&DEFAULT_COLORS), None);
+    }
+
+    #[test]
+    fn test_alpha() {
+        let mut style = Style::new(COLORS);
+        assert_eq!(style.alpha(), 1.);
+        style.blend = 30;
+        assert_eq!(style.alpha(), 0.7);
+        style.blend = 100;
+        assert_eq!(style.alpha(), 0.);
+    }
+
     #[test]
     fn test_special() {
         let mut style = Style::new(COLORS);
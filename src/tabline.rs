@@ -0,0 +1,107 @@
+use nvim::Value;
+
+use crate::bridge::Tab;
+
+/// Tab pages from `tabline_update`.
+#[derive(Clone, Debug, Default)]
+pub struct Tabline {
+    tabs: Vec<Tab>,
+    current: Option<usize>,
+}
+
+impl Tabline {
+    pub fn update(&mut self, current: &Value, tabs: Vec<Tab>) {
+        self.current = tabs.iter().position(|tab| &tab.tab == current);
+        self.tabs = tabs;
+    }
+
+    pub fn tabs(&self) -> &[Tab] {
+        &self.tabs
+    }
+
+    /// Index of the current tab page.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Shown by `showtabline`, 0 never, 1 only if more than one tab page, 2 always.
+    pub fn is_visible(&self, showtabline: u64) -> bool {
+        match showtabline {
+            0 => false,
+            1 => self.tabs.len() > 1,
+            _ => true,
+        }
+    }
+
+    /// Index of tab under `x`, tabs are laid out from left by `widths`.
+    pub fn tab_at(widths: &[f64], x: f64) -> Option<usize> {
+        let mut right = 0.;
+        widths.iter().position(|width| {
+            right += width;
+            x < right
+        })
+    }
+}
+
+/// Title of tab page, file name of its current buffer.
+pub fn title(name: &str) -> &str {
+    match name.rsplit(std::path::is_separator).next() {
+        Some(title) if !title.is_empty() => title,
+        _ => "[No Name]",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tabs(count: i64) -> Vec<Tab> {
+        (1..=count)
+            .map(|n| Tab {
+                tab: Value::from(n),
+                name: format!("src/file{}.rs", n),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tabline() {
+        let mut tabline = Tabline::default();
+        assert!(!tabline.is_visible(1));
+        assert!(tabline.is_visible(2));
+
+        tabline.update(&Value::from(1), tabs(1));
+        assert_eq!(tabline.current(), Some(0));
+        assert!(!tabline.is_visible(0));
+        assert!(!tabline.is_visible(1));
+        assert!(tabline.is_visible(2));
+
+        tabline.update(&Value::from(3), tabs(3));
+        assert_eq!(tabline.current(), Some(2));
+        assert_eq!(tabline.tabs().len(), 3);
+        assert!(!tabline.is_visible(0));
+        assert!(tabline.is_visible(1));
+
+        tabline.update(&Value::from(9), tabs(3));
+        assert_eq!(tabline.current(), None);
+    }
+
+    #[test]
+    fn test_tab_at() {
+        let widths = [40., 60., 50.];
+        assert_eq!(Tabline::tab_at(&widths, 0.), Some(0));
+        assert_eq!(Tabline::tab_at(&widths, 39.5), Some(0));
+        assert_eq!(Tabline::tab_at(&widths, 40.), Some(1));
+        assert_eq!(Tabline::tab_at(&widths, 149.), Some(2));
+        assert_eq!(Tabline::tab_at(&widths, 150.), None);
+        assert_eq!(Tabline::tab_at(&[], 10.), None);
+    }
+
+    #[test]
+    fn test_title() {
+        assert_eq!(title("src/main.rs"), "main.rs");
+        assert_eq!(title("README.md"), "README.md");
+        assert_eq!(title(""), "[No Name]");
+        assert_eq!(title("src/"), "[No Name]");
+    }
+}
@@ -0,0 +1,113 @@
+use super::TextCell;
+
+/// Pixels a background fill extends into the next cell, covers seams of
+/// antialiased edges between adjacent highlighted cells.
+pub const BLEED: f64 = 0.5;
+
+/// Background fill of `len` cells from `col` highlighted by `hldef`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fill {
+    pub col: usize,
+    pub len: usize,
+    pub hldef: u64,
+    /// extend right edge by `BLEED`, the next cell starts another background.
+    pub bleed: bool,
+}
+
+/// Runs of cells sharing the same highlight, cells without highlight are left to
+/// the default background.
+///
+/// Never bleed into trailing half of a wide glyph, the glyph covers the boundary.
+pub fn fills(cells: &[TextCell]) -> Vec<Fill> {
+    let mut fills: Vec<Fill> = Vec::new();
+    for (col, cell) in cells.iter().enumerate() {
+        let hldef = match cell.hldef {
+            Some(hldef) => hldef,
+            None => continue,
+        };
+        match fills.last_mut() {
+            Some(fill) if fill.hldef == hldef && fill.col + fill.len == col => fill.len += 1,
+            _ => fills.push(Fill {
+                col,
+                len: 1,
+                hldef,
+                bleed: false,
+            }),
+        }
+    }
+    for fill in fills.iter_mut() {
+        let next = fill.col + fill.len;
+        fill.bleed = cells
+            .get(next)
+            .map_or(false, |cell| cell.hldef.is_some() && !cell.text.is_empty());
+    }
+    fills
+}
+
+/// Region of `fill` in row `row`, `(x, y, width, height)` in pixel.
+pub fn region(fill: &Fill, row: usize, cell_width: f64, cell_height: f64) -> (f64, f64, f64, f64) {
+    let bleed = if fill.bleed { BLEED } else { 0. };
+    (
+        fill.col as f64 * cell_width,
+        row as f64 * cell_height,
+        fill.len as f64 * cell_width + bleed,
+        cell_height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing::cell;
+
+    fn spans(fills: &[Fill]) -> Vec<(usize, usize, u64, bool)> {
+        fills
+            .iter()
+            .map(|fill| (fill.col, fill.len, fill.hldef, fill.bleed))
+            .collect()
+    }
+
+    #[test]
+    fn test_seams_covered() {
+        // powerline statusline: ` N  m `
+        let cells = vec![
+            cell(" ", Some(1)),
+            cell("N", Some(1)),
+            cell(" ", Some(1)),
+            cell("\u{e0b0}", Some(2)),
+            cell(" ", Some(3)),
+            cell("m", Some(3)),
+            cell(" ", None),
+        ];
+        let fills = fills(&cells);
+        assert_eq!(
+            spans(&fills),
+            vec![(0, 3, 1, true), (3, 1, 2, true), (4, 2, 3, false)]
+        );
+        // adjacent fills overlap, no gap left between them.
+        let regions: Vec<_> = fills.iter().map(|fill| region(fill, 1, 7.5, 16.)).collect();
+        for (left, right) in regions.iter().zip(regions.iter().skip(1)) {
+            assert!(left.0 + left.2 > right.0);
+        }
+        assert_eq!(regions[2], (30., 16., 15., 16.));
+    }
+
+    #[test]
+    fn test_trailing_blanks_filled() {
+        // cursorline past end of line, one fill to the window edge.
+        let mut cells = vec![cell("f", Some(7)), cell("n", Some(7))];
+        cells.extend((0..6).map(|_| cell(" ", Some(7))));
+        assert_eq!(spans(&fills(&cells)), vec![(0, 8, 7, false)]);
+    }
+
+    #[test]
+    fn test_no_bleed_into_wide_glyph() {
+        let cells = vec![cell("a", Some(1)), cell("中", Some(2)), cell("", Some(2))];
+        assert_eq!(
+            spans(&fills(&cells)),
+            vec![(0, 1, 1, true), (1, 2, 2, false)]
+        );
+        let cells = vec![cell("中", Some(1)), cell("", Some(2))];
+        assert!(!fills(&cells)[0].bleed);
+    }
+}
@@ -0,0 +1,68 @@
+/// highlight group of `colorcolumn`.
+pub const COLORCOLUMN_HLGROUP: &str = "ColorColumn";
+
+/// Columns (1-based, of text) from value of `colorcolumn`,
+/// items like `+1` or `-1` are relative to `textwidth`, ignored without it.
+fn parse(colorcolumn: &str, textwidth: u64) -> Vec<usize> {
+    let mut columns: Vec<usize> = colorcolumn
+        .split(',')
+        .map(str::trim)
+        .filter_map(|item| {
+            let col = match item.as_bytes().first()? {
+                b'+' | b'-' if textwidth == 0 => return None,
+                b'+' => textwidth as i64 + item[1..].parse::<i64>().ok()?,
+                b'-' => textwidth as i64 - item[1..].parse::<i64>().ok()?,
+                _ => item.parse::<i64>().ok()?,
+            };
+            (col > 0).then(|| col as usize)
+        })
+        .collect();
+    columns.sort_unstable();
+    columns.dedup();
+    columns
+}
+
+/// Grid columns (0-based) of `colorcolumn` for a window,
+/// `textoff` is width of gutter and `leftcol` is the first visible text column.
+pub fn colorcolumns(colorcolumn: &str, textwidth: u64, textoff: u64, leftcol: u64) -> Vec<usize> {
+    parse(colorcolumn, textwidth)
+        .into_iter()
+        .filter(|col| *col as u64 > leftcol)
+        .map(|col| (col as u64 - 1 - leftcol + textoff) as usize)
+        .collect()
+}
+
+/// x of the rule at left edge of grid column `col`, snapped to whole pixel.
+pub fn rule_x(col: usize, cell_width: f64) -> f64 {
+    (col as f64 * cell_width).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("", 0), Vec::<usize>::new());
+        assert_eq!(parse("80,120", 0), vec![80, 120]);
+        assert_eq!(parse("+1", 0), Vec::<usize>::new());
+        assert_eq!(parse("+1,-2,80", 78), vec![76, 79, 80]);
+        assert_eq!(parse("0,abc", 0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_colorcolumns() {
+        // 4 cells of number column.
+        assert_eq!(colorcolumns("81", 0, 4, 0), vec![84]);
+        // scrolled horizontally.
+        assert_eq!(colorcolumns("81,10", 0, 4, 20), vec![64]);
+    }
+
+    #[test]
+    fn test_rule_x() {
+        assert_eq!(rule_x(0, 8.4), 0.);
+        assert_eq!(rule_x(80, 8.), 640.);
+        assert_eq!(rule_x(80, 8.4), 672.);
+        assert_eq!(rule_x(3, 8.4), 25.);
+    }
+}
@@ -0,0 +1,48 @@
+/// The default grid, holds cmdline and messages with multigrid.
+const DEFAULT_GRID: u64 = 1;
+
+/// Window grid owning focus after cursor moved to `cursor_grid`,
+/// cmdline on the default grid keeps focus of the previous window.
+pub fn focused_grid(focused: u64, cursor_grid: u64) -> u64 {
+    if cursor_grid == DEFAULT_GRID {
+        focused
+    } else {
+        cursor_grid
+    }
+}
+
+/// Whether `grid` is dimmed while `focused` owns focus,
+/// floats and the default grid are never dimmed.
+pub fn is_dimmed(grid: u64, focused: u64, is_float: bool) -> bool {
+    grid != focused && grid != DEFAULT_GRID && !is_float && focused != DEFAULT_GRID
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimmed_grids() {
+        // two splits and a float, cursor in grid 2.
+        let focused = focused_grid(0, 2);
+        assert!(!is_dimmed(2, focused, false));
+        assert!(is_dimmed(4, focused, false));
+        assert!(!is_dimmed(5, focused, true));
+        assert!(!is_dimmed(DEFAULT_GRID, focused, false));
+
+        // entering cmdline keeps focus.
+        let focused = focused_grid(focused, DEFAULT_GRID);
+        assert_eq!(focused, 2);
+        assert!(is_dimmed(4, focused, false));
+
+        // focus moved to the other split.
+        let focused = focused_grid(focused, 4);
+        assert!(is_dimmed(2, focused, false));
+        assert!(!is_dimmed(4, focused, false));
+
+        // single grid without multigrid, nothing to dim.
+        let focused = focused_grid(0, DEFAULT_GRID);
+        assert!(!is_dimmed(DEFAULT_GRID, focused, false));
+        assert!(!is_dimmed(3, focused, false));
+    }
+}
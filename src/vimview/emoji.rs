@@ -0,0 +1,139 @@
+/// Joins emoji into sequence with the next character.
+const ZWJ: char = '\u{200d}';
+/// Selects emoji presentation of the preceding character.
+const VS16: char = '\u{fe0f}';
+
+/// Characters belong to the preceding one, variation selectors, skin tones,
+/// combining marks, keycaps and tags.
+fn is_extending(c: char) -> bool {
+    matches!(c,
+        ZWJ
+        | '\u{fe00}'..='\u{fe0f}'
+        | '\u{1f3fb}'..='\u{1f3ff}'
+        | '\u{0300}'..='\u{036f}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{e0020}'..='\u{e007f}')
+}
+
+/// Pictographs shown as emoji when `emoji` is set.
+fn is_pictographic(c: char) -> bool {
+    matches!(c,
+        '\u{2600}'..='\u{27bf}'
+        | '\u{2b00}'..='\u{2bff}'
+        | '\u{1f000}'..='\u{1faff}')
+}
+
+/// Splits `text` to characters neovim puts in one cell, emoji sequences stay together.
+pub fn graphemes(text: &str) -> Vec<&str> {
+    let mut graphemes = Vec::new();
+    let mut start = 0;
+    let mut joined = false;
+    for (index, c) in text.char_indices() {
+        if index > 0 && !joined && !is_extending(c) {
+            graphemes.push(&text[start..index]);
+            start = index;
+        }
+        joined = c == ZWJ;
+    }
+    if start < text.len() {
+        graphemes.push(&text[start..]);
+    }
+    graphemes
+}
+
+/// Full width under `emoji`, pictographs and sequences of them.
+pub fn is_emoji(grapheme: &str) -> bool {
+    let mut chars = grapheme.chars();
+    chars.next().map_or(false, is_pictographic) || chars.any(|c| c == VS16 || c == ZWJ)
+}
+
+/// Position of each glyph in its cluster and glyphs of the cluster, `None` for
+/// glyphs without advance, marks stacked on others.
+///
+/// Fonts without a ligature shape emoji sequence to glyphs sharing one cluster,
+/// they share its cells too.
+pub fn glyph_clusters(log_clusters: &[i32], advances: &[i32]) -> Vec<Option<(usize, usize)>> {
+    let mut clusters = vec![None; log_clusters.len()];
+    let mut start = 0;
+    while start < log_clusters.len() {
+        let end = log_clusters[start..]
+            .iter()
+            .position(|cluster| *cluster != log_clusters[start])
+            .map_or(log_clusters.len(), |len| start + len);
+        let count = advances[start..end].iter().filter(|a| **a > 0).count();
+        let mut nth = 0;
+        for index in start..end {
+            if advances[index] > 0 {
+                clusters[index] = Some((nth, count));
+                nth += 1;
+            }
+        }
+        start = end;
+    }
+    clusters
+}
+
+/// Width of the `nth` of `count` glyphs sharing `width`, the last takes the rest.
+pub fn share_width(width: i32, nth: usize, count: usize) -> i32 {
+    let each = width / count as i32;
+    if nth + 1 == count {
+        width - each * nth as i32
+    } else {
+        each
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphemes() {
+        assert_eq!(graphemes("ab"), vec!["a", "b"]);
+        assert_eq!(graphemes(""), Vec::<&str>::new());
+        // family by zero width joiner.
+        assert_eq!(
+            graphemes("a👨\u{200d}👩\u{200d}👧b"),
+            vec!["a", "👨\u{200d}👩\u{200d}👧", "b"]
+        );
+        assert_eq!(graphemes("☺\u{fe0f}x"), vec!["☺\u{fe0f}", "x"]);
+        assert_eq!(graphemes("👍🏽👍"), vec!["👍🏽", "👍"]);
+        assert_eq!(graphemes("e\u{301}"), vec!["e\u{301}"]);
+    }
+
+    #[test]
+    fn test_is_emoji() {
+        assert!(is_emoji("👍"));
+        assert!(is_emoji("☺"));
+        assert!(is_emoji("#\u{fe0f}\u{20e3}"));
+        assert!(is_emoji("👨\u{200d}👩\u{200d}👧"));
+        assert!(!is_emoji("a"));
+        assert!(!is_emoji("中"));
+        assert!(!is_emoji(""));
+    }
+
+    #[test]
+    fn test_glyph_clusters() {
+        // family shaped to three glyphs and a mark without advance.
+        let clusters = glyph_clusters(&[0, 1, 1, 1, 1, 12], &[10, 20, 20, 0, 20, 10]);
+        assert_eq!(
+            clusters,
+            vec![
+                Some((0, 1)),
+                Some((0, 3)),
+                Some((1, 3)),
+                None,
+                Some((2, 3)),
+                Some((0, 1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_share_width() {
+        assert_eq!(share_width(20, 0, 1), 20);
+        assert_eq!(share_width(20, 0, 3), 6);
+        assert_eq!(share_width(20, 1, 3), 6);
+        assert_eq!(share_width(20, 2, 3), 8);
+    }
+}
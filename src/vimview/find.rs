@@ -0,0 +1,96 @@
+use super::TextCell;
+
+/// A match on screen, `len` cells start from `col` of `row`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Match {
+    pub row: usize,
+    pub col: usize,
+    pub len: usize,
+}
+
+/// Find `pattern` in visible content of grid, smart case like vim.
+///
+/// Trailing halves of wide cells have empty text, they are counted into matches
+/// but never start one.
+pub fn find(rows: &[&[TextCell]], pattern: &str) -> Vec<Match> {
+    let mut matches = Vec::new();
+    if pattern.is_empty() {
+        return matches;
+    }
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    let pattern = if ignore_case {
+        pattern.to_lowercase()
+    } else {
+        pattern.to_string()
+    };
+
+    for (row, cells) in rows.iter().enumerate() {
+        let mut text = String::new();
+        // byte offset of text to column.
+        let mut columns = Vec::with_capacity(cells.len());
+        for (col, cell) in cells.iter().enumerate() {
+            let celltext = if ignore_case {
+                cell.text.to_lowercase()
+            } else {
+                cell.text.to_string()
+            };
+            columns.push((text.len(), col));
+            text.push_str(&celltext);
+        }
+        let column = |offset: usize| {
+            columns
+                .iter()
+                .rev()
+                .find(|(start, _)| *start <= offset)
+                .map_or(0, |(_, col)| *col)
+        };
+        let mut from = 0;
+        while let Some(found) = text[from..].find(&pattern) {
+            let start = from + found;
+            let end = start + pattern.len();
+            let col = column(start);
+            // include trailing empty cells of the last matched wide char.
+            let mut last = column(end - 1);
+            while cells.get(last + 1).map_or(false, |cell| cell.text.is_empty()) {
+                last += 1;
+            }
+            matches.push(Match {
+                row,
+                col,
+                len: last + 1 - col,
+            });
+            from = end;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    #[test]
+    fn test_find() {
+        let rows = testing::rows(&["fn main() {", "    let Main = 1; // main", "}"], |_| None);
+        assert_eq!(
+            find(&rows, "main"),
+            vec![
+                Match { row: 0, col: 3, len: 4 },
+                Match { row: 1, col: 8, len: 4 },
+                Match { row: 1, col: 21, len: 4 },
+            ]
+        );
+        // smart case.
+        assert_eq!(find(&rows, "Main"), vec![Match { row: 1, col: 8, len: 4 }]);
+        assert!(find(&rows, "").is_empty());
+        assert!(find(&rows, "nothing").is_empty());
+    }
+
+    #[test]
+    fn test_find_wide_chars() {
+        let rows = testing::rows(&["a中文b"], |_| None);
+        assert_eq!(find(&rows, "中文"), vec![Match { row: 0, col: 1, len: 4 }]);
+        assert_eq!(find(&rows, "b"), vec![Match { row: 0, col: 5, len: 1 }]);
+    }
+}
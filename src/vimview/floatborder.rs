@@ -0,0 +1,43 @@
+use super::TextCell;
+
+/// highlight groups of borders drawn by neovim around floating windows.
+pub const FLOAT_BORDER_HLGROUPS: &[&str] = &["FloatBorder"];
+
+/// Whether neovim drew a `border` of its own around the float, by highlight of
+/// its corner cells.
+pub fn has_border(rows: &[&[TextCell]], hlids: &[u64]) -> bool {
+    let is_border = |cell: Option<&TextCell>| {
+        cell.and_then(|cell| cell.hldef)
+            .map_or(false, |id| hlids.contains(&id))
+    };
+    let top_left = rows.first().and_then(|cells| cells.first());
+    let bottom_right = rows.last().and_then(|cells| cells.last());
+    is_border(top_left) && is_border(bottom_right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    const BORDER: u64 = 5;
+
+    fn hldef(c: char) -> Option<u64> {
+        match c {
+            '╭' | '╮' | '╰' | '╯' | '─' | '│' => Some(BORDER),
+            _ => Some(1),
+        }
+    }
+
+    #[test]
+    fn test_has_border() {
+        let rows = testing::rows(&["╭──╮", "│ab│", "╰──╯"], hldef);
+        assert!(has_border(&rows, &[BORDER]));
+        // FloatBorder not defined.
+        assert!(!has_border(&rows, &[]));
+
+        let rows = testing::rows(&["ab", "cd"], hldef);
+        assert!(!has_border(&rows, &[BORDER]));
+        assert!(!has_border(&[], &[BORDER]));
+    }
+}
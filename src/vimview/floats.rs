@@ -0,0 +1,66 @@
+mod imp {
+    use gtk::subclass::prelude::*;
+
+    #[derive(Debug, Default)]
+    pub struct FloatContainer;
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FloatContainer {
+        const NAME: &'static str = "FloatContainer";
+        type ParentType = gtk::Fixed;
+        type Type = super::FloatContainer;
+    }
+
+    impl ObjectImpl for FloatContainer {}
+
+    impl WidgetImpl for FloatContainer {
+        // only floats take pointer, windows below get it anywhere else.
+        fn contains(&self, _widget: &Self::Type, _x: f64, _y: f64) -> bool {
+            false
+        }
+    }
+
+    impl FixedImpl for FloatContainer {}
+}
+
+glib::wrapper! {
+    /// Floating windows above all grids of windows, see-through where no float is.
+    pub struct FloatContainer(ObjectSubclass<imp::FloatContainer>)
+        @extends gtk::Widget, gtk::Fixed,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl FloatContainer {
+    pub fn new() -> FloatContainer {
+        glib::Object::new(&[]).expect("Failed to create `FloatContainer`.")
+    }
+}
+
+impl Default for FloatContainer {
+    fn default() -> Self {
+        FloatContainer::new()
+    }
+}
+
+/// Index of the sibling a float of `order` goes before, `None` for above all.
+///
+/// `orders` are of the other floats from bottom to top, a float goes above
+/// floats of same order, just as the one positioned last by neovim.
+pub fn stack_position(orders: impl IntoIterator<Item = u64>, order: u64) -> Option<usize> {
+    orders.into_iter().position(|other| other > order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_position() {
+        // hover doc below, completion menu above it.
+        assert_eq!(stack_position([], 50), None);
+        assert_eq!(stack_position([50], 100), None);
+        assert_eq!(stack_position([100], 50), Some(0));
+        assert_eq!(stack_position([50, 100, 200], 100), Some(2));
+        assert_eq!(stack_position([50, 100], 50), Some(1));
+    }
+}
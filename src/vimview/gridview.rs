@@ -1,17 +1,35 @@
 mod imp {
     use core::f32;
-    use std::cell::{Cell, Ref};
+    use std::cell::{Cell, Ref, RefCell};
     use std::rc::Rc;
 
     use glib::translate::{from_glib_none, ToGlibPtr};
-    use gtk::{gdk::prelude::*, graphene::Rect, subclass::prelude::*};
+    use gtk::{
+        gdk,
+        gdk::prelude::*,
+        graphene::{Point, Rect},
+        subclass::prelude::*,
+    };
     use parking_lot::RwLock;
+    use rustc_hash::{FxHashMap, FxHashSet};
 
     use crate::metrics::Metrics;
+    use crate::vimview::background;
+    use crate::vimview::colorcolumn;
+    use crate::vimview::emoji;
+    use crate::vimview::find::Match;
+    use crate::vimview::floatborder::{self, FLOAT_BORDER_HLGROUPS};
+    use crate::vimview::gutter::{self, GUTTER_BACKGROUND_HLGROUPS, GUTTER_HLGROUPS};
+    use crate::vimview::placeholder::{self, PLACEHOLDER_HLGROUP};
+    use crate::vimview::scroll::ScrollAnimation;
+    use crate::vimview::scrollbar::{self, ScrollbarFade, Viewport};
+    use crate::vimview::separator::{self, Separator, SEPARATOR_HLGROUPS};
     use crate::vimview::textbuf::Lines;
+    use crate::vimview::underline::{self, Dash};
+    use crate::vimview::whitespace;
     use crate::vimview::TextCell;
 
-    use super::super::highlights::HighlightDefinitions;
+    use super::super::highlights::{HighlightDefinitions, Highlights};
     use super::super::TextBuf;
 
     const PANGO_SCALE: f64 = pango::SCALE as f64;
@@ -20,6 +38,8 @@ mod imp {
     struct CharAttr<'c> {
         c: char,
         cell: &'c TextCell,
+        // column of cell in grid.
+        col: usize,
         // visible width. how much cell used.
         viswidth: f64,
     }
@@ -30,6 +50,43 @@ mod imp {
         width: Cell<u64>,
         height: Cell<u64>,
         is_float: Cell<bool>,
+        // terminal grids trust cell layout from neovim exactly.
+        is_terminal: Cell<bool>,
+        // highlight groups name to id, used for decorations drawn by gui.
+        hlgroups: RefCell<Option<Rc<RwLock<FxHashMap<String, u64>>>>>,
+        // draw split separators by gui, only for single grid.
+        separators: Cell<bool>,
+        // fill sign/number column with distinct background.
+        gutter: Cell<bool>,
+        // fill cell backgrounds by gui, slightly overlapped to hide seams.
+        seamless: Cell<bool>,
+        // alpha of default background, below 1 it is left to the root area.
+        background_alpha: Cell<f64>,
+        // outline floats without a border of their own.
+        float_border: Cell<bool>,
+        // floats of higher order stacked above.
+        sort_order: Cell<u64>,
+        // image placeholders drawn over cells.
+        placeholders: Cell<bool>,
+        // grid columns of `colorcolumn` drawn as rules, in color of `ColorColumn`.
+        colorcolumns: RefCell<(Vec<usize>, Option<gdk::RGBA>)>,
+        // matches of find on screen, drawn translucent over cells.
+        matches: RefCell<(Vec<Match>, Option<gdk::RGBA>)>,
+        // link under pointer as row, col and len, underlined while hovered.
+        link: Cell<Option<(usize, usize, usize)>>,
+        // color of trailing whitespace and highlight ids matched to it by neovim, none for off.
+        whitespace: RefCell<Option<(gdk::RGBA, Rc<RwLock<FxHashSet<u64>>>)>>,
+        // alpha of darkening over inactive window, 0 for none.
+        dim: Cell<f64>,
+        // smooth scrolling of this grid only, ticked by its own callback.
+        scroll: Cell<ScrollAnimation>,
+        tick: RefCell<Option<gtk::TickCallbackId>>,
+        // scrollbar by viewport of window, fades out when idle.
+        viewport: Cell<Option<Viewport>>,
+        fade: Cell<ScrollbarFade>,
+        fade_tick: RefCell<Option<gtk::TickCallbackId>>,
+        // scrollbars kept at left and right edges by `guioptions`, never fade out.
+        pinned: Cell<(bool, bool)>,
         textbuf: Cell<TextBuf>,
     }
 
@@ -40,6 +97,7 @@ mod imp {
                 .field("width", &self.width.get())
                 .field("height", &self.height.get())
                 .field("is-float-window", &self.is_float.get())
+                .field("is-terminal", &self.is_terminal.get())
                 .finish_non_exhaustive()
         }
     }
@@ -56,6 +114,26 @@ mod imp {
                 width: 0.into(),
                 height: 0.into(),
                 is_float: false.into(),
+                is_terminal: false.into(),
+                hlgroups: RefCell::new(None),
+                separators: false.into(),
+                gutter: false.into(),
+                seamless: false.into(),
+                background_alpha: 1.0.into(),
+                float_border: false.into(),
+                sort_order: 0.into(),
+                placeholders: false.into(),
+                colorcolumns: RefCell::new((Vec::new(), None)),
+                matches: RefCell::new((Vec::new(), None)),
+                link: Cell::new(None),
+                whitespace: RefCell::new(None),
+                dim: Cell::new(0.),
+                scroll: Cell::new(ScrollAnimation::default()),
+                tick: RefCell::new(None),
+                viewport: Cell::new(None),
+                fade: Cell::new(ScrollbarFade::default()),
+                fade_tick: RefCell::new(None),
+                pinned: Cell::new((false, false)),
                 textbuf: TextBuf::default().into(),
             }
         }
@@ -67,6 +145,13 @@ mod imp {
             self.parent_constructed(obj);
         }
 
+        fn dispose(&self, _obj: &Self::Type) {
+            log::debug!("grid view {} disposed", self.id.get());
+            // release shared states, textbuf caches go with last textbuf reference.
+            self.hlgroups.replace(None);
+            self.colorcolumns.borrow_mut().0.clear();
+        }
+
         fn properties() -> &'static [glib::ParamSpec] {
             use once_cell::sync::Lazy;
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
@@ -150,56 +235,168 @@ mod imp {
 
             let (width, height) = self.size_required();
 
-            let hldefs = textbuf.hldefs().unwrap();
-            let hldefs = hldefs.read();
+            // drawing keeps a snapshot, not the lock of definitions.
+            let hldefs = textbuf.hldefs().unwrap().read().snapshot();
+            // colorscheme changed, lines cached by old colors are drawn again.
+            textbuf.sync_colors(hldefs.colors());
 
             let metrics = textbuf.metrics().unwrap().get();
 
             let rect = Rect::new(0., 0., width as _, height as _);
 
+            let rows = textbuf.rows();
+            let ligatures = textbuf.ligatures();
+            let textbuf = self.textbuf();
+            let lines = textbuf.lines();
+
             let hldef = hldefs.get(HighlightDefinitions::DEFAULT);
             let mut background = hldef
                 .map(|style| &style.colors)
                 .and_then(|colors| colors.background)
                 .unwrap();
             if self.is_float.get() {
-                // float window should respect blend for background.
-                let blend = hldef.map(|style| style.blend).unwrap_or(0);
-                let alpha = (100 - blend) as f32 / 100.;
-                background.set_alpha(alpha);
+                // float window should respect blend and background of `NormalFloat`
+                // or `winhighlight`, neovim sends them by highlights of its cells.
+                let style = lines
+                    .get(0)
+                    .and_then(|line| line.get(0))
+                    .and_then(|cell| cell.hldef)
+                    .and_then(|id| hldefs.get(id))
+                    .or(hldef);
+                if let (Some(style), Some(defaults)) = (style, hldefs.defaults()) {
+                    if let Some(color) = style.resolved_background(defaults) {
+                        background = color;
+                    }
+                }
+                background.set_alpha(style.map_or(1., |style| style.alpha()));
+                snapshot.append_color(&background, &rect);
+            } else if self.background_alpha.get() >= 1. {
+                // translucent default background is painted once by the root area
+                // beneath grids, painting it here again would compound its alpha.
+                snapshot.append_color(&background, &rect);
             }
-            snapshot.append_color(&background, &rect);
 
-            let cr = snapshot.append_cairo(&rect);
+            if self.gutter.get() {
+                if let Some(hlgroups) = self.hlgroups.borrow().as_ref() {
+                    self.snapshot_gutter(
+                        snapshot,
+                        &lines,
+                        rows,
+                        &hldefs,
+                        &hlgroups.read(),
+                        &metrics,
+                    );
+                }
+            }
 
-            let mut y = metrics.ascent();
+            let now = widget.frame_clock().map(|clock| clock.frame_time());
+            let offset = now.and_then(|now| self.scroll.get().offset(now));
+            if let Some(offset) = offset {
+                snapshot.save();
+                snapshot.translate(&Point::new(0., offset as f32));
+            }
+
+            if self.seamless.get() {
+                self.snapshot_backgrounds(snapshot, &lines, rows, &hldefs, &metrics);
+            }
 
-            let rows = textbuf.rows();
             log::debug!("text to render:");
             let desc = pctx.font_description();
             let mut layout = pango::Layout::new(&pctx);
             layout.set_auto_dir(false);
             layout.set_font_description(desc.as_ref());
-            let textbuf = self.textbuf();
-            let lines = textbuf.lines();
+            // each line is a node of its own, clean lines reuse the node drawn
+            // before, so only changed lines are damaged and repainted.
+            // glyphs may overflow their line, a line of margin around.
+            let height = metrics.height() as f32;
+            let bounds = Rect::new(0., -height, width as _, height * 3.);
             for lineno in 0..rows {
-                cr.move_to(0., y);
-                y += metrics.height();
                 let line = lines.get(lineno).unwrap();
-                let layoutline = if let Some((layout, layoutline)) = line.cache() {
-                    unsafe {
-                        let layout: *mut pango::ffi::PangoLayout = layout.to_glib_none().0;
-                        (*layoutline.to_glib_none().0).layout = layout;
-                    };
-                    pangocairo::update_layout(&cr, &layout);
-                    layoutline
-                } else {
-                    let layoutline = self.layoutline(&mut layout, &lines, lineno, &metrics);
-                    line.set_cache(layout.copy().unwrap(), layoutline.clone());
-                    pangocairo::update_layout(&cr, &layout);
-                    layoutline
+                let node = match line.node(&metrics) {
+                    Some(node) => node,
+                    None => {
+                        let line_snapshot = gtk::Snapshot::new();
+                        let cr = line_snapshot.append_cairo(&bounds);
+                        cr.move_to(0., metrics.baseline());
+                        let layoutline = if let Some((layout, layoutline)) = line.cache() {
+                            unsafe {
+                                let layout: *mut pango::ffi::PangoLayout = layout.to_glib_none().0;
+                                (*layoutline.to_glib_none().0).layout = layout;
+                            };
+                            pangocairo::update_layout(&cr, &layout);
+                            layoutline
+                        } else {
+                            let layoutline =
+                                self.layoutline(&mut layout, &lines, lineno, &metrics, ligatures);
+                            line.set_cache(layout.copy().unwrap(), layoutline.clone());
+                            pangocairo::update_layout(&cr, &layout);
+                            layoutline
+                        };
+                        pangocairo::show_layout_line(&cr, &layoutline);
+                        drop(cr);
+                        let node = match line_snapshot.to_node() {
+                            Some(node) => node,
+                            None => continue,
+                        };
+                        line.set_node(node.clone(), metrics);
+                        node
+                    }
                 };
-                pangocairo::show_layout_line(&cr, &layoutline);
+                snapshot.save();
+                snapshot.translate(&Point::new(0., lineno as f32 * height));
+                snapshot.append_node(&node);
+                snapshot.restore();
+            }
+            if self.placeholders.get() {
+                if let Some(hlgroups) = self.hlgroups.borrow().as_ref() {
+                    self.snapshot_placeholders(
+                        snapshot,
+                        &lines,
+                        rows,
+                        &hldefs,
+                        &hlgroups.read(),
+                        &metrics,
+                    );
+                }
+            }
+            self.snapshot_underlines(snapshot, &lines, rows, &hldefs, &metrics);
+            self.snapshot_link(snapshot, &lines, &hldefs, &metrics);
+            if let Some((color, hlids)) = self.whitespace.borrow().as_ref() {
+                self.snapshot_whitespace(snapshot, &lines, rows, &hlids.read(), color, &metrics);
+            }
+            self.snapshot_matches(snapshot, &metrics);
+            if offset.is_some() {
+                snapshot.restore();
+            }
+            self.snapshot_colorcolumns(snapshot, rows, &metrics);
+            if self.separators.get() {
+                if let Some(hlgroups) = self.hlgroups.borrow().as_ref() {
+                    self.snapshot_separators(
+                        snapshot,
+                        &lines,
+                        rows,
+                        &hldefs,
+                        &hlgroups.read(),
+                        &metrics,
+                    );
+                }
+            }
+            if self.is_float.get() && self.float_border.get() {
+                if let Some(hlgroups) = self.hlgroups.borrow().as_ref() {
+                    self.snapshot_float_border(
+                        snapshot,
+                        &lines,
+                        rows,
+                        &hldefs,
+                        &hlgroups.read(),
+                        &rect,
+                    );
+                }
+            }
+            self.snapshot_scrollbar(widget, snapshot, &hldefs, &metrics);
+            let dim = self.dim.get();
+            if dim > 0. {
+                snapshot.append_color(&gdk::RGBA::new(0., 0., 0., dim as f32), &rect);
             }
             let elapsed = instant.elapsed().as_secs_f32() * 1000.;
             log::info!("snapshot used: {:.3}ms", elapsed);
@@ -253,6 +450,125 @@ mod imp {
             self.is_float.replace(is_float);
         }
 
+        pub(super) fn set_is_terminal(&self, is_terminal: bool) {
+            self.is_terminal.replace(is_terminal);
+        }
+
+        pub(super) fn set_hlgroups(&self, hlgroups: Option<Rc<RwLock<FxHashMap<String, u64>>>>) {
+            self.hlgroups.replace(hlgroups);
+        }
+
+        pub(super) fn set_draw_separators(&self, separators: bool) {
+            self.separators.replace(separators);
+        }
+
+        pub(super) fn set_fill_gutter(&self, gutter: bool) {
+            self.gutter.replace(gutter);
+        }
+
+        pub(super) fn set_seamless_background(&self, seamless: bool) {
+            self.seamless.replace(seamless);
+        }
+
+        pub(super) fn set_background_alpha(&self, alpha: f64) {
+            self.background_alpha.replace(alpha);
+        }
+
+        pub(super) fn set_float_border(&self, float_border: bool) {
+            self.float_border.replace(float_border);
+        }
+
+        pub(super) fn set_sort_order(&self, sort_order: u64) {
+            self.sort_order.replace(sort_order);
+        }
+
+        pub(super) fn sort_order(&self) -> u64 {
+            self.sort_order.get()
+        }
+
+        pub(super) fn set_dim(&self, dim: f64) {
+            self.dim.replace(dim);
+        }
+
+        pub(super) fn set_image_placeholders(&self, placeholders: bool) {
+            self.placeholders.replace(placeholders);
+        }
+
+        pub(super) fn set_trailing_whitespace(
+            &self,
+            whitespace: Option<(gdk::RGBA, Rc<RwLock<FxHashSet<u64>>>)>,
+        ) {
+            self.whitespace.replace(whitespace);
+        }
+
+        pub(super) fn set_colorcolumns(&self, colorcolumns: Vec<usize>, color: Option<gdk::RGBA>) {
+            self.colorcolumns.replace((colorcolumns, color));
+        }
+
+        pub(super) fn set_matches(&self, matches: Vec<Match>, color: Option<gdk::RGBA>) {
+            self.matches.replace((matches, color));
+        }
+
+        pub(super) fn set_link(&self, link: Option<(usize, usize, usize)>) -> bool {
+            self.link.replace(link) != link
+        }
+
+        pub(super) fn animate_scroll(&self, widget: &super::VimGridView, delta: f64) {
+            let now = widget
+                .frame_clock()
+                .map(|clock| clock.frame_time())
+                .unwrap_or(0);
+            let mut scroll = self.scroll.get();
+            scroll.start(delta, now);
+            self.scroll.set(scroll);
+            if self.tick.borrow().is_some() {
+                return;
+            }
+            let id = widget.add_tick_callback(|widget, clock| {
+                let imp = widget.imp();
+                let mut scroll = imp.scroll.get();
+                let running = scroll.tick(clock.frame_time());
+                imp.scroll.set(scroll);
+                widget.queue_draw();
+                if !running {
+                    log::debug!("grid {} scroll animation finished.", imp.id.get());
+                    imp.tick.replace(None);
+                }
+                glib::Continue(running)
+            });
+            self.tick.replace(Some(id));
+        }
+
+        pub(super) fn set_scrollbars(&self, left: bool, right: bool) {
+            self.pinned.set((left, right));
+        }
+
+        pub(super) fn show_scrollbar(&self, widget: &super::VimGridView, viewport: Viewport) {
+            let now = widget
+                .frame_clock()
+                .map(|clock| clock.frame_time())
+                .unwrap_or(0);
+            self.viewport.set(Some(viewport));
+            let mut fade = self.fade.get();
+            fade.show(now);
+            self.fade.set(fade);
+            if self.fade_tick.borrow().is_some() {
+                return;
+            }
+            let id = widget.add_tick_callback(|widget, clock| {
+                let imp = widget.imp();
+                let mut fade = imp.fade.get();
+                let visible = fade.tick(clock.frame_time());
+                imp.fade.set(fade);
+                widget.queue_draw();
+                if !visible {
+                    imp.fade_tick.replace(None);
+                }
+                glib::Continue(visible)
+            });
+            self.fade_tick.replace(Some(id));
+        }
+
         pub(super) fn set_metrics(&self, metrics: Rc<Cell<crate::metrics::Metrics>>) {
             self.textbuf().set_metrics(metrics)
         }
@@ -267,12 +583,392 @@ mod imp {
             (w.ceil() as i32, h.ceil() as i32)
         }
 
+        fn snapshot_gutter(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hldefs: &Highlights,
+            hlgroups: &FxHashMap<String, u64>,
+            metrics: &Metrics,
+        ) {
+            let hlids: Vec<u64> = GUTTER_HLGROUPS
+                .iter()
+                .filter_map(|name| hlgroups.get(*name).copied())
+                .collect();
+            if hlids.is_empty() {
+                return;
+            }
+            let color = GUTTER_BACKGROUND_HLGROUPS
+                .iter()
+                .filter_map(|name| hlgroups.get(*name))
+                .find_map(|id| hldefs.get(*id))
+                .and_then(|style| style.colors.background);
+            let color = if let Some(color) = color {
+                color
+            } else {
+                return;
+            };
+            let cells: Vec<&[TextCell]> = (0..rows)
+                .filter_map(|lineno| lines.get(lineno))
+                .map(|line| line.as_ref())
+                .collect();
+            let cols = gutter::detect(&cells, &hlids);
+            if let Some((x, y, width, height)) =
+                gutter::region(cols, rows, metrics.width(), metrics.height())
+            {
+                let rect = Rect::new(
+                    x as f32,
+                    y as f32,
+                    width.ceil() as f32,
+                    height.ceil() as f32,
+                );
+                snapshot.append_color(&color, &rect);
+            }
+        }
+
+        fn snapshot_backgrounds(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hldefs: &Highlights,
+            metrics: &Metrics,
+        ) {
+            let (width, height) = (metrics.width(), metrics.height());
+            for lineno in 0..rows {
+                let line = match lines.get(lineno) {
+                    Some(line) => line,
+                    None => continue,
+                };
+                for fill in background::fills(line) {
+                    let color = hldefs.get(fill.hldef).zip(hldefs.defaults()).and_then(
+                        |(style, defaults)| {
+                            let mut color = style.resolved_background(defaults)?;
+                            color.set_alpha(style.alpha());
+                            Some(color)
+                        },
+                    );
+                    if let Some(color) = color {
+                        let (x, y, w, h) = background::region(&fill, lineno, width, height);
+                        snapshot.append_color(
+                            &color,
+                            &Rect::new(x as f32, y as f32, w as f32, h as f32),
+                        );
+                    }
+                }
+            }
+        }
+
+        fn snapshot_placeholders(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hldefs: &Highlights,
+            hlgroups: &FxHashMap<String, u64>,
+            metrics: &Metrics,
+        ) {
+            let hlid = match hlgroups.get(PLACEHOLDER_HLGROUP) {
+                Some(hlid) => *hlid,
+                None => return,
+            };
+            // background of the highlight, or a faint foreground if it has none.
+            let color = hldefs
+                .get(hlid)
+                .and_then(|style| style.background())
+                .or_else(|| {
+                    hldefs
+                        .defaults()
+                        .and_then(|colors| colors.foreground)
+                        .map(|mut color| {
+                            color.set_alpha(0.15);
+                            color
+                        })
+                });
+            let color = if let Some(color) = color {
+                color
+            } else {
+                return;
+            };
+            let cells: Vec<&[TextCell]> = (0..rows)
+                .filter_map(|lineno| lines.get(lineno))
+                .map(|line| line.as_ref())
+                .collect();
+            for placeholder in placeholder::detect(&cells, hlid) {
+                let (x, y, width, height) =
+                    placeholder::region(&placeholder, metrics.width(), metrics.height());
+                let rect = Rect::new(x as f32, y as f32, width as f32, height as f32);
+                snapshot.append_color(&color, &rect);
+            }
+        }
+
+        fn snapshot_whitespace(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hlids: &FxHashSet<u64>,
+            color: &gdk::RGBA,
+            metrics: &Metrics,
+        ) {
+            let cells: Vec<&[TextCell]> = (0..rows)
+                .filter_map(|lineno| lines.get(lineno))
+                .map(|line| line.as_ref())
+                .collect();
+            let (width, height) = (metrics.width(), metrics.height());
+            for m in whitespace::trailing(&cells, hlids) {
+                let rect = Rect::new(
+                    (m.col as f64 * width) as f32,
+                    (m.row as f64 * height) as f32,
+                    (m.len as f64 * width) as f32,
+                    height as f32,
+                );
+                snapshot.append_color(color, &rect);
+            }
+        }
+
+        fn snapshot_underlines(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hldefs: &Highlights,
+            metrics: &Metrics,
+        ) {
+            let defaults = match hldefs.defaults() {
+                Some(defaults) => defaults,
+                None => return,
+            };
+            let dash = |hldef| {
+                let style = hldefs.get(hldef)?;
+                if style.underdotted {
+                    Some(Dash::Dotted)
+                } else if style.underdashed {
+                    Some(Dash::Dashed)
+                } else {
+                    None
+                }
+            };
+            let (width, height) = (metrics.width(), metrics.height());
+            // dotted and dashed underlines sit just below baseline.
+            let thickness = (metrics.charheight() / 16.).round().max(1.);
+            for lineno in 0..rows {
+                let line = match lines.get(lineno) {
+                    Some(line) => line,
+                    None => continue,
+                };
+                let y = lineno as f64 * height + metrics.baseline() + thickness;
+                for u in underline::detect(line.as_ref(), dash) {
+                    let color = hldefs.get(u.hldef).unwrap().special(defaults);
+                    for (x, len) in underline::segments(&u, width, thickness) {
+                        let rect = Rect::new(x as f32, y as f32, len as f32, thickness as f32);
+                        snapshot.append_color(&color, &rect);
+                    }
+                }
+            }
+        }
+
+        fn snapshot_link(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            hldefs: &Highlights,
+            metrics: &Metrics,
+        ) {
+            let (row, col, len) = match self.link.get() {
+                Some(link) => link,
+                None => return,
+            };
+            let defaults = match hldefs.defaults() {
+                Some(defaults) => defaults,
+                None => return,
+            };
+            // in color of link text.
+            let color = lines
+                .get(row)
+                .and_then(|line| line.get(col))
+                .and_then(|cell| cell.hldef)
+                .and_then(|hldef| hldefs.get(hldef))
+                .or_else(|| hldefs.get(HighlightDefinitions::DEFAULT))
+                .map(|style| style.foreground(defaults));
+            let color = match color {
+                Some(color) => color,
+                None => return,
+            };
+            let (width, height) = (metrics.width(), metrics.height());
+            let thickness = (metrics.charheight() / 16.).round().max(1.);
+            let rect = Rect::new(
+                (col as f64 * width) as f32,
+                (row as f64 * height + metrics.baseline() + thickness) as f32,
+                (len as f64 * width) as f32,
+                thickness as f32,
+            );
+            snapshot.append_color(&color, &rect);
+        }
+
+        fn snapshot_matches(&self, snapshot: &gtk::Snapshot, metrics: &Metrics) {
+            let matches = self.matches.borrow();
+            let (matches, color) = match &*matches {
+                (matches, Some(color)) if !matches.is_empty() => (matches, color),
+                _ => return,
+            };
+            let (width, height) = (metrics.width(), metrics.height());
+            for m in matches.iter() {
+                let rect = Rect::new(
+                    (m.col as f64 * width) as f32,
+                    (m.row as f64 * height) as f32,
+                    (m.len as f64 * width) as f32,
+                    height as f32,
+                );
+                snapshot.append_color(color, &rect);
+            }
+        }
+
+        fn snapshot_colorcolumns(&self, snapshot: &gtk::Snapshot, rows: usize, metrics: &Metrics) {
+            let colorcolumns = self.colorcolumns.borrow();
+            let (colorcolumns, color) = match &*colorcolumns {
+                (colorcolumns, Some(color)) if !colorcolumns.is_empty() => (colorcolumns, color),
+                _ => return,
+            };
+            let cols = self.width.get() as usize;
+            let height = (rows as f64 * metrics.height()) as f32;
+            for col in colorcolumns.iter().filter(|col| **col < cols) {
+                let x = colorcolumn::rule_x(*col, metrics.width());
+                snapshot.append_color(color, &Rect::new(x as f32, 0., 1., height));
+            }
+        }
+
+        fn snapshot_scrollbar(
+            &self,
+            widget: &super::VimGridView,
+            snapshot: &gtk::Snapshot,
+            hldefs: &Highlights,
+            metrics: &Metrics,
+        ) {
+            let now = widget
+                .frame_clock()
+                .map(|clock| clock.frame_time())
+                .unwrap_or(0);
+            let (left, right) = self.pinned.get();
+            let alpha = if left || right {
+                Some(1.)
+            } else {
+                self.fade.get().alpha(now)
+            };
+            let alpha = match alpha {
+                Some(alpha) => alpha,
+                None => return,
+            };
+            let height = self.height.get() as f64 * metrics.height();
+            let (y, size) = match self.viewport.get().and_then(|v| v.thumb(height)) {
+                Some(thumb) => thumb,
+                None => return,
+            };
+            let mut color = match hldefs.defaults().and_then(|colors| colors.foreground) {
+                Some(color) => color,
+                None => return,
+            };
+            color.set_alpha(0.4 * alpha as f32);
+            let width = self.width.get() as f64 * metrics.width();
+            // fading one is at right edge.
+            let edges = [(left, 0.), (right || !left, width - scrollbar::THUMB_WIDTH)];
+            for (_, x) in edges.into_iter().filter(|(shown, _)| *shown) {
+                let rect = Rect::new(x as _, y as _, scrollbar::THUMB_WIDTH as _, size as _);
+                let radius = scrollbar::THUMB_WIDTH as f32 / 2.;
+                let clip = gtk::gsk::RoundedRect::from_rect(rect.clone(), radius);
+                snapshot.push_rounded_clip(&clip);
+                snapshot.append_color(&color, &rect);
+                snapshot.pop();
+            }
+        }
+
+        fn snapshot_float_border(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hldefs: &Highlights,
+            hlgroups: &FxHashMap<String, u64>,
+            rect: &Rect,
+        ) {
+            let hlids: Vec<u64> = FLOAT_BORDER_HLGROUPS
+                .iter()
+                .filter_map(|name| hlgroups.get(*name).copied())
+                .collect();
+            let cells: Vec<&[TextCell]> = (0..rows)
+                .filter_map(|lineno| lines.get(lineno))
+                .map(|line| line.as_ref())
+                .collect();
+            if floatborder::has_border(&cells, &hlids) {
+                return;
+            }
+            let color = hlids
+                .iter()
+                .find_map(|id| hldefs.get(*id))
+                .and_then(|style| style.colors.foreground)
+                .or_else(|| hldefs.defaults().and_then(|colors| colors.foreground));
+            if let Some(color) = color {
+                let outline = gtk::gsk::RoundedRect::from_rect(rect.clone(), 0.);
+                snapshot.append_border(&outline, &[1.; 4], &[color; 4]);
+            }
+        }
+
+        fn snapshot_separators(
+            &self,
+            snapshot: &gtk::Snapshot,
+            lines: &Lines,
+            rows: usize,
+            hldefs: &Highlights,
+            hlgroups: &FxHashMap<String, u64>,
+            metrics: &Metrics,
+        ) {
+            let hlids: Vec<u64> = SEPARATOR_HLGROUPS
+                .iter()
+                .filter_map(|name| hlgroups.get(*name).copied())
+                .collect();
+            let color = hlids
+                .iter()
+                .find_map(|id| hldefs.get(*id))
+                .and_then(|style| style.colors.foreground)
+                .or_else(|| hldefs.defaults().and_then(|colors| colors.foreground));
+            let color = if let Some(color) = color {
+                color
+            } else {
+                return;
+            };
+            let cells: Vec<&[TextCell]> = (0..rows)
+                .filter_map(|lineno| lines.get(lineno))
+                .map(|line| line.as_ref())
+                .collect();
+            let (width, height) = (metrics.width(), metrics.height());
+            for sep in separator::detect(&cells, &hlids) {
+                // one pixel rule at center of cells, keep it crisp.
+                let rect = match sep {
+                    Separator::Vertical { col, row, len } => Rect::new(
+                        ((col as f64 + 0.5) * width).floor() as f32,
+                        (row as f64 * height) as f32,
+                        1.,
+                        (len as f64 * height) as f32,
+                    ),
+                    Separator::Horizontal { row, col, len } => Rect::new(
+                        (col as f64 * width) as f32,
+                        ((row as f64 + 0.5) * height).floor() as f32,
+                        (len as f64 * width) as f32,
+                        1.,
+                    ),
+                };
+                snapshot.append_color(&color, &rect);
+            }
+        }
+
         fn layoutline(
             &self,
             layout: &mut pango::Layout,
             lines: &Lines,
             lineno: usize,
             metrics: &Metrics,
+            ligatures: bool,
         ) -> pango::LayoutLine {
             let line = lines.get(lineno).unwrap();
             let cols = line.len();
@@ -295,6 +991,7 @@ mod imp {
                         CharAttr {
                             c,
                             cell,
+                            col,
                             viswidth: if cell.double_width {
                                 2.
                             } else if pango::is_zero_width(c) {
@@ -316,6 +1013,7 @@ mod imp {
                         CharAttr {
                             c,
                             cell,
+                            col,
                             viswidth: 0.,
                         }
                     }
@@ -323,10 +1021,19 @@ mod imp {
                     index += c.to_string().bytes().len();
                 }
                 text.push_str(&cell.text);
-                cell.attrs
-                    .clone()
-                    .into_iter()
-                    .for_each(|attr| attrs.change(attr));
+                cell.attrs.iter().cloned().for_each(|mut attr| {
+                    attr.set_start_index(cell.start_index as u32);
+                    attr.set_end_index(cell.end_index as u32);
+                    attrs.change(attr);
+                });
+            }
+            if !ligatures {
+                attrs.insert_before({
+                    let mut attr = pango::AttrFontFeatures::new("liga 0, calt 0, dlig 0");
+                    attr.set_start_index(0);
+                    attr.set_end_index(pango::ATTR_INDEX_TO_TEXT_END);
+                    attr
+                });
             }
             layout.set_text(&text);
             layout.set_attributes(Some(&attrs));
@@ -399,7 +1106,11 @@ mod imp {
                 );
                 log::trace!("{} glyphs item.offset {}", num_glyphs, (*item).offset);
                 log::trace!("log_clusters{:?}", log_clusters);
-                for (glyph, log_cluster) in glyphs.iter_mut().zip(log_clusters) {
+                let advances: Vec<i32> = glyphs.iter().map(|glyph| glyph.geometry.width).collect();
+                let clusters = emoji::glyph_clusters(log_clusters, &advances);
+                for ((glyph, log_cluster), cluster) in
+                    glyphs.iter_mut().zip(log_clusters).zip(clusters)
+                {
                     let index = ((*item).offset + log_cluster) as usize;
                     let isfirst = index == 0;
                     let charattr = chars.get(index).unwrap().as_ref().unwrap_or_else(|| {
@@ -410,9 +1121,20 @@ mod imp {
                         log::debug!("Skipping zerowidth: {}", charattr.cell.text);
                         continue;
                     }
-                    let width = metrics.charwidth() * charattr.viswidth * PANGO_SCALE;
-                    let width = width.ceil() as i32;
+                    // marks stay stacked on their base glyph.
+                    let (nth, count) = match cluster {
+                        Some(cluster) => cluster,
+                        None => continue,
+                    };
                     let geometry = &mut glyph.geometry;
+                    if self.is_terminal.get() {
+                        // fast path, glyph placed at integer column exactly as neovim sent.
+                        let width = metrics.columns_width(charattr.col, charattr.viswidth as usize);
+                        geometry.width = emoji::share_width(width, nth, count);
+                        continue;
+                    }
+                    let width = metrics.charwidth() * charattr.viswidth * PANGO_SCALE;
+                    let width = emoji::share_width(width.ceil() as i32, nth, count);
                     // log::info!("{} char-cell {:?}", index, charattr.cell);
                     if geometry.width > 0 && geometry.width != width {
                         let x_offset = if isfirst {
@@ -453,9 +1175,11 @@ use std::cell::{Cell, Ref};
 use std::rc::Rc;
 
 use glib::subclass::prelude::*;
-use gtk::prelude::*;
+use gtk::{gdk, prelude::*};
 use parking_lot::RwLock;
+use rustc_hash::{FxHashMap, FxHashSet};
 
+use super::find::Match;
 use super::{HighlightDefinitions, TextBuf};
 
 glib::wrapper! {
@@ -480,6 +1204,11 @@ impl VimGridView {
         imp::VimGridView::from_instance(self)
     }
 
+    /// Id of grid drawn by this view.
+    pub fn id(&self) -> u64 {
+        self.imp().id.get()
+    }
+
     pub fn set_hldefs(&self, hldefs: Rc<RwLock<HighlightDefinitions>>) {
         self.imp().set_hldefs(hldefs);
     }
@@ -492,6 +1221,96 @@ impl VimGridView {
         self.imp().set_is_float(is_float);
     }
 
+    pub fn set_is_terminal(&self, is_terminal: bool) {
+        self.imp().set_is_terminal(is_terminal);
+    }
+
+    pub fn set_hlgroups(&self, hlgroups: Option<Rc<RwLock<FxHashMap<String, u64>>>>) {
+        self.imp().set_hlgroups(hlgroups);
+    }
+
+    pub fn set_draw_separators(&self, separators: bool) {
+        self.imp().set_draw_separators(separators);
+    }
+
+    pub fn set_fill_gutter(&self, gutter: bool) {
+        self.imp().set_fill_gutter(gutter);
+    }
+
+    /// Fill cell backgrounds by gui, overlapped by a sub-pixel to hide seams.
+    pub fn set_seamless_background(&self, seamless: bool) {
+        self.imp().set_seamless_background(seamless);
+    }
+
+    /// Alpha of default background, cells of other backgrounds stay opaque.
+    pub fn set_background_alpha(&self, alpha: f64) {
+        self.imp().set_background_alpha(alpha);
+    }
+
+    /// Outline float windows by `FloatBorder`, unless neovim draws their border.
+    pub fn set_float_border(&self, float_border: bool) {
+        self.imp().set_float_border(float_border);
+    }
+
+    /// Stacking order among floats, higher ones are drawn above and take input first.
+    pub fn set_sort_order(&self, sort_order: u64) {
+        self.imp().set_sort_order(sort_order);
+    }
+
+    pub fn sort_order(&self) -> u64 {
+        self.imp().sort_order()
+    }
+
+    /// Darken whole grid by `dim` alpha, 0 for none.
+    pub fn set_dim(&self, dim: f64) {
+        self.imp().set_dim(dim);
+    }
+
+    /// Draw cells highlighted by `ReovimImagePlaceholder` as solid rectangles.
+    pub fn set_image_placeholders(&self, placeholders: bool) {
+        self.imp().set_image_placeholders(placeholders);
+    }
+
+    /// Highlight trailing whitespace of visible lines with `color`, cells of `hlids`
+    /// matched by neovim, none for off.
+    pub fn set_trailing_whitespace(
+        &self,
+        whitespace: Option<(gdk::RGBA, Rc<RwLock<FxHashSet<u64>>>)>,
+    ) {
+        self.imp().set_trailing_whitespace(whitespace);
+    }
+
+    pub fn set_colorcolumns(&self, colorcolumns: Vec<usize>, color: Option<gdk::RGBA>) {
+        self.imp().set_colorcolumns(colorcolumns, color);
+    }
+
+    /// Underline link of `len` cells from `col` of `row` while hovered, `None` for none.
+    pub fn set_link(&self, link: Option<(usize, usize, usize)>) {
+        if self.imp().set_link(link) {
+            self.queue_draw();
+        }
+    }
+
+    /// Matches of find on screen, drawn with `color` over cells.
+    pub fn set_matches(&self, matches: Vec<Match>, color: Option<gdk::RGBA>) {
+        self.imp().set_matches(matches, color);
+    }
+
+    /// Animate content scrolled by `delta` pixels, positive means scrolled up.
+    pub fn animate_scroll(&self, delta: f64) {
+        self.imp().animate_scroll(self, delta);
+    }
+
+    /// Keep scrollbars at `left` and `right` edges, by `guioptions`.
+    pub fn set_scrollbars(&self, left: bool, right: bool) {
+        self.imp().set_scrollbars(left, right);
+    }
+
+    /// Show scrollbar of window at `viewport`, it fades out when idle.
+    pub fn show_scrollbar(&self, viewport: super::Viewport) {
+        self.imp().show_scrollbar(self, viewport);
+    }
+
     pub fn set_font_description(&self, desc: &pango::FontDescription) {
         self.pango_context().set_font_description(desc);
     }
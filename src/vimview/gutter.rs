@@ -0,0 +1,80 @@
+use super::TextCell;
+
+/// highlight groups of gutter cells, line numbers and signs.
+pub const GUTTER_HLGROUPS: &[&str] = &[
+    "LineNr",
+    "LineNrAbove",
+    "LineNrBelow",
+    "CursorLineNr",
+    "SignColumn",
+    "CursorLineSign",
+    "FoldColumn",
+    "CursorLineFold",
+];
+
+/// highlight groups which background fills the gutter, first defined wins.
+pub const GUTTER_BACKGROUND_HLGROUPS: &[&str] = &["SignColumn", "LineNr"];
+
+/// Width of gutter in cells, the longest run of leading cells highlighted by `hlids`.
+pub fn detect(rows: &[&[TextCell]], hlids: &[u64]) -> usize {
+    rows.iter()
+        .map(|cells| {
+            cells
+                .iter()
+                .take_while(|cell| cell.hldef.map_or(false, |id| hlids.contains(&id)))
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Region of gutter `(x, y, width, height)` in pixel, fills the full height of grid.
+pub fn region(
+    cols: usize,
+    rows: usize,
+    cell_width: f64,
+    cell_height: f64,
+) -> Option<(f64, f64, f64, f64)> {
+    if cols == 0 || rows == 0 {
+        return None;
+    }
+    Some((0., 0., cols as f64 * cell_width, rows as f64 * cell_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    const LINENR: u64 = 3;
+    const SIGN: u64 = 4;
+
+    // `!` of signs, then digits of line numbers.
+    fn hldef(c: char) -> Option<u64> {
+        match c {
+            '!' => Some(SIGN),
+            '0'..='9' => Some(LINENR),
+            _ => Some(1),
+        }
+    }
+
+    #[test]
+    fn test_gutter_fill_region() {
+        // short line `~` after end of buffer.
+        let rows = testing::rows(&["!!001fn main() {", "!!002}", "~"], hldef);
+        let cols = detect(&rows, &[LINENR, SIGN]);
+        assert_eq!(cols, 5);
+        assert_eq!(
+            region(cols, rows.len(), 8., 16.),
+            Some((0., 0., 40., 48.))
+        );
+    }
+
+    #[test]
+    fn test_no_gutter() {
+        let rows = testing::rows(&["text"], hldef);
+        let cols = detect(&rows, &[LINENR, SIGN]);
+        assert_eq!(cols, 0);
+        assert_eq!(region(cols, rows.len(), 8., 16.), None);
+    }
+}
@@ -1,10 +1,43 @@
+use std::rc::Rc;
+
 use glib::subclass::prelude::*;
+use rustc_hash::FxHashMap;
 
-use crate::color::Colors;
+use crate::color::{Color, Colors};
 use crate::style;
 
+/// Definitions frozen at a generation, drawing keeps it instead of the lock.
+#[derive(Debug)]
+pub struct Highlights {
+    generation: u64,
+    colors: u64,
+    // shared with definitions until they change again.
+    styles: Rc<FxHashMap<u64, style::Style>>,
+    defaults: Option<Colors>,
+}
+
+impl Highlights {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Generation of colors already drawn, bumped only when they change.
+    pub fn colors(&self) -> u64 {
+        self.colors
+    }
+
+    pub fn get(&self, k: u64) -> Option<&style::Style> {
+        self.styles.get(&k)
+    }
+
+    pub fn defaults(&self) -> Option<&Colors> {
+        self.defaults.as_ref()
+    }
+}
+
 mod imp {
     use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
 
     use glib::subclass::prelude::*;
     use rustc_hash::FxHashMap;
@@ -13,8 +46,14 @@ mod imp {
 
     #[derive(Debug)]
     pub struct HighlightDefinitions {
-        styles: RefCell<FxHashMap<u64, crate::style::Style>>,
+        // copied on write only while a snapshot still holds it.
+        styles: RefCell<Rc<FxHashMap<u64, crate::style::Style>>>,
         defaults: Cell<Option<Colors>>,
+        // bumped by every change, snapshot is rebuilt once per generation.
+        generation: Cell<u64>,
+        // bumped when colors drawn before change, new definitions are not drawn yet.
+        colors: Cell<u64>,
+        snapshot: RefCell<Option<Rc<super::Highlights>>>,
     }
 
     impl Default for HighlightDefinitions {
@@ -27,8 +66,11 @@ mod imp {
             };
             styles.insert(0, crate::style::Style::new(defaults));
             HighlightDefinitions {
-                styles: RefCell::new(styles),
+                styles: RefCell::new(Rc::new(styles)),
                 defaults: Some(defaults).into(),
+                generation: Cell::new(0),
+                colors: Cell::new(0),
+                snapshot: RefCell::new(None),
             }
         }
     }
@@ -50,7 +92,11 @@ mod imp {
             // .unwrap_or_else(|| styles.get(&0).expect("DefaultHighlights not set yet."))
         }
         pub fn set(&self, k: u64, style: crate::style::Style) {
-            self.styles.borrow_mut().insert(k, style);
+            let old = self.styles_mut().insert(k, style);
+            if old.map_or(false, |old| old != style) {
+                self.recolored();
+            }
+            self.changed();
         }
 
         pub fn defaults(&self) -> Option<&Colors> {
@@ -59,8 +105,44 @@ mod imp {
 
         pub fn set_defaults(&self, defaults: Colors) {
             self.defaults.replace(Some(defaults));
-            let styles = unsafe { &mut *self.styles.as_ptr() };
-            styles.insert(0, crate::style::Style::new(defaults));
+            self.styles_mut()
+                .insert(0, crate::style::Style::new(defaults));
+            self.recolored();
+            self.changed();
+        }
+
+        pub fn snapshot(&self) -> Rc<super::Highlights> {
+            self.snapshot
+                .borrow_mut()
+                .get_or_insert_with(|| {
+                    Rc::new(super::Highlights {
+                        generation: self.generation.get(),
+                        colors: self.colors.get(),
+                        styles: Rc::clone(&self.styles.borrow()),
+                        defaults: self.defaults.get(),
+                    })
+                })
+                .clone()
+        }
+
+        pub fn colors(&self) -> u64 {
+            self.colors.get()
+        }
+
+        /// Styles to change, cached snapshot dropped first so the map is
+        /// copied only if a snapshot taken by drawing is still alive.
+        fn styles_mut(&self) -> std::cell::RefMut<FxHashMap<u64, crate::style::Style>> {
+            self.snapshot.replace(None);
+            std::cell::RefMut::map(self.styles.borrow_mut(), Rc::make_mut)
+        }
+
+        fn recolored(&self) {
+            self.colors.set(self.colors.get() + 1);
+        }
+
+        fn changed(&self) {
+            self.generation.set(self.generation.get() + 1);
+            self.snapshot.replace(None);
         }
     }
 }
@@ -76,6 +158,17 @@ impl HighlightDefinitions {
         glib::Object::new::<Self>(&[]).expect("Failed to initialize Timer object")
     }
 
+    /// Definitions with `background` used until neovim sends default colors.
+    pub fn with_background(background: Color) -> HighlightDefinitions {
+        let hldefs = Self::new();
+        let defaults = *hldefs.defaults().unwrap();
+        hldefs.set_defaults(Colors {
+            background: Some(background),
+            ..defaults
+        });
+        hldefs
+    }
+
     fn imp(&self) -> &imp::HighlightDefinitions {
         imp::HighlightDefinitions::from_instance(self)
     }
@@ -95,4 +188,102 @@ impl HighlightDefinitions {
     pub fn set_defaults(&self, defaults: Colors) {
         self.imp().set_defaults(defaults)
     }
+
+    /// Generation of colors, bumped when colors already drawn change.
+    pub fn colors(&self) -> u64 {
+        self.imp().colors()
+    }
+
+    /// Copy of current definitions, shared until they change again.
+    pub fn snapshot(&self) -> Rc<Highlights> {
+        self.imp().snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::ColorExt;
+
+    #[test]
+    fn test_startup_background() {
+        let startup = Color::from_u64(0x282c34);
+        let hldefs = HighlightDefinitions::with_background(startup);
+        assert_eq!(hldefs.defaults().unwrap().background, Some(startup));
+        let style = hldefs.get(HighlightDefinitions::DEFAULT).unwrap();
+        assert_eq!(style.colors.background, Some(startup));
+
+        // overridden by default colors from neovim.
+        let colors = Colors {
+            background: Some(Color::from_u64(0xffffff)),
+            foreground: Some(Color::from_u64(0x000000)),
+            special: None,
+        };
+        hldefs.set_defaults(colors);
+        assert_eq!(hldefs.defaults(), Some(&colors));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let hldefs = HighlightDefinitions::new();
+        let snapshot = hldefs.snapshot();
+        assert!(Rc::ptr_eq(&snapshot, &hldefs.snapshot()));
+        assert_eq!(snapshot.get(1), None);
+
+        let style = style::Style::new(*hldefs.defaults().unwrap());
+        hldefs.set(1, style);
+        let changed = hldefs.snapshot();
+        assert!(changed.generation() > snapshot.generation());
+        assert_eq!(changed.get(1), Some(&style));
+        // taken one stays as it was.
+        assert_eq!(snapshot.get(1), None);
+
+        // new defaults keep other definitions.
+        hldefs.set_defaults(*hldefs.defaults().unwrap());
+        assert_eq!(hldefs.snapshot().get(1), Some(&style));
+        assert_eq!(hldefs.snapshot().defaults(), hldefs.defaults());
+    }
+
+    #[test]
+    fn test_snapshot_shares_styles() {
+        let hldefs = HighlightDefinitions::new();
+        let style = style::Style::new(*hldefs.defaults().unwrap());
+        let snapshot = hldefs.snapshot();
+        let styles = Rc::as_ptr(&snapshot.styles);
+        // changed while taken, copied once for it.
+        hldefs.set(1, style);
+        let changed = hldefs.snapshot();
+        assert!(!Rc::ptr_eq(&snapshot.styles, &changed.styles));
+        assert_eq!(Rc::as_ptr(&snapshot.styles), styles);
+
+        // nothing holds it, changed in place.
+        drop(snapshot);
+        let styles = Rc::as_ptr(&changed.styles);
+        drop(changed);
+        hldefs.set(2, style);
+        assert_eq!(Rc::as_ptr(&hldefs.snapshot().styles), styles);
+    }
+
+    #[test]
+    fn test_colors() {
+        let hldefs = HighlightDefinitions::new();
+        let colors = hldefs.colors();
+        let style = style::Style::new(*hldefs.defaults().unwrap());
+        // new definition, nothing drawn by it yet.
+        hldefs.set(1, style);
+        assert_eq!(hldefs.colors(), colors);
+        hldefs.set(1, style);
+        assert_eq!(hldefs.colors(), colors);
+
+        let mut changed = style;
+        changed.blend = 50;
+        hldefs.set(1, changed);
+        assert!(hldefs.colors() > colors);
+        assert_eq!(hldefs.snapshot().colors(), hldefs.colors());
+
+        // colorscheme changed.
+        let colors = hldefs.colors();
+        hldefs.set_defaults(*hldefs.defaults().unwrap());
+        assert!(hldefs.colors() > colors);
+    }
 }
@@ -0,0 +1,121 @@
+use super::TextCell;
+
+/// Schemes starting a url in text of cells.
+const SCHEMES: [&str; 4] = ["https://", "http://", "file://", "mailto:"];
+
+/// A url on screen, `len` cells start from `col` of its row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    pub col: usize,
+    pub len: usize,
+    pub url: String,
+}
+
+/// Url covering cell `col` of `cells`, e.g. printed in a terminal or a markdown preview.
+///
+/// A url ends at whitespace or quotes, trailing punctuation is left out, closing
+/// parentheses too unless opened inside the url.
+pub fn link_at(cells: &[TextCell], col: usize) -> Option<Link> {
+    let mut text = String::new();
+    // byte offset of text to column.
+    let mut columns = Vec::with_capacity(cells.len());
+    for (col, cell) in cells.iter().enumerate() {
+        columns.push((text.len(), col));
+        text.push_str(&cell.text);
+    }
+    let column = |offset: usize| {
+        columns
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= offset)
+            .map_or(0, |(_, col)| *col)
+    };
+
+    SCHEMES
+        .iter()
+        .flat_map(|scheme| {
+            text.match_indices(scheme)
+                .map(move |(start, _)| (start, scheme.len()))
+        })
+        .filter_map(|(start, scheme)| {
+            let end = url_end(&text[start..]);
+            (end > scheme).then(|| (start, start + end))
+        })
+        .find_map(|(start, end)| {
+            let first = column(start);
+            // include trailing empty cells of the last wide char.
+            let mut last = column(end - 1);
+            while cells
+                .get(last + 1)
+                .map_or(false, |cell| cell.text.is_empty())
+            {
+                last += 1;
+            }
+            (first..=last).contains(&col).then(|| Link {
+                col: first,
+                len: last + 1 - first,
+                url: text[start..end].to_string(),
+            })
+        })
+}
+
+/// Length in bytes of url starting `text`.
+fn url_end(text: &str) -> usize {
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>'))
+        .unwrap_or(text.len());
+    let mut url = &text[..end];
+    loop {
+        let trimmed = url.trim_end_matches(&['.', ',', ';', ':', '!', '?', ']', '}'][..]);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(rest) if trimmed.matches('(').count() < trimmed.matches(')').count() => rest,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url.len();
+        }
+        url = trimmed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    #[test]
+    fn test_link_at() {
+        let cells = testing::line("see https://example.com/a?b=1. for more", |_| None);
+        let link = Link {
+            col: 4,
+            len: 25,
+            url: "https://example.com/a?b=1".to_string(),
+        };
+        assert_eq!(link_at(&cells, 4), Some(link.clone()));
+        assert_eq!(link_at(&cells, 28), Some(link));
+        // trailing dot is not part of it.
+        assert_eq!(link_at(&cells, 29), None);
+        assert_eq!(link_at(&cells, 0), None);
+    }
+
+    #[test]
+    fn test_link_parentheses() {
+        let cells = testing::line("(https://en.wikipedia.org/wiki/Rust_(language))", |_| None);
+        let link = link_at(&cells, 1).unwrap();
+        assert_eq!(link.url, "https://en.wikipedia.org/wiki/Rust_(language)");
+        assert_eq!((link.col, link.len), (1, link.url.len()));
+
+        let cells = testing::line("[doc](http://example.com)", |_| None);
+        assert_eq!(link_at(&cells, 6).unwrap().url, "http://example.com");
+    }
+
+    #[test]
+    fn test_link_wide_chars() {
+        let cells = testing::line("中 file:///tmp/中", |_| None);
+        let link = link_at(&cells, 10).unwrap();
+        assert_eq!(link.url, "file:///tmp/中");
+        assert_eq!((link.col, link.len), (3, 14));
+        // scheme alone is no link.
+        assert_eq!(link_at(&testing::line("https:// x", |_| None), 0), None);
+    }
+}
@@ -1,7 +1,7 @@
 use std::{cell::Cell, rc::Rc};
 
 use glib::subclass::prelude::*;
-use gtk::prelude::*;
+use gtk::{gdk, prelude::*};
 use parking_lot::RwLock;
 use relm4::{
     factory::{Factory, FactoryPrototype, FactoryVec},
@@ -10,21 +10,21 @@ use relm4::{
 
 use crate::{
     app::AppMessage,
-    bridge::{MessageKind, StyledContent},
+    bridge::{GridLineCell, MessageKind, Register, StyledContent},
     metrics::Metrics,
 };
 
 use super::HighlightDefinitions;
 
 mod imp {
-    use std::{cell::Cell, rc::Rc};
+    use std::{cell::Cell, rc::Rc, sync::atomic};
 
-    use glib::{ffi::g_unichar_iswide, translate::from_glib};
     use gtk::{prelude::*, subclass::prelude::*};
     use once_cell::sync::OnceCell;
     use parking_lot::RwLock;
 
     use crate::{
+        app::{AmbiWidthDouble, EmojiDoubleWidth},
         bridge::{GridLineCell, MessageKind, StyledContent},
         metrics::Metrics,
         vimview::{HighlightDefinitions, VimGridView},
@@ -155,41 +155,13 @@ mod imp {
     }
 
     impl VimMessageView {
-        pub fn set_styled_context(&self, styled_content: StyledContent) {
-            let (mut max_cols, mut cols, mut rows) = (1, 1, 0);
-            let mut lines: Vec<Vec<GridLineCell>> = Vec::new();
-            lines.push(Vec::new());
-            for (style, text) in styled_content.iter() {
-                for (no, line) in text.lines().enumerate() {
-                    if no > 0 {
-                        max_cols = max_cols.max(cols);
-                        lines.push(Vec::with_capacity(max_cols));
-                        rows += 1;
-                        cols = 0;
-                    }
-                    for c in line.chars() {
-                        let double_width: bool = unsafe { from_glib(g_unichar_iswide(c as u32)) };
-                        lines[rows].push(GridLineCell {
-                            text: String::from(c),
-                            hldef: Some(*style),
-                            repeat: None,
-                            double_width,
-                        });
-                        cols += 1;
-                        if double_width {
-                            lines[rows].push(GridLineCell {
-                                text: String::from(""),
-                                hldef: Some(*style),
-                                repeat: None,
-                                double_width: false,
-                            });
-                            cols += 1;
-                        }
-                    }
-                }
-            }
-            cols = max_cols.max(cols);
-            rows = rows + 1;
+        pub fn set_styled_context(&self, styled_content: StyledContent, max_cols: usize) {
+            let ambiwidth_double = AmbiWidthDouble.load(atomic::Ordering::Relaxed);
+            let emoji = EmojiDoubleWidth.load(atomic::Ordering::Relaxed);
+            let mut lines =
+                super::message_lines(&styled_content, max_cols, ambiwidth_double, emoji);
+            let cols = lines.iter().map(Vec::len).max().unwrap_or(0).max(1);
+            let rows = lines.len();
             let textbuf = self.view.textbuf();
             textbuf.resize(rows, cols);
             for (no, cells) in lines.iter_mut().enumerate() {
@@ -220,6 +192,80 @@ mod imp {
         pub fn set_kind(&self, kind: MessageKind) {
             self.kind.set(kind);
         }
+        pub fn redraw(&self) {
+            self.view.queue_draw();
+        }
+    }
+}
+
+/// Cells of message by lines, lines longer than `max_cols` wrapped.
+///
+/// A wide character never splits over two rows, it starts next row instead.
+fn message_lines(
+    styled_content: &StyledContent,
+    max_cols: usize,
+    ambiwidth_double: bool,
+    emoji: bool,
+) -> Vec<Vec<GridLineCell>> {
+    let mut lines: Vec<Vec<GridLineCell>> = vec![Vec::new()];
+    for (style, text) in styled_content.iter() {
+        for (no, line) in text.lines().enumerate() {
+            if no > 0 {
+                lines.push(Vec::new());
+            }
+            for grapheme in super::emoji::graphemes(line) {
+                let double_width = is_double_width(grapheme, ambiwidth_double, emoji);
+                let width = if double_width { 2 } else { 1 };
+                let cols = lines.last().map_or(0, Vec::len);
+                if cols > 0 && cols + width > max_cols {
+                    lines.push(Vec::new());
+                }
+                let cells = lines.last_mut().unwrap();
+                cells.push(GridLineCell {
+                    text: String::from(grapheme),
+                    hldef: Some(*style),
+                    repeat: None,
+                    double_width,
+                });
+                if double_width {
+                    cells.push(GridLineCell {
+                        text: String::from(""),
+                        hldef: Some(*style),
+                        repeat: None,
+                        double_width: false,
+                    });
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Text of message without highlights, as copied to clipboard.
+fn plain_text(styled_content: &StyledContent) -> String {
+    styled_content
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect()
+}
+
+/// Wide characters take two cells, ambiguous width ones too under `ambiwidth=double`
+/// and emoji under `emoji`.
+fn is_double_width(grapheme: &str, ambiwidth_double: bool, emoji: bool) -> bool {
+    use glib::{ffi, translate::from_glib};
+    if emoji && super::emoji::is_emoji(grapheme) {
+        return true;
+    }
+    let c = match grapheme.chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    unsafe {
+        if ambiwidth_double {
+            from_glib(ffi::g_unichar_iswide_cjk(c as u32))
+        } else {
+            from_glib(ffi::g_unichar_iswide(c as u32))
+        }
     }
 }
 
@@ -236,6 +282,7 @@ impl VimMessageView {
         hldefs: Rc<RwLock<HighlightDefinitions>>,
         metrics: Rc<Cell<Metrics>>,
         pctx: Rc<pango::Context>,
+        max_cols: usize,
     ) -> VimMessageView {
         let this: VimMessageView =
             glib::Object::new(&[]).expect("Failed to create `VimMessageView`.");
@@ -247,20 +294,34 @@ impl VimMessageView {
         imp.set_hldefs(hldefs);
         imp.set_metrics(metrics);
         imp.set_pango_context(pctx);
-        imp.set_styled_context(styled_content);
+        imp.set_styled_context(styled_content, max_cols);
         this.set_halign(gtk::Align::End);
         this.set_valign(gtk::Align::Start);
         this.set_overflow(gtk::Overflow::Visible);
         this
     }
+    /// Draw content again, e.g. by new colors.
+    pub fn redraw(&self) {
+        self.imp().redraw();
+    }
     fn imp(&self) -> &imp::VimMessageView {
         imp::VimMessageView::from_instance(self)
     }
 }
 
+/// Time of message fading out before dismissed.
+pub const MESSAGE_FADE: std::time::Duration = std::time::Duration::from_millis(300);
+
 pub struct VimMessage {
+    id: u64,
+    fading: bool,
+    dismissed: bool,
+    collapsed: bool,
+    max_cols: usize,
     kind: MessageKind,
     styled_content: StyledContent,
+    // generation of highlight colors to draw by.
+    colors: u64,
     hldefs: Rc<RwLock<HighlightDefinitions>>,
     metrics: Rc<Cell<Metrics>>,
     pctx: Rc<pango::Context>,
@@ -275,8 +336,14 @@ impl VimMessage {
         pctx: Rc<pango::Context>,
     ) -> VimMessage {
         VimMessage {
+            id: 0,
+            fading: false,
+            dismissed: false,
+            collapsed: false,
+            max_cols: usize::MAX,
             kind,
             styled_content,
+            colors: hldefs.read().colors(),
             hldefs,
             metrics,
             pctx,
@@ -286,11 +353,75 @@ impl VimMessage {
     pub fn kind(&self) -> MessageKind {
         self.kind
     }
+
+    /// Serial of message, auto-dismiss finds it by when it fires.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fading
+    }
+
+    /// Fade out in [`MESSAGE_FADE`], still takes its place till dismissed.
+    pub fn fade(&mut self) {
+        self.fading = true;
+    }
+
+    pub fn is_dismissed(&self) -> bool {
+        self.dismissed
+    }
+
+    /// Hide message, removed once no message above it is left.
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Hide older message behind newer ones, counted by "+N more" instead.
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+    }
+
+    /// Wrap lines longer than `max_cols`, before the message is shown.
+    pub fn set_max_cols(&mut self, max_cols: usize) {
+        self.max_cols = max_cols.max(1);
+    }
+
+    /// Redraw by highlight colors of generation `colors`, e.g. after colorscheme changed.
+    pub fn set_colors(&mut self, colors: u64) {
+        self.colors = colors;
+    }
+
+    /// Frame of message by default colors.
+    fn css(&self) -> String {
+        let guard = self.hldefs.read();
+        let colors = guard.defaults().unwrap();
+        let metrics = self.metrics.get();
+        let fg = colors.foreground.unwrap();
+        format!(
+            "border: 1px solid {}; padding: {}px {}px; background: {};",
+            fg.to_str(),
+            metrics.height() / 2.,
+            metrics.width(),
+            colors.background.unwrap().to_str()
+        )
+    }
 }
 
 #[derive(Debug)]
 pub struct MessageViewWidgets {
     view: VimMessageView,
+    // generation of highlight colors the frame drawn by.
+    colors: Cell<u64>,
+    fading: Cell<bool>,
 }
 
 impl FactoryPrototype for VimMessage {
@@ -302,10 +433,8 @@ impl FactoryPrototype for VimMessage {
     fn init_view(
         &self,
         _key: &<Self::Factory as Factory<Self, Self::View>>::Key,
-        _sender: relm4::Sender<AppMessage>,
+        sender: relm4::Sender<AppMessage>,
     ) -> Self::Widgets {
-        let guard = self.hldefs.read();
-        let colors = guard.defaults().unwrap();
         let metrics = self.metrics.get();
         let view = VimMessageView::new(
             self.kind,
@@ -313,36 +442,120 @@ impl FactoryPrototype for VimMessage {
             self.hldefs.clone(),
             self.metrics.clone(),
             self.pctx.clone(),
+            self.max_cols,
         );
         view.set_margin_top(metrics.height() as _);
         view.set_margin_end(metrics.width() as _);
-        let fg = colors.foreground.unwrap();
         if matches!(self.kind, MessageKind::Echo) {
         } else {
             //
         }
-        let style = format!(
-            "border: 1px solid {}; padding: {}px {}px; background: {};",
-            fg.to_str(),
-            metrics.height() / 2.,
-            metrics.width(),
-            colors.background.unwrap().to_str()
-        );
+        // text of messages can not be selected, e.g. a stack trace copied whole.
+        view.set_tooltip_text(Some("Right click to copy"));
+        let click_listener = gtk::GestureClick::builder()
+            .button(gdk::BUTTON_SECONDARY)
+            .name("message-click-listener")
+            .build();
+        let text = plain_text(&self.styled_content);
+        click_listener.connect_pressed(move |_, _, _, _| {
+            log::debug!(
+                "copy message of {} chars to clipboard",
+                text.chars().count()
+            );
+            sender
+                .send(AppMessage::WriteClipboard(
+                    Register::Clipboard,
+                    text.clone(),
+                ))
+                .unwrap();
+        });
+        view.add_controller(&click_listener);
+        let style = self.css();
         log::info!("inline css for message: {}", &style);
         view.inline_css(style.as_bytes());
-        MessageViewWidgets { view }
+        let colors = Cell::new(self.colors);
+        MessageViewWidgets {
+            view,
+            colors,
+            fading: Cell::new(false),
+        }
     }
 
     fn position(&self, _: &usize) {}
     fn view(&self, _: &usize, widgets: &Self::Widgets) {
-        // let guard = self.hldefs.read();
-        // let colors = guard.defaults().unwrap();
-        // widgets.view.inline_css(
-        //     format!("border 1px solid {}", colors.foreground.unwrap().to_str()).as_bytes(),
-        // );
-        widgets.view.show();
+        // colorscheme changed since drawn.
+        if widgets.colors.replace(self.colors) != self.colors {
+            widgets.view.inline_css(self.css().as_bytes());
+            widgets.view.redraw();
+        }
+        if self.fading && !widgets.fading.replace(true) {
+            let style = format!(
+                "opacity: 0; transition: opacity {}ms ease-out;",
+                MESSAGE_FADE.as_millis()
+            );
+            widgets.view.inline_css(style.as_bytes());
+        }
+        widgets.view.set_visible(!self.dismissed && !self.collapsed);
     }
     fn root_widget(widgets: &Self::Widgets) -> &Self::Root {
         &widgets.view
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Vec<GridLineCell>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|cells| cells.iter().map(|cell| cell.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let content = vec![
+            (1, "E5108: Error".to_string()),
+            (2, "\nstack traceback:".to_string()),
+        ];
+        assert_eq!(plain_text(&content), "E5108: Error\nstack traceback:");
+        assert_eq!(plain_text(&Vec::new()), "");
+    }
+
+    #[test]
+    fn test_message_lines() {
+        let content = vec![(1, "abcdef\ngh".to_string()), (2, "ij".to_string())];
+        let lines = message_lines(&content, usize::MAX, false, true);
+        assert_eq!(text(&lines), ["abcdef", "ghij"]);
+        assert_eq!(lines[1][3].hldef, Some(2));
+
+        // long line of a stack trace wraps.
+        let lines = message_lines(&content, 4, false, true);
+        assert_eq!(text(&lines), ["abcd", "ef", "ghij"]);
+        assert!(lines.iter().all(|cells| cells.len() <= 4));
+
+        // wide character starts next row instead of split.
+        let content = vec![(1, "abc中".to_string())];
+        let lines = message_lines(&content, 4, false, true);
+        assert_eq!(text(&lines), ["abc", "中"]);
+        assert_eq!(lines[1].len(), 2);
+    }
+
+    #[test]
+    fn test_is_double_width() {
+        assert!(!is_double_width("a", false, true));
+        assert!(!is_double_width("a", true, true));
+        assert!(is_double_width("中", false, true));
+        assert!(is_double_width("中", true, true));
+        // ambiguous width.
+        assert!(!is_double_width("×", false, true));
+        assert!(is_double_width("×", true, true));
+        assert!(!is_double_width("─", false, true));
+        assert!(is_double_width("─", true, true));
+        // text presentation emoji wide only under `emoji`.
+        assert!(is_double_width("☺", false, true));
+        assert!(!is_double_width("☺", false, false));
+        assert!(is_double_width("👨\u{200d}👩\u{200d}👧", false, true));
+    }
+}
@@ -1,8 +1,27 @@
 //mod commandview;
+mod background;
+mod colorcolumn;
+mod dim;
+mod emoji;
+mod find;
+mod floatborder;
+mod floats;
 mod gridview;
+mod gutter;
 mod highlights;
+mod links;
 mod messageview;
+mod msggrid;
+mod placeholder;
+mod prompt;
+mod scroll;
+mod scrollbar;
+mod separator;
+#[cfg(test)]
+mod testing;
 mod textbuf;
+mod underline;
+mod whitespace;
 mod widgets;
 
 use std::{
@@ -11,11 +30,21 @@ use std::{
     rc::Rc,
 };
 
+pub use colorcolumn::{colorcolumns, COLORCOLUMN_HLGROUP};
+pub use dim::{focused_grid, is_dimmed};
+pub use emoji::{glyph_clusters, share_width};
+pub use find::{find, Match};
+pub use floats::FloatContainer;
 pub use gridview::VimGridView;
 pub use highlights::HighlightDefinitions;
-pub use messageview::{MessageViewWidgets, VimMessage, VimMessageView};
+pub use messageview::{MessageViewWidgets, VimMessage, VimMessageView, MESSAGE_FADE};
+pub use msggrid::MessageGrid;
+pub use placeholder::PLACEHOLDER_HLGROUP;
+pub use prompt::{is_more_prompt, MORE_PROMPT_HLGROUP};
+pub use scrollbar::Viewport;
 pub use textbuf::{TextCell, TextLine};
-pub use widgets::{VimGrid, VimGridWidgets};
+pub use whitespace::WHITESPACE_HLGROUP;
+pub use widgets::{connect_input, VimGrid, VimGridWidgets};
 
 #[derive(Clone, Debug)]
 pub struct TextBuf(Rc<RefCell<textbuf::TextBuf>>);
@@ -0,0 +1,86 @@
+/// Grid of messages without ext_messages, placed by `msg_set_pos`.
+///
+/// Rendered in the message area over all windows instead of as a window grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MessageGrid {
+    grid: Option<u64>,
+    row: usize,
+    scrolled: bool,
+    separator: Option<char>,
+}
+
+impl MessageGrid {
+    pub fn set_position(&mut self, grid: u64, row: usize, scrolled: bool, separator: &str) {
+        self.grid.replace(grid);
+        self.row = row;
+        self.scrolled = scrolled;
+        self.separator = separator.chars().next();
+    }
+
+    /// Forget the message grid once destroyed, returns whether it was.
+    pub fn destroy(&mut self, grid: u64) -> bool {
+        if self.is_message_grid(grid) {
+            *self = MessageGrid::default();
+            return true;
+        }
+        false
+    }
+
+    pub fn is_message_grid(&self, grid: u64) -> bool {
+        self.grid == Some(grid)
+    }
+
+    pub fn grid(&self) -> Option<u64> {
+        self.grid
+    }
+
+    /// Messages scrolled over windows, more than the message area.
+    pub fn scrolled(&self) -> bool {
+        self.scrolled
+    }
+
+    /// Character of rule drawn in the row above scrolled messages.
+    pub fn separator(&self) -> Option<char> {
+        self.separator.filter(|_| self.scrolled && self.row > 0)
+    }
+
+    /// Top of message area in pixel.
+    pub fn top(&self, cell_height: f64) -> f64 {
+        self.row as f64 * cell_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_grid() {
+        let mut msggrid = MessageGrid::default();
+        assert_eq!(msggrid.grid(), None);
+        assert!(!msggrid.is_message_grid(1));
+
+        msggrid.set_position(3, 20, false, "─");
+        assert!(msggrid.is_message_grid(3));
+        assert!(!msggrid.is_message_grid(1));
+        assert_eq!(msggrid.top(17.), 340.);
+        assert_eq!(msggrid.separator(), None);
+
+        // messages grow and scroll the screen.
+        msggrid.set_position(3, 12, true, "─");
+        assert!(msggrid.scrolled());
+        assert_eq!(msggrid.top(17.), 204.);
+        assert_eq!(msggrid.separator(), Some('─'));
+
+        // no room above for the rule.
+        msggrid.set_position(3, 0, true, "─");
+        assert_eq!(msggrid.separator(), None);
+        msggrid.set_position(3, 12, true, "");
+        assert_eq!(msggrid.separator(), None);
+
+        assert!(!msggrid.destroy(1));
+        assert!(msggrid.is_message_grid(3));
+        assert!(msggrid.destroy(3));
+        assert_eq!(msggrid, MessageGrid::default());
+    }
+}
@@ -0,0 +1,121 @@
+use super::TextCell;
+
+/// highlight group marking cells reserved by plugins for inline images.
+pub const PLACEHOLDER_HLGROUP: &str = "ReovimImagePlaceholder";
+
+/// Rectangle of `rows`x`cols` cells from `row`,`col` reserved for an image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placeholder {
+    pub row: usize,
+    pub col: usize,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// Placeholders of grid, runs of cells highlighted by `hlid` at the same
+/// columns of consecutive rows are joined into one rectangle.
+pub fn detect(rows: &[&[TextCell]], hlid: u64) -> Vec<Placeholder> {
+    let mut placeholders: Vec<Placeholder> = Vec::new();
+    for (row, cells) in rows.iter().enumerate() {
+        let mut col = 0;
+        while col < cells.len() {
+            if cells[col].hldef != Some(hlid) {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < cells.len() && cells[col].hldef == Some(hlid) {
+                col += 1;
+            }
+            let cols = col - start;
+            let above = placeholders.iter_mut().find(|placeholder| {
+                placeholder.row + placeholder.rows == row
+                    && placeholder.col == start
+                    && placeholder.cols == cols
+            });
+            match above {
+                Some(placeholder) => placeholder.rows += 1,
+                None => placeholders.push(Placeholder {
+                    row,
+                    col: start,
+                    rows: 1,
+                    cols,
+                }),
+            }
+        }
+    }
+    placeholders
+}
+
+/// Region of `placeholder` `(x, y, width, height)` in pixel.
+pub fn region(
+    placeholder: &Placeholder,
+    cell_width: f64,
+    cell_height: f64,
+) -> (f64, f64, f64, f64) {
+    (
+        placeholder.col as f64 * cell_width,
+        placeholder.row as f64 * cell_height,
+        placeholder.cols as f64 * cell_width,
+        placeholder.rows as f64 * cell_height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    const IMAGE: u64 = 7;
+
+    // `#` highlighted as placeholder.
+    fn hldef(c: char) -> Option<u64> {
+        Some(if c == '#' { IMAGE } else { 1 })
+    }
+
+    #[test]
+    fn test_placeholder_region() {
+        let rows = testing::rows(
+            &[
+                "text ##### ",
+                "more ##### #",
+                "     #####  ",
+                "  ###       ",
+            ],
+            hldef,
+        );
+        let placeholders = detect(&rows, IMAGE);
+        assert_eq!(
+            placeholders,
+            vec![
+                Placeholder {
+                    row: 0,
+                    col: 5,
+                    rows: 3,
+                    cols: 5
+                },
+                Placeholder {
+                    row: 1,
+                    col: 11,
+                    rows: 1,
+                    cols: 1
+                },
+                Placeholder {
+                    row: 3,
+                    col: 2,
+                    rows: 1,
+                    cols: 3
+                },
+            ]
+        );
+        assert_eq!(region(&placeholders[0], 8., 17.), (40., 0., 40., 51.));
+        assert_eq!(region(&placeholders[2], 8., 17.), (16., 51., 24., 17.));
+    }
+
+    #[test]
+    fn test_no_placeholder() {
+        let rows = testing::rows(&["plain text"], hldef);
+        assert!(detect(&rows, IMAGE).is_empty());
+        assert!(detect(&[], IMAGE).is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use super::TextCell;
+
+/// highlight group of neovim's more-prompt.
+pub const MORE_PROMPT_HLGROUP: &str = "MoreMsg";
+
+const MORE_PROMPT: &str = "-- More --";
+
+/// Line shows neovim's more-prompt, `-- More --` highlighted by `hlid` if known.
+pub fn is_more_prompt(cells: &[TextCell], hlid: Option<u64>) -> bool {
+    let start = match cells.iter().position(|cell| !cell.text.trim().is_empty()) {
+        Some(start) => start,
+        None => return false,
+    };
+    if let Some(hlid) = hlid {
+        if cells[start].hldef != Some(hlid) {
+            return false;
+        }
+    }
+    let text: String = cells[start..]
+        .iter()
+        .take(MORE_PROMPT.len())
+        .map(|cell| &*cell.text)
+        .collect();
+    text.starts_with(MORE_PROMPT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    const MOREMSG: u64 = 9;
+
+    #[test]
+    fn test_is_more_prompt() {
+        let cells = testing::line("-- More --                ", |_| Some(MOREMSG));
+        assert!(is_more_prompt(&cells, Some(MOREMSG)));
+        assert!(is_more_prompt(&cells, None));
+        // typed by user, not highlighted as prompt.
+        let cells = testing::line("-- More --", |_| Some(1));
+        assert!(!is_more_prompt(&cells, Some(MOREMSG)));
+        let cells = testing::line("-- INSERT --", |_| Some(MOREMSG));
+        assert!(!is_more_prompt(&cells, Some(MOREMSG)));
+        let cells = testing::line("          ", |_| Some(MOREMSG));
+        assert!(!is_more_prompt(&cells, Some(MOREMSG)));
+    }
+}
@@ -0,0 +1,89 @@
+/// Duration of smooth scrolling in microseconds.
+const DURATION: i64 = 150_000;
+
+/// Scroll animation of a single grid, driven by frame time of its own widget.
+///
+/// Content is drawn at final position shifted by `offset`, which decays to zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrollAnimation {
+    // pixels shifted at `start`.
+    from: f64,
+    // frame time in microseconds, `None` if not animating.
+    start: Option<i64>,
+}
+
+impl ScrollAnimation {
+    /// Scroll content by `delta` pixels at frame time `now`,
+    /// keeps remaining offset of the running animation.
+    pub fn start(&mut self, delta: f64, now: i64) {
+        let remaining = self.offset(now).unwrap_or(0.);
+        self.from = remaining + delta;
+        self.start = Some(now);
+    }
+
+    /// Offset in pixels at frame time `now`, `None` once finished.
+    pub fn offset(&self, now: i64) -> Option<f64> {
+        let start = self.start?;
+        let elapsed = (now - start).max(0);
+        if elapsed >= DURATION {
+            return None;
+        }
+        let t = elapsed as f64 / DURATION as f64;
+        // ease out cubic.
+        let progress = 1. - (1. - t).powi(3);
+        Some(self.from * (1. - progress))
+    }
+
+    /// Drop finished animation, returns whether it still running.
+    pub fn tick(&mut self, now: i64) -> bool {
+        if self.offset(now).is_none() {
+            *self = ScrollAnimation::default();
+            return false;
+        }
+        true
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_grids() {
+        let mut left = ScrollAnimation::default();
+        let mut right = ScrollAnimation::default();
+        assert_eq!(left.offset(0), None);
+
+        left.start(48., 0);
+        assert_eq!(left.offset(0), Some(48.));
+        assert_eq!(right.offset(0), None);
+
+        right.start(-32., 50_000);
+        // right starts later, left keeps going at its own pace.
+        let l = left.offset(50_000).unwrap();
+        let r = right.offset(50_000).unwrap();
+        assert!(l > 0. && l < 48.);
+        assert_eq!(r, -32.);
+
+        // left finished and cleaned up, right still running.
+        assert!(!left.tick(DURATION));
+        assert!(!left.is_running());
+        assert!(right.tick(DURATION));
+        assert!(right.offset(DURATION).unwrap() < 0.);
+        assert!(!right.tick(DURATION + 50_000));
+        assert_eq!(right, ScrollAnimation::default());
+    }
+
+    #[test]
+    fn test_restart_keeps_remaining() {
+        let mut animation = ScrollAnimation::default();
+        animation.start(16., 0);
+        let remaining = animation.offset(DURATION / 2).unwrap();
+        animation.start(16., DURATION / 2);
+        assert_eq!(animation.offset(DURATION / 2), Some(remaining + 16.));
+    }
+}
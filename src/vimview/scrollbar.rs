@@ -0,0 +1,106 @@
+/// Microseconds the scrollbar stays after viewport changed.
+const IDLE: i64 = 1_000_000;
+/// Microseconds the scrollbar takes to fade out.
+const FADE: i64 = 300_000;
+/// Minimal height of thumb in pixels, keeps long buffers visible.
+const MIN_THUMB: f64 = 8.;
+/// Width of thumb in pixels, drawn at the right edge of grid.
+pub const THUMB_WIDTH: f64 = 4.;
+
+/// Lines of buffer shown in window, by `win_viewport`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Viewport {
+    pub top_line: f64,
+    pub bottom_line: f64,
+    pub line_count: f64,
+}
+
+impl Viewport {
+    /// Offset and height of thumb in grid of `height` pixels,
+    /// `None` if the whole buffer is visible.
+    pub fn thumb(&self, height: f64) -> Option<(f64, f64)> {
+        let visible = self.bottom_line - self.top_line;
+        if self.line_count <= 0. || visible >= self.line_count {
+            return None;
+        }
+        let size = (visible / self.line_count * height).clamp(MIN_THUMB.min(height), height);
+        let y = self.top_line / self.line_count * height;
+        Some((y.clamp(0., height - size), size))
+    }
+}
+
+/// Fading of scrollbar, driven by frame time of its grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrollbarFade {
+    // frame time of last viewport change, `None` once faded.
+    shown: Option<i64>,
+}
+
+impl ScrollbarFade {
+    pub fn show(&mut self, now: i64) {
+        self.shown = Some(now);
+    }
+
+    /// Opacity at frame time `now`, `None` once faded out.
+    pub fn alpha(&self, now: i64) -> Option<f64> {
+        let elapsed = (now - self.shown?).max(0);
+        if elapsed < IDLE {
+            Some(1.)
+        } else if elapsed < IDLE + FADE {
+            Some(1. - (elapsed - IDLE) as f64 / FADE as f64)
+        } else {
+            None
+        }
+    }
+
+    /// Drop faded scrollbar, returns whether it still visible.
+    pub fn tick(&mut self, now: i64) -> bool {
+        if self.alpha(now).is_none() {
+            *self = ScrollbarFade::default();
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewport(top_line: f64, bottom_line: f64, line_count: f64) -> Viewport {
+        Viewport {
+            top_line,
+            bottom_line,
+            line_count,
+        }
+    }
+
+    #[test]
+    fn test_thumb() {
+        assert_eq!(viewport(0., 10., 100.).thumb(200.), Some((0., 20.)));
+        assert_eq!(viewport(50., 60., 100.).thumb(200.), Some((100., 20.)));
+        assert_eq!(viewport(90., 100., 100.).thumb(200.), Some((180., 20.)));
+        // whole buffer visible.
+        assert_eq!(viewport(0., 10., 10.).thumb(200.), None);
+        assert_eq!(viewport(0., 0., 0.).thumb(200.), None);
+    }
+
+    #[test]
+    fn test_thumb_min() {
+        assert_eq!(viewport(0., 1., 10000.).thumb(200.), Some((0., MIN_THUMB)));
+        let (y, size) = viewport(9999., 10000., 10000.).thumb(200.).unwrap();
+        assert_eq!(y + size, 200.);
+    }
+
+    #[test]
+    fn test_fade() {
+        let mut fade = ScrollbarFade::default();
+        assert_eq!(fade.alpha(0), None);
+        fade.show(100);
+        assert_eq!(fade.alpha(100), Some(1.));
+        assert_eq!(fade.alpha(100 + IDLE + FADE / 2), Some(0.5));
+        assert!(fade.tick(100 + IDLE));
+        assert!(!fade.tick(100 + IDLE + FADE));
+        assert_eq!(fade, ScrollbarFade::default());
+    }
+}
@@ -0,0 +1,132 @@
+use super::TextCell;
+
+/// highlight groups used by neovim drawing window separators.
+pub const SEPARATOR_HLGROUPS: &[&str] = &["WinSeparator", "VertSplit"];
+
+const VERTICAL: &[&str] = &["│", "┃", "║", "|"];
+const HORIZONTAL: &[&str] = &["─", "━", "═"];
+const CROSSING: &[&str] = &["┼", "├", "┤", "┬", "┴", "╋", "┣", "┫", "┳", "┻"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Separator {
+    /// `len` rows start from `row` at column `col`.
+    Vertical { col: usize, row: usize, len: usize },
+    /// `len` cols start from `col` at row `row`.
+    Horizontal { row: usize, col: usize, len: usize },
+}
+
+fn is_separator(cell: &TextCell, hlids: &[u64]) -> bool {
+    cell.hldef.map_or(false, |id| hlids.contains(&id))
+}
+
+fn is_vertical(cell: &TextCell) -> bool {
+    let text: &str = &cell.text;
+    VERTICAL.contains(&text) || CROSSING.contains(&text)
+}
+
+fn is_horizontal(cell: &TextCell) -> bool {
+    let text: &str = &cell.text;
+    HORIZONTAL.contains(&text) || CROSSING.contains(&text)
+}
+
+/// Detect split separators of a single grid by highlight of cells.
+///
+/// Only cells highlighted by one of `hlids` are considered,
+/// the fill character decides direction of the separator.
+pub fn detect(rows: &[&[TextCell]], hlids: &[u64]) -> Vec<Separator> {
+    let mut separators = Vec::new();
+    if hlids.is_empty() {
+        return separators;
+    }
+    let cols = rows.iter().map(|cells| cells.len()).max().unwrap_or(0);
+
+    for col in 0..cols {
+        let mut start = None;
+        for row in 0..=rows.len() {
+            let matched = rows
+                .get(row)
+                .and_then(|cells| cells.get(col))
+                .map_or(false, |cell| is_separator(cell, hlids) && is_vertical(cell));
+            match (matched, start) {
+                (true, None) => start = Some(row),
+                (false, Some(from)) => {
+                    separators.push(Separator::Vertical {
+                        col,
+                        row: from,
+                        len: row - from,
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (row, cells) in rows.iter().enumerate() {
+        let mut start = None;
+        for col in 0..=cells.len() {
+            let matched = cells
+                .get(col)
+                .map_or(false, |cell| is_separator(cell, hlids) && is_horizontal(cell));
+            match (matched, start) {
+                (true, None) => start = Some(col),
+                (false, Some(from)) => {
+                    separators.push(Separator::Horizontal {
+                        row,
+                        col: from,
+                        len: col - from,
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    separators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing;
+
+    const SEP: u64 = 7;
+
+    fn hldef(c: char) -> Option<u64> {
+        match c {
+            '│' | '─' | '┤' => Some(SEP),
+            _ => Some(1),
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let rows = testing::rows(
+            &["abc│def", "abc│def", "───┤def", "ghi│def", "a|c d f"],
+            hldef,
+        );
+        let separators = detect(&rows, &[SEP]);
+        assert_eq!(
+            separators,
+            vec![
+                Separator::Vertical {
+                    col: 3,
+                    row: 0,
+                    len: 4
+                },
+                Separator::Horizontal {
+                    row: 2,
+                    col: 0,
+                    len: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_without_hlgroups() {
+        let rows = testing::rows(&["abc│def"], hldef);
+        assert!(detect(&rows, &[]).is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+//! Fixtures shared by tests of grids.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use parking_lot::RwLock;
+
+use super::textbuf::{TextBuf, TextCell};
+use super::HighlightDefinitions;
+use crate::bridge::GridLineCell;
+
+/// Text buffer of `rows` x `cols` with default font, highlights and metrics.
+pub fn textbuf(rows: usize, cols: usize) -> TextBuf {
+    let pctx = pangocairo::FontMap::default()
+        .unwrap()
+        .create_context()
+        .unwrap();
+    let textbuf = TextBuf::new();
+    textbuf.set_pango_context(Rc::new(pctx));
+    textbuf.set_hldefs(Rc::new(RwLock::new(HighlightDefinitions::new())));
+    textbuf.set_metrics(Rc::new(Cell::new(crate::metrics::Metrics::new())));
+    textbuf.resize(rows, cols);
+    textbuf
+}
+
+/// Cell of `grid_line`, highlight carried forward by parser.
+pub fn grid_cell(text: &str, hldef: u64, repeat: Option<u64>) -> GridLineCell {
+    GridLineCell {
+        text: text.to_string(),
+        hldef: Some(hldef),
+        repeat,
+        double_width: false,
+    }
+}
+
+/// Cell of text buffer.
+pub fn cell(text: &str, hldef: Option<u64>) -> TextCell {
+    TextCell {
+        text: TextCell::intern(text),
+        hldef,
+        ..TextCell::default()
+    }
+}
+
+/// Cells of `text` highlighted by `hldef`, CJK ideographs are followed by an
+/// empty cell like double width chars from neovim.
+pub fn line(text: &str, hldef: impl Fn(char) -> Option<u64>) -> Vec<TextCell> {
+    text.chars()
+        .flat_map(|c| {
+            let wide = ('\u{4e00}'..='\u{9fff}').contains(&c);
+            let hldef = hldef(c);
+            let empty = wide.then(|| cell("", hldef));
+            std::iter::once(cell(&c.to_string(), hldef)).chain(empty)
+        })
+        .collect()
+}
+
+/// Rows of grid from `lines` highlighted by `hldef`, see [`line`].
+/// Cells are leaked to be borrowed like rows of a text buffer.
+pub fn rows(lines: &[&str], hldef: impl Fn(char) -> Option<u64>) -> Vec<&'static [TextCell]> {
+    lines
+        .iter()
+        .map(|text| &*Box::leak(line(text, &hldef).into_boxed_slice()))
+        .collect()
+}
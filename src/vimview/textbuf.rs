@@ -14,6 +14,7 @@ mod imp {
     use glib::subclass::prelude::*;
     use parking_lot::{RwLock, RwLockReadGuard};
 
+    use super::AttrTemplates;
     use crate::vimview::HighlightDefinitions;
 
     #[derive(Derivative)]
@@ -31,6 +32,15 @@ mod imp {
 
         #[derivative(Debug = "ignore")]
         pctx: Option<Rc<pango::Context>>,
+
+        // font of double width cells, `guifontwide`.
+        wide_font: Option<pango::FontDescription>,
+        // shape ligatures of font, off draws `==` and `->` as separate glyphs.
+        ligatures: bool,
+
+        // attributes of cells by highlight, shared instead of built per cell.
+        #[derivative(Debug = "ignore")]
+        templates: AttrTemplates,
     }
 
     impl Default for _TextBuf {
@@ -49,6 +59,9 @@ mod imp {
                 pctx: None,
                 hldefs: None,
                 metrics: None,
+                wide_font: None,
+                ligatures: true,
+                templates: AttrTemplates::default(),
             }
         }
 
@@ -57,18 +70,24 @@ mod imp {
         }
 
         fn reset_cache(&mut self) {
+            // font or colors changed, every template is stale.
+            self.templates.clear();
             let pctx = self.pctx.as_ref().unwrap();
+            let wide_font = self.wide_font.as_ref();
             let hldefs = self.hldefs.as_ref().unwrap().read();
             let metrics = self.metrics.as_ref().unwrap().get();
+            let templates = &mut self.templates;
             self.cells.iter_mut().for_each(|line| {
+                line.invalidate();
                 line.iter_mut().for_each(|cell| {
-                    cell.reset_attrs(pctx, &hldefs, &metrics);
+                    cell.reset_attrs(templates, pctx, &hldefs, &metrics, wide_font);
                 });
             });
         }
 
         pub fn set_hldefs(&mut self, hldefs: Rc<RwLock<HighlightDefinitions>>) {
             self.hldefs.replace(hldefs);
+            self.templates.clear();
         }
 
         pub fn set_metrics(&mut self, metrics: Rc<Cell<crate::metrics::Metrics>>) {
@@ -77,6 +96,16 @@ mod imp {
 
         pub fn set_pango_context(&mut self, pctx: Rc<pango::Context>) {
             self.pctx.replace(pctx);
+            self.templates.clear();
+        }
+
+        pub fn set_wide_font(&mut self, wide_font: Option<pango::FontDescription>) {
+            self.wide_font = wide_font;
+            self.templates.clear();
+        }
+
+        pub fn set_ligatures(&mut self, ligatures: bool) {
+            self.ligatures = ligatures;
         }
 
         fn set_cells(&mut self, row: usize, col: usize, cells: &[crate::bridge::GridLineCell]) {
@@ -91,10 +120,14 @@ mod imp {
                 return;
             }
             let line = &self.cells[row];
-            line.cache.set(None);
+            // the whole line is shaped again, a ligature across the updated
+            // range is never split into pieces shaped apart.
+            line.invalidate();
             let pctx = self.pctx.as_ref().unwrap();
+            let wide_font = self.wide_font.as_ref();
             let hldefs = self.hldefs.as_ref().unwrap().read();
             let metrics = self.metrics.as_ref().unwrap().get();
+            let templates = &mut self.templates;
             let mut expands = Vec::with_capacity(line.len());
             let mut start_index = line.get(col).map(|cell| cell.start_index).unwrap_or(0);
             for cell in cells.iter() {
@@ -104,19 +137,20 @@ mod imp {
                     repeat,
                     double_width,
                 } = cell;
+                // repeated cells share the text.
+                let text = super::TextCell::intern(text);
                 for _ in 0..repeat.unwrap_or(1) {
                     // FIXME: invalid start_index
                     let end_index = start_index + text.len();
-                    let attrs = Vec::new();
                     let mut cell = super::TextCell {
-                        text: text.to_string(),
+                        text: text.clone(),
                         hldef: hldef.clone(),
                         double_width: *double_width,
-                        attrs,
                         start_index,
                         end_index,
+                        ..super::TextCell::default()
                     };
-                    cell.reset_attrs(pctx, &hldefs, &metrics);
+                    cell.reset_attrs(templates, pctx, &hldefs, &metrics, wide_font);
                     log::trace!(
                         "Setting cell {}x{} start_index {} end_index {}",
                         row,
@@ -152,26 +186,108 @@ mod imp {
             );
             let line = &mut self.cells[row];
             line[col..col_to].swap_with_slice(&mut expands);
+            super::join_double_width(line);
             line.iter_mut().fold(0, |start_index, cell| {
                 cell.start_index = start_index;
                 cell.end_index = start_index + cell.text.len();
-                cell.reset_attrs(pctx, &hldefs, &metrics);
+                cell.reset_attrs(templates, pctx, &hldefs, &metrics, wide_font);
                 cell.end_index
             });
         }
 
-        /// drop head of {} rows. leave tail as empty.
-        fn up(&mut self, rows: usize) {
-            let mut cells = _TextBuf::make(self.rows, self.cols);
-            cells[..(self.rows - rows)].swap_with_slice(&mut self.cells[rows..]);
-            self.cells = cells;
+        /// drop head of {} rows in region `[top, bottom)`x`[left, right)`. leave tail as empty.
+        fn up(&mut self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+            self.scroll_rows(top, bottom, left, right, rows as isize);
         }
 
-        /// drop tail of {} rows. leave head as empty.
-        fn down(&mut self, rows: usize) {
-            let mut cells = _TextBuf::make(self.rows, self.cols);
-            cells[rows..].swap_with_slice(&mut self.cells[..(self.rows - rows)]);
-            self.cells = cells;
+        /// drop tail of {} rows in region `[top, bottom)`x`[left, right)`. leave head as empty.
+        fn down(&mut self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+            self.scroll_rows(top, bottom, left, right, -(rows as isize));
+        }
+
+        fn scroll_rows(
+            &mut self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            rows: isize,
+        ) {
+            let bottom = bottom.min(self.rows);
+            if top >= bottom {
+                return;
+            }
+            super::scroll_rows(&mut self.cells[top..bottom], left, right, rows);
+            let n = rows.unsigned_abs().min(bottom - top);
+            if left == 0 && right >= self.cols {
+                // whole lines moved, only vacated lines changed.
+                if rows > 0 {
+                    self.reset_attrs(bottom - n, bottom);
+                } else {
+                    self.reset_attrs(top, top + n);
+                }
+            } else {
+                self.reset_attrs(top, bottom);
+            }
+        }
+
+        /// drop head of {} columns in region `[top, bottom)`x`[left, right)`. leave tail as empty.
+        fn left(&mut self, top: usize, bottom: usize, left: usize, right: usize, cols: usize) {
+            self.scroll_cols(top, bottom, left, right, cols as isize);
+        }
+
+        /// drop tail of {} columns in region `[top, bottom)`x`[left, right)`. leave head as empty.
+        fn right(&mut self, top: usize, bottom: usize, left: usize, right: usize, cols: usize) {
+            self.scroll_cols(top, bottom, left, right, -(cols as isize));
+        }
+
+        fn scroll_cols(
+            &mut self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            cols: isize,
+        ) {
+            let bottom = bottom.min(self.rows);
+            if top >= bottom {
+                return;
+            }
+            super::scroll_cols(&mut self.cells[top..bottom], left, right, cols);
+            self.reset_attrs(top, bottom);
+        }
+
+        /// reset cells of rows `[top, bottom)` and columns `[left, right)` to blank.
+        fn clear_region(&mut self, top: usize, bottom: usize, left: usize, right: usize) {
+            let bottom = bottom.min(self.rows);
+            if top >= bottom {
+                return;
+            }
+            super::clear_region(&mut self.cells[top..bottom], left, right);
+            self.reset_attrs(top, bottom);
+        }
+
+        /// rebuild attributes of rows `[top, bottom)` after byte indexes changed.
+        fn reset_attrs(&mut self, top: usize, bottom: usize) {
+            let bottom = bottom.min(self.rows);
+            if top >= bottom {
+                return;
+            }
+            if let (Some(pctx), Some(hldefs), Some(metrics)) = (
+                self.pctx.as_ref(),
+                self.hldefs.as_ref(),
+                self.metrics.as_ref(),
+            ) {
+                let hldefs = hldefs.read();
+                let metrics = metrics.get();
+                let wide_font = self.wide_font.as_ref();
+                let templates = &mut self.templates;
+                self.cells[top..bottom].iter_mut().for_each(|line| {
+                    line.iter_mut().for_each(|cell| {
+                        cell.reset_attrs(templates, pctx, &hldefs, &metrics, wide_font)
+                    });
+                });
+            }
         }
 
         fn pango_context(&self) -> Rc<pango::Context> {
@@ -182,6 +298,8 @@ mod imp {
     #[derive(Debug, Default)]
     pub struct TextBuf {
         inner: RwLock<_TextBuf>,
+        // generation of highlight colors cached by lines.
+        colors: Cell<u64>,
     }
 
     #[glib::object_subclass]
@@ -198,11 +316,48 @@ mod imp {
     }
 
     impl TextBuf {
-        pub(super) fn up(&self, rows: usize) {
-            self.inner.write().up(rows);
+        pub(super) fn up(&self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+            self.inner.write().up(top, bottom, left, right, rows);
+        }
+        pub(super) fn down(
+            &self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            rows: usize,
+        ) {
+            self.inner.write().down(top, bottom, left, right, rows);
+        }
+        pub(super) fn left(
+            &self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            cols: usize,
+        ) {
+            self.inner.write().left(top, bottom, left, right, cols);
+        }
+        pub(super) fn right(
+            &self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            cols: usize,
+        ) {
+            self.inner.write().right(top, bottom, left, right, cols);
         }
-        pub(super) fn down(&self, rows: usize) {
-            self.inner.write().down(rows);
+        pub(super) fn clear_region(&self, top: usize, bottom: usize, left: usize, right: usize) {
+            log::debug!(
+                "textbuf cleared region rows {}..{} cols {}..{}",
+                top,
+                bottom,
+                left,
+                right
+            );
+            self.inner.write().clear_region(top, bottom, left, right);
         }
 
         pub(super) fn set_cells(
@@ -226,6 +381,18 @@ mod imp {
             self.inner.write().set_pango_context(pctx);
         }
 
+        pub(super) fn set_wide_font(&self, wide_font: Option<pango::FontDescription>) {
+            self.inner.write().set_wide_font(wide_font);
+        }
+
+        pub(super) fn set_ligatures(&self, ligatures: bool) {
+            self.inner.write().set_ligatures(ligatures);
+        }
+
+        pub(super) fn ligatures(&self) -> bool {
+            self.inner.read().ligatures
+        }
+
         pub(super) fn pango_context(&self) -> Rc<pango::Context> {
             self.inner.write().pango_context()
         }
@@ -242,6 +409,12 @@ mod imp {
             self.inner.write().reset_cache();
         }
 
+        pub(super) fn sync_colors(&self, colors: u64) {
+            if self.colors.replace(colors) != colors {
+                self.reset_cache();
+            }
+        }
+
         pub(super) fn clear(&self) {
             log::debug!("textbuf cleared");
             self.inner.write().clear();
@@ -394,6 +567,20 @@ impl TextBuf {
         self.imp().set_pango_context(pctx);
     }
 
+    /// Font of double width cells, the font of pango context if `None`.
+    pub fn set_wide_font(&self, wide_font: Option<pango::FontDescription>) {
+        self.imp().set_wide_font(wide_font);
+    }
+
+    /// Shape ligatures of font, lines are shaped again after cache reset.
+    pub fn set_ligatures(&self, ligatures: bool) {
+        self.imp().set_ligatures(ligatures);
+    }
+
+    pub fn ligatures(&self) -> bool {
+        self.imp().ligatures()
+    }
+
     pub fn pango_context(&self) -> Rc<pango::Context> {
         self.imp().pango_context()
     }
@@ -402,25 +589,55 @@ impl TextBuf {
         self.imp().cell(row, col)
     }
 
-    pub fn up(&self, rows: usize) {
-        self.imp().up(rows);
+    /// move cells of rows `[top, bottom)` and columns `[left, right)` up by `rows`.
+    pub fn up(&self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+        self.imp().up(top, bottom, left, right, rows);
+    }
+
+    /// move cells of rows `[top, bottom)` and columns `[left, right)` down by `rows`.
+    pub fn down(&self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+        self.imp().down(top, bottom, left, right, rows);
+    }
+
+    /// move cells of rows `[top, bottom)` and columns `[left, right)` left by `cols`.
+    pub fn left(&self, top: usize, bottom: usize, left: usize, right: usize, cols: usize) {
+        self.imp().left(top, bottom, left, right, cols);
+    }
+
+    /// move cells of rows `[top, bottom)` and columns `[left, right)` right by `cols`.
+    pub fn right(&self, top: usize, bottom: usize, left: usize, right: usize, cols: usize) {
+        self.imp().right(top, bottom, left, right, cols);
     }
 
-    pub fn down(&self, rows: usize) {
-        self.imp().down(rows);
+    pub fn clear_region(&self, top: usize, bottom: usize, left: usize, right: usize) {
+        self.imp().clear_region(top, bottom, left, right);
     }
 
     pub fn reset_cache(&self) {
         self.imp().reset_cache();
     }
+
+    /// Drop caches drawn by colors older than `colors` generation of highlights.
+    pub fn sync_colors(&self, colors: u64) {
+        self.imp().sync_colors(colors);
+    }
+}
+
+/// Attributes of cells by highlight and double width, see `TextCell::reset_attrs`.
+type AttrTemplates = rustc_hash::FxHashMap<(Option<u64>, bool), Rc<[pango::Attribute]>>;
+
+thread_local! {
+    static NO_ATTRS: Rc<[pango::Attribute]> = Rc::from(Vec::new());
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct TextCell {
-    pub text: String,
+    // blank cells and repeats of a cell share text, never allocate.
+    pub text: Rc<str>,
     pub hldef: Option<u64>,
     pub double_width: bool,
-    pub attrs: Vec<pango::Attribute>,
+    // attributes of its highlight shared by cells, not indexed to the cell.
+    pub attrs: Rc<[pango::Attribute]>,
     pub start_index: usize,
     pub end_index: usize,
 }
@@ -428,124 +645,258 @@ pub struct TextCell {
 impl Default for TextCell {
     fn default() -> TextCell {
         TextCell {
-            text: String::from(" "),
+            text: TextCell::intern(" "),
             hldef: None,
             double_width: false,
-            attrs: Vec::new(),
+            attrs: NO_ATTRS.with(Rc::clone),
             start_index: 0,
             end_index: 0,
         }
     }
 }
 
+impl TextCell {
+    /// Text of cell, blank and empty text are shared without allocation.
+    pub fn intern(text: &str) -> Rc<str> {
+        thread_local! {
+            static BLANK: Rc<str> = Rc::from(" ");
+            static EMPTY: Rc<str> = Rc::from("");
+        }
+        match text {
+            " " => BLANK.with(Rc::clone),
+            "" => EMPTY.with(Rc::clone),
+            text => Rc::from(text),
+        }
+    }
+}
+
+/// Cells with empty text are trailing half of the preceding double width glyph,
+/// keep them associated instead of blanking.
+fn join_double_width(cells: &mut [TextCell]) {
+    for col in 1..cells.len() {
+        if !cells[col].text.is_empty() {
+            continue;
+        }
+        let (leading, trailing) = cells.split_at_mut(col);
+        let glyph = &mut leading[col - 1];
+        if glyph.text.is_empty() {
+            continue;
+        }
+        glyph.double_width = true;
+        trailing[0].hldef = glyph.hldef;
+        trailing[0].double_width = false;
+    }
+}
+
+/// Drop cached layout of `line` and rebuild byte indexes of its cells.
+fn reindex(line: &mut TextLine) {
+    line.invalidate();
+    line.iter_mut().fold(0, |start_index, cell| {
+        cell.start_index = start_index;
+        cell.end_index = start_index + cell.text.len();
+        cell.end_index
+    });
+}
+
+/// Blank columns `[left, right)` of `lines`, byte indexes of each line are rebuilt.
+fn clear_region(lines: &mut [TextLine], left: usize, right: usize) {
+    for line in lines.iter_mut() {
+        let right = right.min(line.len());
+        if left >= right {
+            continue;
+        }
+        line[left..right].fill(TextCell::default());
+        reindex(line);
+    }
+}
+
+/// Move cells in columns `[left, right)` of `lines` up by `rows`, down if negative,
+/// vacated cells are blank, cells out of the columns are untouched.
+fn scroll_rows(lines: &mut [TextLine], left: usize, right: usize, rows: isize) {
+    let len = lines.len();
+    let n = rows.unsigned_abs().min(len);
+    if n == 0 {
+        return;
+    }
+    if left == 0 && lines.iter().all(|line| right >= line.len()) {
+        // whole lines, move them without touching cells.
+        if rows > 0 {
+            lines.rotate_left(n);
+            clear_region(&mut lines[len - n..], 0, usize::MAX);
+        } else {
+            lines.rotate_right(n);
+            clear_region(&mut lines[..n], 0, usize::MAX);
+        }
+        return;
+    }
+    // swap columns of each line with its source, sources end up vacated.
+    let mut swap = |to: usize, from: usize| {
+        let (to, from) = if to < from {
+            let (head, tail) = lines.split_at_mut(from);
+            (&mut head[to], &mut tail[0])
+        } else {
+            let (head, tail) = lines.split_at_mut(to);
+            (&mut tail[0], &mut head[from])
+        };
+        let right = right.min(to.len()).min(from.len());
+        if left < right {
+            to[left..right].swap_with_slice(&mut from[left..right]);
+            reindex(to);
+            reindex(from);
+        }
+    };
+    if rows > 0 {
+        (0..len - n).for_each(|row| swap(row, row + n));
+        clear_region(&mut lines[len - n..], left, right);
+    } else {
+        (n..len).rev().for_each(|row| swap(row, row - n));
+        clear_region(&mut lines[..n], left, right);
+    }
+}
+
+/// Move cells in columns `[left, right)` of each line left by `cols`, right if negative,
+/// vacated cells are blank, cells out of the columns are untouched.
+fn scroll_cols(lines: &mut [TextLine], left: usize, right: usize, cols: isize) {
+    for line in lines.iter_mut() {
+        let right = right.min(line.len());
+        if left >= right {
+            continue;
+        }
+        let n = cols.unsigned_abs().min(right - left);
+        if cols > 0 {
+            line[left..right].rotate_left(n);
+            clear_region(std::slice::from_mut(line), right - n, right);
+        } else if cols < 0 {
+            line[left..right].rotate_right(n);
+            clear_region(std::slice::from_mut(line), left, left + n);
+        }
+    }
+}
+
 impl TextCell {
     fn reset_attrs(
         &mut self,
-        _pctx: &pango::Context,
+        templates: &mut AttrTemplates,
+        pctx: &pango::Context,
         hldefs: &HighlightDefinitions,
         _metrics: &crate::metrics::Metrics,
+        wide_font: Option<&pango::FontDescription>,
     ) {
-        const U16MAX: f32 = u16::MAX as f32;
-
-        self.attrs.clear();
-        let attrs = pango::AttrList::new();
-
         if self.end_index == self.start_index {
+            self.attrs = NO_ATTRS.with(Rc::clone);
             return;
         }
 
-        let start_index = self.start_index as u32;
-        let end_index = self.end_index as u32;
-        let default_hldef = hldefs.get(HighlightDefinitions::DEFAULT).unwrap();
-        let default_colors = hldefs.defaults().unwrap();
-        let mut background = None;
-        let mut hldef = default_hldef;
-        if let Some(ref id) = self.hldef {
-            let style = hldefs.get(*id);
-            if let Some(style) = style {
-                background = style.background();
-                hldef = style;
-            }
+        let wide = self.double_width && wide_font.is_some();
+        let key = (self.hldef, wide);
+        if let Some(attrs) = templates.get(&key) {
+            self.attrs = attrs.clone();
+            return;
         }
-        if hldef.italic {
-            let mut attr = pango::AttrInt::new_style(pango::Style::Italic);
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        if hldef.bold {
-            let mut attr = pango::AttrInt::new_weight(pango::Weight::Semibold);
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        if hldef.strikethrough {
-            let mut attr = pango::AttrInt::new_strikethrough(true);
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        if hldef.underline {
-            let mut attr = pango::AttrInt::new_underline(pango::Underline::Single);
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        if hldef.undercurl {
-            let mut attr = pango::AttrInt::new_underline(pango::Underline::Error);
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        // alpha color
-        // blend is 0 - 100. Could be used by UIs to support
-        // blending floating windows to the background or to
-        // signal a transparent cursor.
-        // let blend = u16::MAX as u32 * hldef.blend as u32 / 100;
-        // let mut attr = pango::AttrInt::new_background_alpha(blend as u16);
-        // log::info!("blend {}", hldef.blend);
-        // attr.set_start_index(start_index as _);
-        // attr.set_end_index(end_index as _);
-        // attrs.insert(attr);
-        if let Some(fg) = hldef.colors.foreground.or(default_colors.foreground) {
-            let mut attr = pango::AttrColor::new_foreground(
-                (fg.red() * U16MAX).round() as u16,
-                (fg.green() * U16MAX).round() as u16,
-                (fg.blue() * U16MAX).round() as u16,
-            );
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        if let Some(bg) = background {
-            let mut attr = pango::AttrColor::new_background(
-                (bg.red() * U16MAX).round() as u16,
-                (bg.green() * U16MAX).round() as u16,
-                (bg.blue() * U16MAX).round() as u16,
-            );
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
-        if let Some(special) = hldef.colors.special.or(default_colors.special) {
-            let mut attr = pango::AttrColor::new_underline_color(
-                (special.red() * U16MAX).round() as u16,
-                (special.green() * U16MAX).round() as u16,
-                (special.blue() * U16MAX).round() as u16,
-            );
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
+        let attrs = highlight_attrs(pctx, hldefs, self.hldef, wide_font.filter(|_| wide));
+        // undefined highlight may be defined later, without a reset of cache.
+        if self.hldef.map_or(true, |id| hldefs.get(id).is_some()) {
+            templates.insert(key, attrs.clone());
         }
+        self.attrs = attrs;
+    }
+}
 
-        self.attrs = attrs.attributes();
+/// Attributes of the highlight `id`, indexes are left to the consumer.
+fn highlight_attrs(
+    pctx: &pango::Context,
+    hldefs: &HighlightDefinitions,
+    id: Option<u64>,
+    wide_font: Option<&pango::FontDescription>,
+) -> Rc<[pango::Attribute]> {
+    const U16MAX: f32 = u16::MAX as f32;
+
+    let attrs = pango::AttrList::new();
+    let default_hldef = hldefs.get(HighlightDefinitions::DEFAULT).unwrap();
+    let default_colors = hldefs.defaults().unwrap();
+    let mut background = None;
+    let mut hldef = default_hldef;
+    if let Some(id) = id {
+        let style = hldefs.get(id);
+        if let Some(style) = style {
+            background = style.resolved_background(default_colors);
+            hldef = style;
+        }
+    }
+    // before style and weight, they apply to the wide font too.
+    if let Some(desc) = wide_font {
+        let attr = pango::AttrFontDesc::new(desc);
+        attrs.insert(attr);
+    }
+    if hldef.italic {
+        let attr = pango::AttrInt::new_style(pango::Style::Italic);
+        attrs.insert(attr);
     }
+    if hldef.bold {
+        // on top of weight of `guifont`, never lighter than it.
+        let weight = pctx
+            .font_description()
+            .map_or(pango::Weight::Normal, |desc| desc.weight());
+        let attr = pango::AttrInt::new_weight(crate::guifont::bolder(weight));
+        attrs.insert(attr);
+    }
+    if hldef.strikethrough {
+        let attr = pango::AttrInt::new_strikethrough(true);
+        attrs.insert(attr);
+    }
+    if hldef.underline {
+        let attr = pango::AttrInt::new_underline(pango::Underline::Single);
+        attrs.insert(attr);
+    }
+    if hldef.undercurl {
+        let attr = pango::AttrInt::new_underline(pango::Underline::Error);
+        attrs.insert(attr);
+    }
+    if hldef.underdouble {
+        let attr = pango::AttrInt::new_underline(pango::Underline::Double);
+        attrs.insert(attr);
+    }
+    // blend is 0 - 100, used by `winblend` and `pumblend` to blend
+    // floating windows to the background.
+    if hldef.blend > 0 {
+        let alpha = (u16::MAX as f32 * hldef.alpha()).round() as u16;
+        let attr = pango::AttrInt::new_background_alpha(alpha);
+        attrs.insert(attr);
+    }
+    // reversed highlight swaps colors, falls back to defaults.
+    let fg = hldef.foreground(default_colors);
+    let attr = pango::AttrColor::new_foreground(
+        (fg.red() * U16MAX).round() as u16,
+        (fg.green() * U16MAX).round() as u16,
+        (fg.blue() * U16MAX).round() as u16,
+    );
+    attrs.insert(attr);
+    if let Some(bg) = background {
+        let attr = pango::AttrColor::new_background(
+            (bg.red() * U16MAX).round() as u16,
+            (bg.green() * U16MAX).round() as u16,
+            (bg.blue() * U16MAX).round() as u16,
+        );
+        attrs.insert(attr);
+    }
+    if let Some(special) = hldef.colors.special.or(default_colors.special) {
+        let attr = pango::AttrColor::new_underline_color(
+            (special.red() * U16MAX).round() as u16,
+            (special.green() * U16MAX).round() as u16,
+            (special.blue() * U16MAX).round() as u16,
+        );
+        attrs.insert(attr);
+    }
+
+    attrs.attributes().into()
 }
 
 #[derive(Default)]
 pub struct TextLine {
     boxed: Box<[TextCell]>,
     cache: Cell<Option<(pango::Layout, pango::LayoutLine)>>,
+    // text drawn for metrics, reused while the line is clean.
+    node: Cell<Option<(gtk::gsk::RenderNode, crate::metrics::Metrics)>>,
 }
 
 impl Clone for TextLine {
@@ -553,6 +904,7 @@ impl Clone for TextLine {
         TextLine {
             boxed: self.boxed.clone(),
             cache: Cell::new(unsafe { &*self.cache.as_ptr() }.clone()),
+            node: Cell::new(unsafe { &*self.node.as_ptr() }.clone()),
         }
     }
 }
@@ -568,6 +920,7 @@ impl TextLine {
         Self {
             boxed: line.into_boxed_slice(),
             cache: Cell::new(None),
+            node: Cell::new(None),
         }
     }
 
@@ -578,6 +931,24 @@ impl TextLine {
     pub fn set_cache(&self, layout: pango::Layout, line: pango::LayoutLine) {
         self.cache.set((layout, line).into());
     }
+
+    /// Text of line drawn before with `metrics`, `None` if line changed since.
+    pub fn node(&self, metrics: &crate::metrics::Metrics) -> Option<gtk::gsk::RenderNode> {
+        unsafe { &*self.node.as_ptr() }
+            .as_ref()
+            .filter(|(_, drawn)| drawn == metrics)
+            .map(|(node, _)| node.clone())
+    }
+
+    pub fn set_node(&self, node: gtk::gsk::RenderNode, metrics: crate::metrics::Metrics) {
+        self.node.set(Some((node, metrics)));
+    }
+
+    /// Line changed, layout and text drawn are dropped.
+    fn invalidate(&self) {
+        self.cache.set(None);
+        self.node.set(None);
+    }
 }
 
 impl Deref for TextLine {
@@ -626,3 +997,232 @@ impl TextLine {
         self.boxed
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing::{cell, grid_cell, line, textbuf};
+
+    #[test]
+    fn test_join_double_width() {
+        let mut cells = vec![
+            cell("a", Some(1)),
+            cell("中", Some(2)),
+            cell("", Some(1)),
+            cell("b", Some(1)),
+        ];
+        join_double_width(&mut cells);
+        assert!(!cells[0].double_width);
+        assert!(cells[1].double_width);
+        assert_eq!(&*cells[2].text, "");
+        assert_eq!(cells[2].hldef, Some(2));
+        assert!(!cells[3].double_width);
+    }
+
+    #[test]
+    fn test_join_double_width_leading_empty() {
+        let mut cells = vec![cell("", Some(1)), cell("a", Some(1))];
+        join_double_width(&mut cells);
+        assert_eq!(&*cells[0].text, "");
+        assert!(!cells[0].double_width);
+        assert!(!cells[1].double_width);
+    }
+
+    #[test]
+    fn test_clear_region() {
+        let text = |line: &TextLine| line.iter().map(|cell| &*cell.text).collect::<String>();
+        let mut lines: Vec<TextLine> = ["abcd", "e中\u{0}g", "hijk"]
+            .iter()
+            .map(|s| {
+                let cells: Vec<_> = s
+                    .chars()
+                    .map(|c| match c {
+                        '\u{0}' => cell("", Some(2)),
+                        c => cell(&c.to_string(), Some(1)),
+                    })
+                    .collect();
+                TextLine::from(cells.into_boxed_slice())
+            })
+            .collect();
+        clear_region(&mut lines[1..3], 1, 3);
+        assert_eq!(text(&lines[0]), "abcd");
+        assert_eq!(text(&lines[1]), "e  g");
+        assert_eq!(text(&lines[2]), "h  k");
+        assert_eq!(lines[1][0].hldef, Some(1));
+        assert_eq!(lines[1][1].hldef, None);
+        assert_eq!(lines[1][3].hldef, Some(1));
+        assert_eq!((lines[1][3].start_index, lines[1][3].end_index), (3, 4));
+        // out of range columns are ignored.
+        clear_region(&mut lines[..1], 3, 10);
+        assert_eq!(text(&lines[0]), "abc ");
+        clear_region(&mut lines[..1], 5, 10);
+        assert_eq!(text(&lines[0]), "abc ");
+    }
+
+    #[test]
+    fn test_scroll_rows_and_cols() {
+        let text = |lines: &[TextLine]| {
+            lines
+                .iter()
+                .map(|line| line.iter().map(|cell| &*cell.text).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        let mut lines: Vec<TextLine> = ["abcd", "efgh", "ijkl"]
+            .iter()
+            .map(|s| TextLine::from(line(s, |_| Some(1)).into_boxed_slice()))
+            .collect();
+        // vertical first, then horizontal.
+        scroll_rows(&mut lines, 0, usize::MAX, 1);
+        scroll_cols(&mut lines, 0, usize::MAX, -1);
+        assert_eq!(text(&lines), vec![" efg", " ijk", "    "]);
+        assert_eq!((lines[0][3].start_index, lines[0][3].end_index), (3, 4));
+        scroll_rows(&mut lines, 0, usize::MAX, -2);
+        scroll_cols(&mut lines, 0, usize::MAX, 2);
+        assert_eq!(text(&lines), vec!["    ", "    ", "fg  "]);
+        // over scrolled, all blank.
+        scroll_cols(&mut lines, 0, usize::MAX, 10);
+        assert_eq!(text(&lines), vec!["    ", "    ", "    "]);
+    }
+
+    #[test]
+    fn test_scroll_cols_region() {
+        let text = |lines: &[TextLine]| {
+            lines
+                .iter()
+                .map(|line| line.iter().map(|cell| &*cell.text).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        let mut lines: Vec<TextLine> = ["|abcdef|", "|ghijkl|"]
+            .iter()
+            .map(|s| TextLine::from(line(s, |_| Some(1)).into_boxed_slice()))
+            .collect();
+        // window between separators, only columns 1..7 move.
+        scroll_cols(&mut lines[..1], 1, 7, 2);
+        assert_eq!(text(&lines), vec!["|cdef  |", "|ghijkl|"]);
+        scroll_cols(&mut lines[1..], 1, 7, -3);
+        assert_eq!(text(&lines), vec!["|cdef  |", "|   ghi|"]);
+        assert_eq!((lines[1][7].start_index, lines[1][7].end_index), (7, 8));
+        // region out of line is ignored.
+        scroll_cols(&mut lines, 8, 10, 1);
+        assert_eq!(text(&lines), vec!["|cdef  |", "|   ghi|"]);
+    }
+
+    #[test]
+    fn test_scroll_rows_region() {
+        let text = |lines: &[TextLine]| {
+            lines
+                .iter()
+                .map(|line| line.iter().map(|cell| &*cell.text).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        // two windows side by side with a separator, statuslines at bottom.
+        let mut lines: Vec<TextLine> = ["a1a|b1b", "a2a|b2b", "a3a|b3b", "a4a|b4b", "sta|stb"]
+            .iter()
+            .map(|s| TextLine::from(line(s, |_| Some(1)).into_boxed_slice()))
+            .collect();
+        // left window scrolled up by one, rows 0..4 and columns 0..3.
+        scroll_rows(&mut lines[0..4], 0, 3, 1);
+        assert_eq!(
+            text(&lines),
+            vec!["a2a|b1b", "a3a|b2b", "a4a|b3b", "   |b4b", "sta|stb"]
+        );
+        // right window scrolled down by two, rows 0..4 and columns 4..7.
+        scroll_rows(&mut lines[0..4], 4, 7, -2);
+        assert_eq!(
+            text(&lines),
+            vec!["a2a|   ", "a3a|   ", "a4a|b1b", "   |b2b", "sta|stb"]
+        );
+        assert_eq!((lines[2][6].start_index, lines[2][6].end_index), (6, 7));
+        // over scrolled region is blank, cells out of it are untouched.
+        scroll_rows(&mut lines[1..3], 0, 3, 5);
+        assert_eq!(
+            text(&lines),
+            vec!["a2a|   ", "   |   ", "   |b1b", "   |b2b", "sta|stb"]
+        );
+    }
+
+    #[test]
+    fn test_partial_update_reshapes_ligature() {
+        let grid_cells = |text: &str| -> Vec<_> {
+            text.chars()
+                .map(|c| grid_cell(&c.to_string(), HighlightDefinitions::DEFAULT, None))
+                .collect()
+        };
+        let textbuf = textbuf(1, 6);
+        let pctx = textbuf.pango_context();
+        textbuf.set_cells(0, 0, &grid_cells("a == b"));
+        {
+            let lines = textbuf.lines();
+            let line = lines.get(0).unwrap();
+            let layout = pango::Layout::new(&pctx);
+            layout.set_text(&line.iter().map(|cell| &*cell.text).collect::<String>());
+            line.set_cache(layout.clone(), layout.line(0).unwrap());
+        }
+
+        // `!` lands in the middle of `==`.
+        textbuf.set_cells(0, 2, &grid_cells("!"));
+        let lines = textbuf.lines();
+        let line = lines.get(0).unwrap();
+        assert!(line.cache().is_none());
+        let text: String = line.iter().map(|cell| &*cell.text).collect();
+        assert_eq!(text, "a != b");
+        let indexes: Vec<_> = line.iter().map(|cell| cell.start_index).collect();
+        assert_eq!(indexes, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_highlighted_blanks_kept() {
+        const COLORCOLUMN: u64 = 5;
+        const SIGNCOLUMN: u64 = 6;
+        let textbuf = textbuf(1, 8);
+        // empty sign column, `x`, then `colorcolumn` past end of line.
+        let default = HighlightDefinitions::DEFAULT;
+        textbuf.set_cells(
+            0,
+            0,
+            &[
+                grid_cell(" ", SIGNCOLUMN, Some(2)),
+                grid_cell("x", default, None),
+                grid_cell(" ", default, Some(2)),
+                grid_cell(" ", COLORCOLUMN, None),
+                grid_cell(" ", default, Some(2)),
+            ],
+        );
+        textbuf.clear_region(0, 1, 6, 8);
+        let lines = textbuf.lines();
+        let line = lines.get(0).unwrap();
+        let hldefs: Vec<_> = line.iter().map(|cell| cell.hldef).collect();
+        assert_eq!(
+            hldefs,
+            vec![
+                Some(SIGNCOLUMN),
+                Some(SIGNCOLUMN),
+                Some(default),
+                Some(default),
+                Some(default),
+                Some(COLORCOLUMN),
+                // true blanks, cleared by gui.
+                None,
+                None,
+            ]
+        );
+        let text: String = line.iter().map(|cell| &*cell.text).collect();
+        assert_eq!(text, "  x     ");
+        // cells of a highlight share its attributes.
+        assert!(Rc::ptr_eq(&line[2].attrs, &line[4].attrs));
+    }
+
+    #[test]
+    fn test_long_line_without_allocation() {
+        let line = TextLine::new(10_000);
+        assert_eq!(line.len(), 10_000);
+        let blank = TextCell::intern(" ");
+        assert!(line.iter().all(|cell| Rc::ptr_eq(&cell.text, &blank)));
+        let empty = TextCell::default().attrs;
+        assert!(line.iter().all(|cell| Rc::ptr_eq(&cell.attrs, &empty)));
+        let line = line.clone();
+        assert!(line.iter().all(|cell| Rc::ptr_eq(&cell.text, &blank)));
+        assert!(Rc::ptr_eq(&TextCell::intern(""), &TextCell::intern("")));
+        assert!(!Rc::ptr_eq(&TextCell::intern("a"), &TextCell::intern("a")));
+    }
+}
@@ -0,0 +1,148 @@
+use super::TextCell;
+
+/// Underlines pango has no style for, drawn by gui instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dash {
+    Dotted,
+    Dashed,
+}
+
+/// Underline of `len` cells from `col` highlighted by `hldef`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Underline {
+    pub col: usize,
+    pub len: usize,
+    pub hldef: u64,
+    pub dash: Dash,
+}
+
+/// Runs of cells sharing the same highlight underlined by `dash` of it.
+pub fn detect(cells: &[TextCell], dash: impl Fn(u64) -> Option<Dash>) -> Vec<Underline> {
+    let mut underlines: Vec<Underline> = Vec::new();
+    for (col, cell) in cells.iter().enumerate() {
+        let (hldef, dash) = match cell.hldef.and_then(|hldef| Some((hldef, dash(hldef)?))) {
+            Some(underlined) => underlined,
+            None => continue,
+        };
+        match underlines.last_mut() {
+            Some(underline) if underline.hldef == hldef && underline.col + underline.len == col => {
+                underline.len += 1
+            }
+            _ => underlines.push(Underline {
+                col,
+                len: 1,
+                hldef,
+                dash,
+            }),
+        }
+    }
+    underlines
+}
+
+/// Dashes of `underline` `(x, width)` in pixel, `thickness` sized dots or half cell wide dashes.
+pub fn segments(underline: &Underline, cell_width: f64, thickness: f64) -> Vec<(f64, f64)> {
+    let (on, off) = match underline.dash {
+        Dash::Dotted => (thickness, thickness),
+        Dash::Dashed => (cell_width / 2., cell_width / 4.),
+    };
+    let start = underline.col as f64 * cell_width;
+    let end = start + underline.len as f64 * cell_width;
+    let mut segments = Vec::new();
+    let mut x = start;
+    while x < end {
+        segments.push((x, on.min(end - x)));
+        x += on + off;
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOTTED: u64 = 3;
+    const DASHED: u64 = 4;
+
+    fn dash(hldef: u64) -> Option<Dash> {
+        match hldef {
+            DOTTED => Some(Dash::Dotted),
+            DASHED => Some(Dash::Dashed),
+            _ => None,
+        }
+    }
+
+    fn cells(hldefs: &[Option<u64>]) -> Vec<TextCell> {
+        hldefs
+            .iter()
+            .map(|hldef| TextCell {
+                hldef: *hldef,
+                ..TextCell::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let cells = cells(&[
+            Some(1),
+            Some(DOTTED),
+            Some(DOTTED),
+            Some(DASHED),
+            None,
+            Some(DASHED),
+            Some(DASHED),
+        ]);
+        let underlines = detect(&cells, dash);
+        assert_eq!(
+            underlines,
+            vec![
+                Underline {
+                    col: 1,
+                    len: 2,
+                    hldef: DOTTED,
+                    dash: Dash::Dotted
+                },
+                Underline {
+                    col: 3,
+                    len: 1,
+                    hldef: DASHED,
+                    dash: Dash::Dashed
+                },
+                Underline {
+                    col: 5,
+                    len: 2,
+                    hldef: DASHED,
+                    dash: Dash::Dashed
+                },
+            ]
+        );
+        assert!(detect(&[], dash).is_empty());
+    }
+
+    #[test]
+    fn test_segments() {
+        let dotted = Underline {
+            col: 1,
+            len: 1,
+            hldef: DOTTED,
+            dash: Dash::Dotted,
+        };
+        assert_eq!(
+            segments(&dotted, 8., 1.),
+            vec![(8., 1.), (10., 1.), (12., 1.), (14., 1.)]
+        );
+        let dashed = Underline {
+            col: 0,
+            len: 2,
+            hldef: DASHED,
+            dash: Dash::Dashed,
+        };
+        // the last dash is cut at the end of cells.
+        assert_eq!(
+            segments(&dashed, 8., 1.),
+            vec![(0., 4.), (6., 4.), (12., 4.)]
+        );
+        let dashed = Underline { len: 1, ..dashed };
+        assert_eq!(segments(&dashed, 10., 1.), vec![(0., 5.), (7.5, 2.5)]);
+    }
+}
@@ -0,0 +1,92 @@
+use rustc_hash::FxHashSet;
+
+use super::{Match, TextCell};
+
+/// highlight group matched to trailing whitespace by `matchadd()` in neovim.
+pub const WHITESPACE_HLGROUP: &str = "ReovimTrailingWhitespace";
+
+/// Trailing whitespace of visible lines, runs of cells highlighted by any of `hlids`.
+///
+/// The match group is combined with highlights below it, e.g. `CursorLine`,
+/// so every highlight id defined with it is given.
+pub fn trailing(rows: &[&[TextCell]], hlids: &FxHashSet<u64>) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for (row, cells) in rows.iter().enumerate() {
+        let matched = |col: usize| {
+            cells[col]
+                .hldef
+                .map_or(false, |hldef| hlids.contains(&hldef))
+        };
+        let mut col = 0;
+        while col < cells.len() {
+            if !matched(col) {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < cells.len() && matched(col) {
+                col += 1;
+            }
+            matches.push(Match {
+                row,
+                col: start,
+                len: col - start,
+            });
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vimview::testing::{grid_cell, textbuf};
+
+    const WHITESPACE: u64 = 7;
+    // `WHITESPACE` combined with `CursorLine`.
+    const CURSORLINE_WHITESPACE: u64 = 8;
+
+    #[test]
+    fn test_trailing_whitespace() {
+        let textbuf = textbuf(3, 10);
+        // `ab  ` then cleared end of line.
+        textbuf.set_cells(
+            0,
+            0,
+            &[
+                grid_cell("a", 0, None),
+                grid_cell("b", 0, None),
+                grid_cell(" ", WHITESPACE, Some(2)),
+                grid_cell(" ", 0, Some(6)),
+            ],
+        );
+        // `x\t` on cursor line, tab expanded to spaces by neovim.
+        textbuf.set_cells(
+            1,
+            0,
+            &[
+                grid_cell("x", 1, None),
+                grid_cell(" ", CURSORLINE_WHITESPACE, Some(7)),
+                grid_cell(" ", 1, Some(2)),
+            ],
+        );
+        // spaces inside line are not matched.
+        textbuf.set_cells(
+            2,
+            0,
+            &[
+                grid_cell("a", 0, None),
+                grid_cell(" ", 0, Some(2)),
+                grid_cell("b", 0, None),
+                grid_cell(" ", 0, Some(6)),
+            ],
+        );
+        let lines = textbuf.lines();
+        let rows: Vec<&[TextCell]> = (0..3).map(|row| lines.get(row).unwrap().as_ref()).collect();
+        let hlids: FxHashSet<u64> = [WHITESPACE, CURSORLINE_WHITESPACE].into_iter().collect();
+        let matches = trailing(&rows, &hlids);
+        let at = |row, col, len| Match { row, col, len };
+        assert_eq!(matches, vec![at(0, 2, 2), at(1, 1, 7)]);
+        assert!(trailing(&rows, &FxHashSet::default()).is_empty());
+    }
+}
@@ -2,17 +2,22 @@ use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::sync::atomic;
 
-use gtk::prelude::*;
+use gtk::{gdk, prelude::*};
 use parking_lot::RwLock;
 use relm4::factory::positions::FixedPosition;
 use relm4::*;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::app::{self, Dragging};
-use crate::bridge::{MouseAction, MouseButton, SerialCommand, UiCommand};
+use crate::bridge::{MouseAction, MouseButton, Register, SerialCommand, UiCommand};
 use crate::event_aggregator::EVENT_AGGREGATOR;
 use crate::grapheme::{Coord, Pos, Rectangle};
 
+use super::find::Match;
+use super::floats::stack_position;
 use super::gridview::VimGridView;
+use super::links::{self, Link};
+use super::scrollbar::Viewport;
 use super::TextBuf;
 
 type HighlightDefinitions = Rc<RwLock<crate::vimview::HighlightDefinitions>>;
@@ -26,10 +31,37 @@ pub struct VimGrid {
     width: usize,
     height: usize,
     is_float: bool,
+    sort_order: u64,
+    is_terminal: bool,
     focusable: bool,
+    // floats are moved into it, above grids of all windows.
+    float_container: Option<gtk::Fixed>,
     metrics: Rc<Cell<crate::metrics::Metrics>>,
     font_description: Rc<RefCell<pango::FontDescription>>,
     dragging: Rc<Cell<Option<Dragging>>>,
+    hlgroups: Option<Rc<RwLock<FxHashMap<String, u64>>>>,
+    separators: bool,
+    gutter: bool,
+    seamless: bool,
+    background_alpha: f64,
+    float_border: bool,
+    placeholders: bool,
+    colorcolumns: Vec<usize>,
+    colorcolumn_color: Option<gdk::RGBA>,
+    smooth_scroll: bool,
+    // middle click pastes primary selection by gui.
+    middle_paste: bool,
+    dim: f64,
+    matches: Vec<Match>,
+    match_color: Option<gdk::RGBA>,
+    whitespace: Option<(gdk::RGBA, Rc<RwLock<FxHashSet<u64>>>)>,
+    // rows scrolled since last view update.
+    scrolled: Cell<f64>,
+    // viewport of window, shown as scrollbar once changed.
+    viewport: Option<Viewport>,
+    viewport_changed: Cell<bool>,
+    // scrollbars kept at left and right edges by `guioptions`.
+    scrollbars: (bool, bool),
 
     textbuf: TextBuf,
 
@@ -61,8 +93,30 @@ impl VimGrid {
             height: rect.height as _,
             move_to: None.into(),
             dragging,
+            hlgroups: None,
+            separators: false,
+            gutter: false,
+            seamless: false,
+            background_alpha: 1.,
+            float_border: false,
+            placeholders: false,
+            colorcolumns: Vec::new(),
+            colorcolumn_color: None,
+            smooth_scroll: false,
+            middle_paste: false,
+            dim: 0.,
+            matches: Vec::new(),
+            match_color: None,
+            whitespace: None,
+            scrolled: Cell::new(0.),
+            viewport: None,
+            viewport_changed: Cell::new(false),
+            scrollbars: (false, false),
             is_float: false,
+            sort_order: 0,
+            is_terminal: false,
             focusable: true,
+            float_container: None,
             metrics,
             textbuf,
             visible: true,
@@ -87,6 +141,16 @@ impl VimGrid {
         &self.coord
     }
 
+    /// Handle of window shown by this grid, 0 if not known yet.
+    pub fn win(&self) -> u64 {
+        self.win
+    }
+
+    /// Window of grid, known from `win_pos` or `win_viewport`.
+    pub fn set_win(&mut self, win: u64) {
+        self.win = win;
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
     }
@@ -103,36 +167,88 @@ impl VimGrid {
         self.textbuf().borrow().reset_cache();
     }
 
+    /// Region covers the whole grid, only it is animated by smooth scrolling,
+    /// e.g. `winbar` or a split scroll region stays in place.
+    fn whole_region(&self, top: usize, bottom: usize, left: usize, right: usize) -> bool {
+        top == 0 && bottom >= self.height && left == 0 && right >= self.width
+    }
+
     // content go up, view go down, eat head of rows.
-    pub fn up(
-        &mut self,
-        // top: usize,
-        // bottom: usize,
-        // left: usize,
-        // right: usize,
-        rows: usize,
-        // cols: usize,
-    ) {
-        log::debug!("scroll-region {} rows moved up.", rows);
+    pub fn up(&mut self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+        log::debug!(
+            "scroll-region {}..{}x{}..{} {} rows moved up.",
+            top,
+            bottom,
+            left,
+            right,
+            rows
+        );
         log::debug!(
             "Origin Region {:?} {}x{}",
             self.coord,
             self.width,
             self.height
         );
-        self.textbuf().borrow_mut().up(rows);
+        if self.smooth_scroll && self.whole_region(top, bottom, left, right) {
+            self.scrolled.set(self.scrolled.get() + rows as f64);
+        }
+        self.textbuf()
+            .borrow_mut()
+            .up(top, bottom, left, right, rows);
     }
 
     // content go down, view go up, eat tail of rows.
-    pub fn down(&mut self, rows: usize) {
-        log::debug!("scroll-region {} rows moved down.", rows);
+    pub fn down(&mut self, top: usize, bottom: usize, left: usize, right: usize, rows: usize) {
+        log::debug!(
+            "scroll-region {}..{}x{}..{} {} rows moved down.",
+            top,
+            bottom,
+            left,
+            right,
+            rows
+        );
         log::debug!(
             "Origin Region {:?} {}x{}",
             self.coord,
             self.width,
             self.height
         );
-        self.textbuf().borrow_mut().down(rows);
+        if self.smooth_scroll && self.whole_region(top, bottom, left, right) {
+            self.scrolled.set(self.scrolled.get() - rows as f64);
+        }
+        self.textbuf()
+            .borrow_mut()
+            .down(top, bottom, left, right, rows);
+    }
+
+    // content go left, view go right, eat head of columns.
+    pub fn left(&mut self, top: usize, bottom: usize, left: usize, right: usize, cols: usize) {
+        log::debug!(
+            "scroll-region {}..{}x{}..{} {} columns moved left.",
+            top,
+            bottom,
+            left,
+            right,
+            cols
+        );
+        self.textbuf()
+            .borrow_mut()
+            .left(top, bottom, left, right, cols);
+    }
+
+    // content go right, view go left, eat tail of columns.
+    pub fn right(&mut self, top: usize, bottom: usize, left: usize, right: usize, cols: usize) {
+        log::debug!(
+            "scroll-region {}..{}x{}..{} {} columns moved right.",
+            top,
+            bottom,
+            left,
+            right,
+            cols
+        );
+        self.textbuf()
+            .borrow_mut()
+            .right(top, bottom, left, right, cols);
     }
 
     pub fn resize(&mut self, width: usize, height: usize) {
@@ -154,18 +270,137 @@ impl VimGrid {
         self.is_float = is_float;
     }
 
+    pub fn is_float(&self) -> bool {
+        self.is_float
+    }
+
+    pub fn set_sort_order(&mut self, sort_order: u64) {
+        self.sort_order = sort_order;
+    }
+
+    /// Container of float windows, floats stay with other grids if `None`.
+    pub fn set_float_container(&mut self, container: Option<gtk::Fixed>) {
+        self.float_container = container;
+    }
+
+    /// darken inactive window by `dim` alpha, 0 for none.
+    pub fn set_dim(&mut self, dim: f64) {
+        self.dim = dim;
+    }
+
+    pub fn dim(&self) -> f64 {
+        self.dim
+    }
+
+    pub fn set_is_terminal(&mut self, is_terminal: bool) {
+        self.is_terminal = is_terminal;
+    }
+
     pub fn set_focusable(&mut self, focusable: bool) {
         self.focusable = focusable;
     }
 
+    pub fn set_hlgroups(&mut self, hlgroups: Rc<RwLock<FxHashMap<String, u64>>>) {
+        self.hlgroups.replace(hlgroups);
+    }
+
+    /// draw split separators by gui, for the single grid without multigrid.
+    pub fn set_draw_separators(&mut self, separators: bool) {
+        self.separators = separators;
+    }
+
+    /// fill sign/number column full height with its background.
+    pub fn set_fill_gutter(&mut self, gutter: bool) {
+        self.gutter = gutter;
+    }
+
+    /// overlap cell backgrounds by a sub-pixel, hides seams of powerline glyphs.
+    pub fn set_seamless_background(&mut self, seamless: bool) {
+        self.seamless = seamless;
+    }
+
+    /// alpha of default background, below 1 shows window behind.
+    pub fn set_background_alpha(&mut self, alpha: f64) {
+        self.background_alpha = alpha;
+    }
+
+    /// outline float windows without a border of their own.
+    pub fn set_float_border(&mut self, float_border: bool) {
+        self.float_border = float_border;
+    }
+
+    /// draw image placeholder cells as solid rectangles.
+    pub fn set_image_placeholders(&mut self, placeholders: bool) {
+        self.placeholders = placeholders;
+    }
+
+    /// matches of find on screen, highlighted with `color`.
+    pub fn set_matches(&mut self, matches: Vec<Match>, color: Option<gdk::RGBA>) {
+        self.matches = matches;
+        self.match_color = color;
+    }
+
+    /// trailing whitespace on screen highlighted with `color`, cells highlighted by `hlids`
+    /// as matched by neovim, none for off.
+    pub fn set_trailing_whitespace(
+        &mut self,
+        color: Option<gdk::RGBA>,
+        hlids: Rc<RwLock<FxHashSet<u64>>>,
+    ) {
+        self.whitespace = color.map(|color| (color, hlids));
+    }
+
+    /// animate scrolling of this grid.
+    pub fn set_smooth_scroll(&mut self, smooth_scroll: bool) {
+        self.smooth_scroll = smooth_scroll;
+    }
+
+    /// middle click pastes primary selection at cursor, not sent to neovim as a mouse button.
+    pub fn set_middle_paste(&mut self, middle_paste: bool) {
+        self.middle_paste = middle_paste;
+    }
+
+    /// grid columns of `colorcolumn`, drawn as thin rules of `color` by gui.
+    pub fn set_colorcolumns(&mut self, colorcolumns: Vec<usize>, color: Option<gdk::RGBA>) {
+        self.colorcolumns = colorcolumns;
+        self.colorcolumn_color = color;
+    }
+
+    /// lines of buffer shown in window, scrollbar shows up if it scrolled.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        if self.viewport != Some(viewport) {
+            self.viewport.replace(viewport);
+            self.viewport_changed.set(true);
+        }
+    }
+
+    /// scrollbars at `left` and `right` edges, kept shown instead of fading out.
+    pub fn set_scrollbars(&mut self, left: bool, right: bool) {
+        self.scrollbars = (left, right);
+    }
+
     pub fn set_pango_context(&self, pctx: Rc<pango::Context>) {
         self.textbuf().borrow().set_pango_context(pctx);
     }
+
+    /// Font of double width cells by `guifontwide`.
+    pub fn set_wide_font(&mut self, wide_font: Option<pango::FontDescription>) {
+        self.textbuf().borrow().set_wide_font(wide_font);
+        self.reset_cache();
+    }
+
+    /// Shape ligatures of font, off draws their characters as separate glyphs.
+    pub fn set_ligatures(&mut self, ligatures: bool) {
+        self.textbuf().borrow().set_ligatures(ligatures);
+        self.reset_cache();
+    }
 }
 
 #[derive(Debug)]
 pub struct VimGridWidgets {
     view: VimGridView,
+    // container of grids of windows, the grid returns to it from floating.
+    home: RefCell<Option<gtk::Fixed>>,
 }
 
 impl factory::FactoryPrototype for VimGrid {
@@ -190,114 +425,30 @@ impl factory::FactoryPrototype for VimGrid {
                 set_overflow: gtk::Overflow::Hidden,
 
                 set_font_description: &self.font_description.borrow(),
+                set_hlgroups: self.hlgroups.clone(),
+                set_draw_separators: self.separators,
+                set_fill_gutter: self.gutter,
+                set_seamless_background: self.seamless,
+                set_background_alpha: self.background_alpha,
+                set_float_border: self.float_border,
+                set_image_placeholders: self.placeholders,
 
                 set_css_classes: &["vim-view-grid", &format!("vim-view-grid-{}", self.grid)],
             }
         }
 
-        let click_listener = gtk::GestureClick::builder()
-            .button(0)
-            .exclusive(false)
-            .touch_only(false)
-            .n_points(1)
-            .name("click-listener")
-            .build();
-        click_listener.connect_pressed(
-            glib::clone!(@strong sender, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, n_press, x, y| {
-                sender.send(app::AppMessage::ShowPointer).unwrap();
-                let metrics = metrics.get();
-                let width = metrics.width();
-                let height = metrics.height();
-                let cols = x as f64 / width;
-                let rows = y as f64 / height;
-                log::trace!("grid {} mouse pressed {} times at {}x{} -> {}x{}", grid, n_press, x, y, cols, rows);
-                let position = (cols.floor() as u32, rows.floor() as u32);
-                let modifier = c.current_event_state().to_string();
-                let btn = match c.current_button() {
-                    1 => MouseButton::Left,
-                    2 => MouseButton::Middle,
-                    3 => MouseButton::Right,
-                    _ => { return; }
-                };
-                dragging.set(Dragging{ btn, pos: position}.into());
-                EVENT_AGGREGATOR.send(
-                    UiCommand::Serial(SerialCommand::MouseButton {
-                        action: MouseAction::Press,
-                        button: btn,
-                        modifier: c.current_event_state(),
-                        grid_id: grid,
-                        position
-                    })
-                );
-                log::trace!("grid {} release button {} current_button {} modifier {}", grid, c.button(), c.current_button(), modifier);
-            }),
+        connect_input(
+            &view,
+            sender,
+            &self.dragging,
+            &self.metrics,
+            self.middle_paste,
         );
-        click_listener.connect_released(
-            glib::clone!(@strong sender, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, n_press, x, y| {
-                sender.send(app::AppMessage::ShowPointer).unwrap();
-                let metrics = metrics.get();
-                let width = metrics.width();
-                let height = metrics.height();
-                let cols = x as f64 / width;
-                let rows = y as f64 / height;
-                log::trace!("grid {} mouse released {} times at {}x{} -> {}x{}", grid, n_press, x, y, cols, rows);
-                let modifier = c.current_event_state().to_string();
-                dragging.set(None);
-                let btn = match c.current_button() {
-                    1 => MouseButton::Left,
-                    2 => MouseButton::Middle,
-                    3 => MouseButton::Right,
-                    _ => { return; }
-                };
-                EVENT_AGGREGATOR.send(
-                    UiCommand::Serial(SerialCommand::MouseButton {
-                        action: MouseAction::Release,
-                        button: btn,
-                        modifier: c.current_event_state(),
-                        grid_id: grid,
-                        position: (cols.floor() as u32, rows.floor() as u32)
-                    })
-                );
-                log::trace!("grid {} release button {} current_button {} modifier {}", grid, c.button(), c.current_button(), modifier);
-            }),
-        );
-        view.add_controller(&click_listener);
-
-        let motion_listener = gtk::EventControllerMotion::new();
-        let grid_id = grid;
-        motion_listener.connect_enter(move |_, _, _| {
-            app::GridActived.store(grid_id, atomic::Ordering::Relaxed);
-        });
-        motion_listener.connect_motion(glib::clone!(@strong sender, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, x, y| {
-            sender.send(app::AppMessage::ShowPointer).unwrap();
-            log::trace!("cursor motion {} {}", x, y);
-            if let Some(Dragging { btn, pos }) = dragging.get() {
-                let metrics = metrics.get();
-                let width = metrics.width();
-                let height = metrics.height();
-                let cols = x as f64 / width;
-                let rows = y as f64 / height;
-                let position = (cols.floor() as u32, rows.floor() as u32);
-                log::trace!("Dragging {} from {:?} to {:?}", btn, pos, position);
-                if pos != position {
-                    EVENT_AGGREGATOR.send(
-                        UiCommand::Serial(SerialCommand::Drag {
-                            button: btn,
-                            modifier: c.current_event_state(),
-                            grid_id: grid,
-                            position,
-                        })
-                    );
-                    dragging.set(Dragging { btn, pos: position }.into());
-                }
-            }
-            // for mouse auto hide
-            // if motion show one second.
 
-        }));
-        view.add_controller(&motion_listener);
-
-        VimGridWidgets { view }
+        VimGridWidgets {
+            view,
+            home: RefCell::new(None),
+        }
     }
 
     fn position(&self, _: &u64) -> FixedPosition {
@@ -325,7 +476,51 @@ impl factory::FactoryPrototype for VimGrid {
         }
 
         view.set_focusable(self.focusable);
+        // clicks on floats without focus, e.g. completion menus, go to windows below.
+        view.set_can_target(self.focusable);
         view.set_is_float(self.is_float);
+        view.set_is_terminal(self.is_terminal);
+        view.set_colorcolumns(self.colorcolumns.clone(), self.colorcolumn_color);
+        view.set_matches(self.matches.clone(), self.match_color);
+        view.set_trailing_whitespace(self.whitespace.clone());
+        view.set_dim(self.dim);
+        let scrolled = self.scrolled.replace(0.);
+        if scrolled != 0. {
+            view.animate_scroll(scrolled * self.metrics.get().height());
+        }
+        view.set_scrollbars(self.scrollbars.0, self.scrollbars.1);
+        if let (true, Some(viewport)) = (self.viewport_changed.replace(false), self.viewport) {
+            view.show_scrollbar(viewport);
+        }
+
+        let parent = view
+            .parent()
+            .and_then(|parent| parent.downcast::<gtk::Fixed>().ok());
+        if let (Some(floats), Some(parent)) = (self.float_container.as_ref(), parent) {
+            if &parent != floats {
+                widgets.home.replace(Some(parent.clone()));
+            }
+            let container = if self.is_float {
+                Some(floats.clone())
+            } else {
+                widgets.home.borrow().clone()
+            };
+            if let Some(container) = container.filter(|container| container != &parent) {
+                log::debug!(
+                    "grid {} moved to container {}",
+                    index,
+                    container.widget_name()
+                );
+                let pos: FixedPosition = self.pos.into();
+                parent.remove(view);
+                container.put(view, pos.x, pos.y);
+                self.move_to.take();
+            }
+        }
+        if let (true, Some(floats)) = (self.is_float, self.float_container.as_ref()) {
+            view.set_sort_order(self.sort_order);
+            restack(floats, view);
+        }
 
         if let Some(pos) = self.move_to.take() {
             gtk::prelude::FixedExt::move_(
@@ -338,9 +533,188 @@ impl factory::FactoryPrototype for VimGrid {
 
         view.queue_allocate();
         view.queue_resize();
+        view.queue_draw();
     }
 
     fn root_widget(widgets: &VimGridWidgets) -> &VimGridView {
         &widgets.view
     }
 }
+
+/// Link of text in cell at `position` of `view`.
+fn link_at(view: &VimGridView, (col, row): (u32, u32)) -> Option<Link> {
+    let textbuf = view.textbuf();
+    let lines = textbuf.lines();
+    let line = lines.get(row as usize)?;
+    links::link_at(line.as_ref(), col as usize)
+}
+
+/// Move float `view` among other floats of `container` by their sort order.
+fn restack(container: &gtk::Fixed, view: &VimGridView) {
+    if view.parent().as_ref() != Some(container.upcast_ref::<gtk::Widget>()) {
+        return;
+    }
+    let mut siblings = Vec::new();
+    let mut child = container.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        if &widget != view.upcast_ref::<gtk::Widget>() {
+            if let Ok(sibling) = widget.downcast::<VimGridView>() {
+                siblings.push(sibling);
+            }
+        }
+    }
+    let next = stack_position(
+        siblings.iter().map(|sibling| sibling.sort_order()),
+        view.sort_order(),
+    )
+    .map(|index| siblings[index].clone().upcast::<gtk::Widget>());
+    if view.next_sibling() != next {
+        view.insert_before(container, next.as_ref());
+    }
+}
+
+/// Forward clicks, drags and link hovering over `view` to neovim,
+/// as the grid `view` currently draws.
+pub fn connect_input(
+    view: &VimGridView,
+    sender: Sender<app::AppMessage>,
+    dragging: &Rc<Cell<Option<Dragging>>>,
+    metrics: &Rc<Cell<crate::metrics::Metrics>>,
+    middle_paste: bool,
+) {
+    let click_listener = gtk::GestureClick::builder()
+        .button(0)
+        .exclusive(false)
+        .touch_only(false)
+        .n_points(1)
+        .name("click-listener")
+        .build();
+    // press of Ctrl+click opening a link, its release not sent too.
+    let link_opened = Rc::new(Cell::new(false));
+    click_listener.connect_pressed(
+        glib::clone!(@strong sender, @strong link_opened, @weak view, @weak dragging, @weak metrics => move |c, n_press, x, y| {
+            let grid = view.id();
+            sender.send(app::AppMessage::ShowPointer).unwrap();
+            let metrics = metrics.get();
+            let width = metrics.width();
+            let height = metrics.height();
+            let cols = x as f64 / width;
+            let rows = y as f64 / height;
+            log::trace!("grid {} mouse pressed {} times at {}x{} -> {}x{}", grid, n_press, x, y, cols, rows);
+            let position = (cols.floor() as u32, rows.floor() as u32);
+            if c.current_button() == gdk::BUTTON_PRIMARY && c.current_event_state().contains(gdk::ModifierType::CONTROL_MASK) {
+                if let Some(link) = c.widget().downcast_ref::<VimGridView>().and_then(|view| link_at(view, position)) {
+                    log::info!("grid {} opening link {}", grid, link.url);
+                    gtk::show_uri(None::<&gtk::Window>, &link.url, c.current_event_time());
+                    link_opened.set(true);
+                    return;
+                }
+            }
+            if middle_paste && c.current_button() == gdk::BUTTON_MIDDLE {
+                return;
+            }
+            let modifier = c.current_event_state().to_string();
+            let btn = match c.current_button() {
+                1 => MouseButton::Left,
+                2 => MouseButton::Middle,
+                3 => MouseButton::Right,
+                _ => { return; }
+            };
+            dragging.set(Dragging{ btn, pos: position}.into());
+            EVENT_AGGREGATOR.send(
+                UiCommand::Serial(SerialCommand::MouseButton {
+                    action: MouseAction::Press,
+                    button: btn,
+                    modifier: c.current_event_state(),
+                    grid_id: grid,
+                    position
+                })
+            );
+            log::trace!("grid {} release button {} current_button {} modifier {}", grid, c.button(), c.current_button(), modifier);
+        }),
+    );
+    click_listener.connect_released(
+        glib::clone!(@strong sender, @strong link_opened, @weak view, @weak dragging, @weak metrics => move |c, n_press, x, y| {
+            let grid = view.id();
+            sender.send(app::AppMessage::ShowPointer).unwrap();
+            if link_opened.replace(false) {
+                return;
+            }
+            let metrics = metrics.get();
+            let width = metrics.width();
+            let height = metrics.height();
+            let cols = x as f64 / width;
+            let rows = y as f64 / height;
+            log::trace!("grid {} mouse released {} times at {}x{} -> {}x{}", grid, n_press, x, y, cols, rows);
+            if middle_paste && c.current_button() == gdk::BUTTON_MIDDLE {
+                log::trace!("grid {} pastes primary selection", grid);
+                sender.send(app::AppMessage::Paste(Register::Primary)).unwrap();
+                return;
+            }
+            let modifier = c.current_event_state().to_string();
+            dragging.set(None);
+            let btn = match c.current_button() {
+                1 => MouseButton::Left,
+                2 => MouseButton::Middle,
+                3 => MouseButton::Right,
+                _ => { return; }
+            };
+            EVENT_AGGREGATOR.send(
+                UiCommand::Serial(SerialCommand::MouseButton {
+                    action: MouseAction::Release,
+                    button: btn,
+                    modifier: c.current_event_state(),
+                    grid_id: grid,
+                    position: (cols.floor() as u32, rows.floor() as u32)
+                })
+            );
+            log::trace!("grid {} release button {} current_button {} modifier {}", grid, c.button(), c.current_button(), modifier);
+        }),
+    );
+    view.add_controller(&click_listener);
+
+    let motion_listener = gtk::EventControllerMotion::new();
+    motion_listener.connect_enter(glib::clone!(@weak view => move |_, _, _| {
+        app::GridActived.store(view.id(), atomic::Ordering::Relaxed);
+    }));
+    motion_listener.connect_leave(|c| {
+        if let Some(view) = c.widget().downcast_ref::<VimGridView>() {
+            view.set_cursor_from_name(None);
+            view.set_link(None);
+        }
+    });
+    motion_listener.connect_motion(
+        glib::clone!(@weak view, @weak dragging, @weak metrics => move |c, x, y| {
+            let grid = view.id();
+            log::trace!("cursor motion {} {}", x, y);
+            let metrics = metrics.get();
+            let cols = x as f64 / metrics.width();
+            let rows = y as f64 / metrics.height();
+            let position = (cols.floor() as u32, rows.floor() as u32);
+            *app::PointerPosition.write() = position;
+            if let Some(view) = c.widget().downcast_ref::<VimGridView>() {
+                // links only hovered with Ctrl, as only Ctrl+click opens them.
+                let ctrl = c.current_event_state().contains(gdk::ModifierType::CONTROL_MASK);
+                let link = if ctrl { link_at(view, position) } else { None };
+                view.set_cursor_from_name(link.as_ref().map(|_| "pointer"));
+                view.set_link(link.map(|link| (position.1 as usize, link.col, link.len)));
+            }
+            if let Some(Dragging { btn, pos }) = dragging.get() {
+                log::trace!("Dragging {} from {:?} to {:?}", btn, pos, position);
+                if pos != position {
+                    EVENT_AGGREGATOR.send(
+                        UiCommand::Serial(SerialCommand::Drag {
+                            button: btn,
+                            modifier: c.current_event_state(),
+                            grid_id: grid,
+                            position,
+                        })
+                    );
+                    dragging.set(Dragging { btn, pos: position }.into());
+                }
+            }
+        }),
+    );
+    view.add_controller(&motion_listener);
+}
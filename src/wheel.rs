@@ -0,0 +1,110 @@
+/// Accumulates fractional wheel deltas of touchpads to whole lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WheelAccumulator {
+    delta: f64,
+}
+
+impl WheelAccumulator {
+    /// Adds `delta` scaled by `multiplier`, returns whole lines crossed,
+    /// negative for up. The rest is kept for the next event.
+    pub fn lines(&mut self, delta: f64, multiplier: f64) -> i64 {
+        let delta = delta * multiplier;
+        // turning back drops the rest of the other direction.
+        if delta * self.delta < 0. {
+            self.delta = 0.;
+        }
+        self.delta += delta;
+        let lines = self.delta.trunc();
+        self.delta -= lines;
+        lines as i64
+    }
+
+    pub fn reset(&mut self) {
+        self.delta = 0.;
+    }
+}
+
+/// Accumulators of both axes of wheel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Wheel {
+    vertical: WheelAccumulator,
+    horizontal: WheelAccumulator,
+}
+
+impl Wheel {
+    /// Wheel steps sent to neovim for deltas `dx` and `dy`, as direction and count.
+    pub fn steps(&mut self, dx: f64, dy: f64, multiplier: f64) -> Vec<(&'static str, i64)> {
+        let lines = self.vertical.lines(dy, multiplier);
+        let cols = self.horizontal.lines(dx, multiplier);
+        let mut steps = Vec::with_capacity(2);
+        if lines != 0 {
+            steps.push((if lines < 0 { "up" } else { "down" }, lines.abs()));
+        }
+        if cols != 0 {
+            steps.push((if cols < 0 { "left" } else { "right" }, cols.abs()));
+        }
+        steps
+    }
+}
+
+/// Shift turns vertical wheel to horizontal, wheels without tilting scroll sideways by it.
+pub fn shift_horizontal(dx: f64, dy: f64) -> (f64, f64) {
+    if dx == 0. {
+        (dy, 0.)
+    } else {
+        (dx, dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let mut wheel = WheelAccumulator::default();
+        assert_eq!(wheel.lines(0.4, 1.), 0);
+        assert_eq!(wheel.lines(0.4, 1.), 0);
+        assert_eq!(wheel.lines(0.4, 1.), 1);
+        assert_eq!(wheel.lines(2.5, 1.), 2);
+        // discrete wheel.
+        wheel.reset();
+        assert_eq!(wheel.lines(1., 1.), 1);
+        assert_eq!(wheel.lines(-1., 1.), -1);
+    }
+
+    #[test]
+    fn test_lines_turn_back() {
+        let mut wheel = WheelAccumulator::default();
+        assert_eq!(wheel.lines(0.9, 1.), 0);
+        assert_eq!(wheel.lines(-0.5, 1.), 0);
+        assert_eq!(wheel.lines(-0.6, 1.), -1);
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut wheel = Wheel::default();
+        assert_eq!(wheel.steps(0., 1., 1.), vec![("down", 1)]);
+        assert_eq!(wheel.steps(0., -2., 1.), vec![("up", 2)]);
+        assert_eq!(wheel.steps(-1., 0., 1.), vec![("left", 1)]);
+        assert_eq!(wheel.steps(0.5, 0.5, 1.), vec![]);
+        assert_eq!(wheel.steps(0.5, 0.5, 1.), vec![("down", 1), ("right", 1)]);
+    }
+
+    #[test]
+    fn test_shift_horizontal() {
+        assert_eq!(shift_horizontal(0., 1.), (1., 0.));
+        assert_eq!(shift_horizontal(0., -1.), (-1., 0.));
+        // tilted already.
+        assert_eq!(shift_horizontal(1., 0.), (1., 0.));
+        assert_eq!(shift_horizontal(0.5, 0.2), (0.5, 0.2));
+    }
+
+    #[test]
+    fn test_lines_multiplier() {
+        let mut wheel = WheelAccumulator::default();
+        assert_eq!(wheel.lines(1., 3.), 3);
+        assert_eq!(wheel.lines(0.25, 2.), 0);
+        assert_eq!(wheel.lines(0.25, 2.), 1);
+    }
+}